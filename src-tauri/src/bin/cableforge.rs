@@ -0,0 +1,686 @@
+//! Headless command-line interface for CableForge project files.
+//!
+//! Works directly against the database layer so project files can be edited in
+//! scripts and checked in CI without launching the desktop app.
+//!
+//! Exit codes: 0 on success, 1 when `validate` finds errors (or warnings with
+//! `--deny-warnings`), an import rejects rows or `ampacity` finds undersized cables,
+//! 2 when a command fails.
+
+use anyhow::{anyhow, bail, Context, Result};
+use app_lib::calculations::{
+    creates_feeder_loop, ElectricalCalculator, LoadAnalysisOptions, LoadGroupSummary, VoltageDropSeverity, DEFAULT_CLEARING_TIME,
+    DEFAULT_FEEDER_PATH_LIMIT,
+};
+use app_lib::database::{models::*, Database};
+use app_lib::export::{self, xlsx, Schedule};
+use app_lib::import::{self, ImportAction};
+use app_lib::validation::{CableValidator, ValidationSeverity};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "cableforge", version, about = "Edit and check CableForge project files from the command line")]
+struct Cli {
+    /// Path to the .cfp project file
+    #[arg(short, long, global = true, value_name = "FILE")]
+    project: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
+enum Command {
+    /// Create a new project file
+    Init {
+        /// Project name
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// List, add and update cables
+    #[command(subcommand)]
+    Cables(CableCommand),
+    /// List and set the prospective fault current at each bus
+    #[command(subcommand)]
+    FaultLevels(FaultLevelCommand),
+    /// Run the cable validation rules
+    Validate {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// Treat warnings as failures
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Check every cable's derated ampacity against its load current
+    Ampacity {
+        /// Ambient temperature in °C
+        #[arg(long, default_value_t = 30.0)]
+        ambient: f64,
+    },
+    /// Recalculate and store every cable's voltage drop from its load current
+    VoltageDrop,
+    /// Check the cumulative voltage drop from the source bus to every load
+    FeederDrop {
+        /// Maximum combined feeder and branch voltage drop in percent
+        #[arg(long, default_value_t = DEFAULT_FEEDER_PATH_LIMIT)]
+        limit: f64,
+    },
+    /// Break down the demand load and size the transformer and main breaker
+    LoadAnalysis {
+        /// Project power factor
+        #[arg(long, default_value_t = 0.85)]
+        power_factor: f64,
+        /// Diversity factor (1.0 or more)
+        #[arg(long, default_value_t = 1.0)]
+        diversity: f64,
+        /// Voltage to size the main breaker at; defaults to the highest load voltage
+        #[arg(long)]
+        service_voltage: Option<f64>,
+        /// Service phases (1 or 3); defaults to the most of any load
+        #[arg(long)]
+        service_phases: Option<i32>,
+    },
+    /// Recalculate conduit and tray fill percentages
+    RecalcFills,
+    /// Export every schedule to an Excel workbook with validation highlighting
+    ExportXlsx {
+        /// Output .xlsx file
+        output: PathBuf,
+    },
+    /// Apply a marked-up workbook from `export-xlsx`, recording each change in the revision history
+    ImportXlsx {
+        /// Marked-up .xlsx file
+        file: PathBuf,
+        /// Report the differences without saving anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Clear fields whose cell was blanked instead of keeping them
+        #[arg(long)]
+        clear_blanks: bool,
+    },
+    /// Export schedules as CSV
+    Export {
+        #[arg(value_enum)]
+        schedule: ScheduleKind,
+        /// Output file (or directory for `all`); defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CableCommand {
+    /// List all cables in the project
+    List {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Add a new cable
+    Add {
+        #[arg(long)]
+        tag: String,
+        #[command(flatten)]
+        fields: CableFields,
+    },
+    /// Create or update cables from a CSV file, matching rows by tag
+    Import {
+        /// CSV file whose headers match the cable schedule export
+        file: PathBuf,
+        /// Report what would change without saving anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Clear fields of existing cables whose cell is blank instead of keeping them
+        #[arg(long)]
+        clear_blanks: bool,
+    },
+    /// Update an existing cable, looked up by tag
+    Update {
+        /// Tag of the cable to update
+        tag: String,
+        /// Rename the cable
+        #[arg(long)]
+        new_tag: Option<String>,
+        #[command(flatten)]
+        fields: CableFields,
+    },
+    /// Set the upstream feeder a cable is supplied from
+    SetParent {
+        /// Tag of the downstream cable
+        tag: String,
+        /// Tag of the feeder cable; omit to clear
+        #[arg(long)]
+        parent: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FaultLevelCommand {
+    /// List fault levels by bus
+    List,
+    /// Create or replace the fault level of a bus
+    Set {
+        /// Bus or source name, matched against each cable's from equipment
+        bus: String,
+        /// Prospective fault current in kA
+        #[arg(long)]
+        ka: f64,
+        /// Protective device clearing time in seconds
+        #[arg(long, default_value_t = DEFAULT_CLEARING_TIME)]
+        clearing_time: f64,
+    },
+}
+
+#[derive(Args)]
+struct CableFields {
+    #[arg(long)]
+    description: Option<String>,
+    #[arg(long)]
+    function: Option<String>,
+    #[arg(long)]
+    voltage: Option<f64>,
+    #[arg(long)]
+    current: Option<f64>,
+    #[arg(long)]
+    cable_type: Option<String>,
+    #[arg(long)]
+    size: Option<String>,
+    #[arg(long)]
+    cores: Option<i32>,
+    #[arg(long)]
+    segregation_class: Option<String>,
+    #[arg(long)]
+    from_location: Option<String>,
+    #[arg(long)]
+    from_equipment: Option<String>,
+    #[arg(long)]
+    to_location: Option<String>,
+    #[arg(long)]
+    to_equipment: Option<String>,
+    /// Length in feet
+    #[arg(long)]
+    length: Option<f64>,
+    #[arg(long)]
+    spare_percentage: Option<f64>,
+    #[arg(long)]
+    route: Option<String>,
+    #[arg(long)]
+    manufacturer: Option<String>,
+    #[arg(long)]
+    part_number: Option<String>,
+    /// Outer diameter in mm
+    #[arg(long)]
+    outer_diameter: Option<f64>,
+    #[arg(long)]
+    notes: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ScheduleKind {
+    Cables,
+    IoPoints,
+    Loads,
+    Conduits,
+    Trays,
+    All,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    let path = cli.project.ok_or_else(|| anyhow!("no project file given (use --project <FILE>)"))?;
+
+    if let Command::Init { name } = cli.command {
+        if path.exists() {
+            bail!("{} already exists", path.display());
+        }
+        let db = Database::new(&path)?;
+        let project = match name {
+            Some(name) => {
+                let now = chrono::Utc::now();
+                db.insert_project(&Project {
+                    id: None,
+                    name,
+                    description: None,
+                    client: None,
+                    engineer: None,
+                    major_revision: "Draft".to_string(),
+                    minor_revision: 0,
+                    created_at: now,
+                    updated_at: now,
+                })?
+            }
+            None => db.create_default_project()?,
+        };
+        println!("Created project '{}' in {}", project.name, path.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let (db, project_id) = open_project(&path)?;
+
+    match cli.command {
+        Command::Init { .. } => unreachable!(),
+        Command::Cables(CableCommand::List { format }) => {
+            let cables = db.get_cables(project_id)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&cables)?),
+                OutputFormat::Table => print_schedule(&export::cable_schedule(&cables), &[0, 2, 3, 6, 7, 9, 11, 13]),
+            }
+        }
+        Command::Cables(CableCommand::Add { tag, fields }) => {
+            let cable = db.insert_cable(project_id, &fields.into_new_cable(tag))?;
+            println!("Added cable {} (id {})", cable.tag, cable.id.unwrap_or_default());
+        }
+        Command::Cables(CableCommand::Import { file, dry_run, clear_blanks }) => {
+            let preview = import::preview_csv(File::open(&file)?)?;
+            let rows = import::read_csv(File::open(&file)?, &preview.suggested_mappings)?;
+            let report = db.import_cables(project_id, &rows, dry_run, clear_blanks)?;
+
+            for row in report.rows.iter().filter(|row| row.action == ImportAction::Error) {
+                eprintln!("row {}: {}", row.row, row.errors.join("; "));
+            }
+            println!(
+                "{} created, {} updated, {} failed{}",
+                report.created,
+                report.updated,
+                report.failed,
+                if report.committed { "" } else { " (nothing saved)" }
+            );
+            if report.failed > 0 {
+                return Ok(ExitCode::from(1));
+            }
+        }
+        Command::Cables(CableCommand::Update { tag, new_tag, fields }) => {
+            let cable = find_cable(&db, project_id, &tag)?;
+            let updated = db.update_cable(cable.id.unwrap_or_default(), &fields.into_update_cable(new_tag))?;
+            println!("Updated cable {}", updated.tag);
+        }
+        Command::Cables(CableCommand::SetParent { tag, parent }) => {
+            let cable = find_cable(&db, project_id, &tag)?;
+            let cable_id = cable.id.unwrap_or_default();
+            let parent_id = match parent {
+                Some(parent_tag) => {
+                    let parent = find_cable(&db, project_id, &parent_tag)?;
+                    let parent_id = parent.id.unwrap_or_default();
+                    if creates_feeder_loop(cable_id, parent_id, &db.get_cables(project_id)?) {
+                        bail!("{} cannot be fed from {}: the path would loop", tag, parent_tag);
+                    }
+                    Some(parent_id)
+                }
+                None => None,
+            };
+            db.set_cable_parent(cable_id, parent_id)?;
+            println!("Updated feeder of {}", tag);
+        }
+        Command::FaultLevels(FaultLevelCommand::List) => {
+            for fault_level in db.get_fault_levels(project_id)? {
+                println!("{:<16} {:>8.1} kA  {:.3} s", fault_level.bus, fault_level.fault_current_ka, fault_level.clearing_time);
+            }
+        }
+        Command::FaultLevels(FaultLevelCommand::Set { bus, ka, clearing_time }) => {
+            let existing = db.get_fault_levels(project_id)?
+                .into_iter()
+                .find(|f| f.bus.eq_ignore_ascii_case(&bus));
+            match existing {
+                Some(fault_level) => {
+                    db.update_fault_level(fault_level.id.unwrap_or_default(), &UpdateFaultLevel {
+                        bus: None,
+                        fault_current_ka: Some(ka),
+                        clearing_time: Some(clearing_time),
+                        notes: None,
+                    })?;
+                }
+                None => {
+                    db.insert_fault_level(project_id, &NewFaultLevel {
+                        bus: bus.clone(),
+                        fault_current_ka: ka,
+                        clearing_time: Some(clearing_time),
+                        notes: None,
+                    })?;
+                }
+            }
+            println!("Set fault level of {} to {:.1} kA", bus, ka);
+        }
+        Command::Validate { format, deny_warnings } => {
+            let summary = CableValidator::for_project(&db, project_id)?.validate_project(&db, project_id)?;
+
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+                OutputFormat::Table => {
+                    for result in summary.results.iter().filter(|r| !r.suppressed) {
+                        let severity = match result.severity {
+                            ValidationSeverity::Error => "ERROR",
+                            ValidationSeverity::Warning => "WARN",
+                            ValidationSeverity::Info => "INFO",
+                        };
+                        println!("{:<5} {:<12} {}", severity, result.entity_tag, result.message);
+                    }
+                    println!(
+                        "{} cables checked: {} errors, {} warnings, {} info, {} overridden",
+                        summary.total_cables, summary.error_count, summary.warning_count, summary.info_count,
+                        summary.suppressed_count
+                    );
+                }
+            }
+
+            if summary.error_count > 0 || (deny_warnings && summary.warning_count > 0) {
+                return Ok(ExitCode::from(1));
+            }
+        }
+        Command::Ampacity { ambient } => {
+            let cables = db.get_cables(project_id)?;
+            let loads = db.get_loads(project_id)?;
+            let library = db.get_cable_library_items(None, None)?;
+            let calculator = ElectricalCalculator::new();
+            let mut failures = 0;
+
+            for cable in &cables {
+                let check = calculator.check_cable_ampacity(cable, &cables, &loads, &library, ambient);
+                match (&check.result, &check.skipped_reason) {
+                    (_, Some(reason)) => println!("{:<12} skipped: {}", check.cable_tag, reason),
+                    (Some(result), None) => {
+                        let adequate = result.is_adequate.unwrap_or(true);
+                        if !adequate {
+                            failures += 1;
+                        }
+                        println!(
+                            "{:<12} {:>7.1} A load / {:>7.1} A rated  {}",
+                            check.cable_tag,
+                            result.load_current.unwrap_or_default(),
+                            result.derated_ampacity,
+                            if adequate { "OK" } else { "UNDERSIZED" }
+                        );
+                    }
+                    (None, None) => {}
+                }
+            }
+            if failures > 0 {
+                return Ok(ExitCode::from(1));
+            }
+        }
+        Command::VoltageDrop => {
+            let report = db.recalculate_voltage_drops(project_id)?;
+            let mut failures = 0;
+
+            for check in &report.checks {
+                match (&check.result, &check.skipped_reason) {
+                    (Some(result), _) => {
+                        if matches!(result.severity, VoltageDropSeverity::Error) {
+                            failures += 1;
+                        }
+                        println!(
+                            "{:<12} {:>6.2}% ({:.1} V)  {:?}",
+                            check.cable_tag, result.voltage_drop_percentage, result.voltage_drop_volts, result.severity
+                        );
+                    }
+                    (None, Some(reason)) => println!("{:<12} skipped: {}", check.cable_tag, reason),
+                    (None, None) => {}
+                }
+            }
+            eprintln!("{} updated, {} skipped", report.updated, report.skipped);
+            if failures > 0 {
+                return Ok(ExitCode::from(1));
+            }
+        }
+        Command::FeederDrop { limit } => {
+            let cables = db.get_cables(project_id)?;
+            let loads = db.get_loads(project_id)?;
+            let library = db.get_cable_library_items(None, None)?;
+            let raceways = db.cable_raceway_types(project_id)?;
+            let calculator = ElectricalCalculator::new();
+            let mut failures = 0;
+
+            for load in &loads {
+                let check = calculator.check_feeder_path(load, &cables, &loads, &library, &raceways, limit);
+                match (check.cumulative_percentage, &check.skipped_reason) {
+                    (Some(total), _) => {
+                        if check.exceeds_limit {
+                            failures += 1;
+                        }
+                        println!(
+                            "{:<12} {:>6.2}%  {}  {}",
+                            check.load_tag,
+                            total,
+                            if check.exceeds_limit { "OVER LIMIT" } else { "OK" },
+                            check.cable_tags.join(" > ")
+                        );
+                    }
+                    (None, Some(reason)) => println!("{:<12} skipped: {}", check.load_tag, reason),
+                    (None, None) => {}
+                }
+            }
+            if failures > 0 {
+                return Ok(ExitCode::from(1));
+            }
+        }
+        Command::LoadAnalysis { power_factor, diversity, service_voltage, service_phases } => {
+            let options = LoadAnalysisOptions { power_factor, diversity_factor: diversity, service_voltage, service_phases };
+            let report = ElectricalCalculator::new()
+                .analyze_loads(&db.get_loads(project_id)?, &db.get_cables(project_id)?, &options)
+                .map_err(|e| anyhow!(e))?;
+
+            for (title, groups) in [("Voltage", &report.by_voltage), ("Load type", &report.by_load_type), ("Bus", &report.by_bus)] {
+                println!("{:<16} {:>5} {:>10} {:>10} {:>10}", title, "Loads", "Conn kW", "Dem kW", "Design kVA");
+                for group in groups {
+                    print_load_group(group);
+                }
+                println!();
+            }
+            print_load_group(&report.totals);
+            if !report.loads_without_power.is_empty() {
+                println!("No power rating: {}", report.loads_without_power.join(", "));
+            }
+            match report.recommended_transformer_kva {
+                Some(kva) => println!("Transformer: {} kVA", kva),
+                None if report.totals.design_kva > 0.0 => println!("Transformer: larger than the standard ratings"),
+                None => {}
+            }
+            if let (Some(voltage), Some(current), Some(breaker)) =
+                (report.service_voltage, report.service_current, report.recommended_main_breaker)
+            {
+                println!(
+                    "Main breaker: {:.0} A ({:.0} A at {:.0} V, {}-phase)",
+                    breaker, current, voltage, report.service_phases
+                );
+            }
+        }
+        Command::RecalcFills => {
+            db.recalculate_all_fills(project_id)?;
+            for conduit in db.get_conduits(project_id)? {
+                println!("{:<12} {:>6.1}% (max {:.0}%)", conduit.tag, conduit.fill_percentage, conduit.max_fill_percentage);
+            }
+            for tray in db.get_trays(project_id)? {
+                println!("{:<12} {:>6.1}% (max {:.0}%)", tray.tag, tray.fill_percentage, tray.max_fill_percentage);
+            }
+        }
+        Command::ExportXlsx { output } => {
+            let (title, sheets) = xlsx::project_sheets(&db, project_id)?;
+            xlsx::build_workbook(&title, &sheets)?.save(&output)?;
+            println!("Wrote {}", output.display());
+        }
+        Command::ImportXlsx { file, dry_run, clear_blanks } => {
+            let rows = import::xlsx::diff_workbook(io::BufReader::new(File::open(&file)?), &db, project_id, clear_blanks)?;
+            let report = db.apply_markup(project_id, rows, dry_run)?;
+
+            for row in &report.rows {
+                for change in &row.changes {
+                    println!(
+                        "{} row {} {}: {} '{}' -> '{}'",
+                        row.sheet, row.row, row.tag, change.column,
+                        change.old_value.as_deref().unwrap_or_default(),
+                        change.new_value.as_deref().unwrap_or_default()
+                    );
+                }
+                for message in row.warnings.iter().chain(&row.errors) {
+                    eprintln!("{} row {} {}: {}", row.sheet, row.row, row.tag, message);
+                }
+            }
+            println!(
+                "{} rows updated, {} fields changed, {} failed{}",
+                report.rows_updated,
+                report.fields_changed,
+                report.failed,
+                if report.committed { "" } else { " (nothing saved)" }
+            );
+            if report.failed > 0 {
+                return Ok(ExitCode::from(1));
+            }
+        }
+        Command::Export { schedule, output } => export_schedules(&db, project_id, schedule, output)?,
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn open_project(path: &Path) -> Result<(Database, i64)> {
+    if !path.exists() {
+        bail!("project file {} does not exist", path.display());
+    }
+    let db = Database::new(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let project_id = db.get_projects()?
+        .first()
+        .and_then(|p| p.id)
+        .ok_or_else(|| anyhow!("no project found in {}", path.display()))?;
+    Ok((db, project_id))
+}
+
+fn find_cable(db: &Database, project_id: i64, tag: &str) -> Result<Cable> {
+    db.get_cables(project_id)?
+        .into_iter()
+        .find(|c| c.tag.eq_ignore_ascii_case(tag))
+        .ok_or_else(|| anyhow!("no cable with tag '{}'", tag))
+}
+
+fn export_schedules(db: &Database, project_id: i64, kind: ScheduleKind, output: Option<PathBuf>) -> Result<()> {
+    let cables = db.get_cables(project_id)?;
+    let build = |kind: ScheduleKind| -> Result<Schedule> {
+        Ok(match kind {
+            ScheduleKind::Cables => export::cable_schedule(&cables),
+            ScheduleKind::IoPoints => export::io_point_schedule(&db.get_io_points(project_id)?, &cables),
+            ScheduleKind::Loads => export::load_schedule(&db.get_loads(project_id)?, &cables),
+            ScheduleKind::Conduits => export::conduit_schedule(&db.get_conduits(project_id)?),
+            ScheduleKind::Trays => export::tray_schedule(&db.get_trays(project_id)?),
+            ScheduleKind::All => unreachable!(),
+        })
+    };
+
+    if kind != ScheduleKind::All {
+        let schedule = build(kind)?;
+        return match output {
+            Some(path) => Ok(export::write_csv(&schedule, File::create(&path)?)?),
+            None => Ok(export::write_csv(&schedule, io::stdout().lock())?),
+        };
+    }
+
+    let dir = output.ok_or_else(|| anyhow!("exporting all schedules requires --output <DIR>"))?;
+    std::fs::create_dir_all(&dir)?;
+    for kind in [ScheduleKind::Cables, ScheduleKind::IoPoints, ScheduleKind::Loads, ScheduleKind::Conduits, ScheduleKind::Trays] {
+        let schedule = build(kind)?;
+        let path = dir.join(format!("{}.csv", schedule.name.to_lowercase().replace(' ', "-")));
+        export::write_csv(&schedule, File::create(&path)?)?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Print selected schedule columns as a fixed-width text table
+fn print_schedule(schedule: &Schedule, columns: &[usize]) {
+    let cells: Vec<Vec<String>> = schedule.rows.iter()
+        .map(|row| columns.iter().map(|&i| row[i].to_display_string()).collect())
+        .collect();
+    let widths: Vec<usize> = columns.iter().enumerate()
+        .map(|(n, &i)| cells.iter().map(|row| row[n].len()).chain([schedule.columns[i].len()]).max().unwrap_or(0))
+        .collect();
+
+    let mut out = io::stdout().lock();
+    let header: Vec<String> = columns.iter().zip(&widths).map(|(&i, &w)| format!("{:<w$}", schedule.columns[i])).collect();
+    let _ = writeln!(out, "{}", header.join("  ").trim_end());
+    for row in cells {
+        let line: Vec<String> = row.iter().zip(&widths).map(|(cell, &w)| format!("{:<w$}", cell)).collect();
+        let _ = writeln!(out, "{}", line.join("  ").trim_end());
+    }
+}
+
+fn print_load_group(group: &LoadGroupSummary) {
+    println!(
+        "{:<16} {:>5} {:>10.1} {:>10.1} {:>10.1}",
+        group.name, group.load_count, group.connected_kw, group.demand_kw, group.design_kva
+    );
+}
+
+impl CableFields {
+    fn into_new_cable(self, tag: String) -> NewCable {
+        NewCable {
+            tag,
+            description: self.description,
+            function: self.function,
+            voltage: self.voltage,
+            current: self.current,
+            cable_type: self.cable_type,
+            size: self.size,
+            cores: self.cores,
+            segregation_class: self.segregation_class,
+            from_location: self.from_location,
+            from_equipment: self.from_equipment,
+            to_location: self.to_location,
+            to_equipment: self.to_equipment,
+            length: self.length,
+            spare_percentage: self.spare_percentage,
+            route: self.route,
+            manufacturer: self.manufacturer,
+            part_number: self.part_number,
+            outer_diameter: self.outer_diameter,
+            tray_id: None,
+            conduit_id: None,
+            notes: self.notes,
+        }
+    }
+
+    fn into_update_cable(self, tag: Option<String>) -> UpdateCable {
+        UpdateCable {
+            tag,
+            description: self.description,
+            function: self.function,
+            voltage: self.voltage,
+            current: self.current,
+            cable_type: self.cable_type,
+            size: self.size,
+            cores: self.cores,
+            segregation_class: self.segregation_class,
+            from_location: self.from_location,
+            from_equipment: self.from_equipment,
+            to_location: self.to_location,
+            to_equipment: self.to_equipment,
+            length: self.length,
+            spare_percentage: self.spare_percentage,
+            route: self.route,
+            manufacturer: self.manufacturer,
+            part_number: self.part_number,
+            outer_diameter: self.outer_diameter,
+            voltage_drop_percentage: None,
+            segregation_warning: None,
+            tray_id: None,
+            conduit_id: None,
+            notes: self.notes,
+        }
+    }
+}
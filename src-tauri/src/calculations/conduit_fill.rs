@@ -0,0 +1,313 @@
+/**
+ * NEC Chapter 9 Conduit Fill
+ * Table 4 internal areas by conduit type and trade size, Table 1 fill limits by cable count,
+ * jam ratio checks and minimum trade size selection
+ */
+
+use crate::database::models::{Cable, Conduit};
+use serde::{Deserialize, Serialize};
+
+const SQ_IN_TO_MM2: f64 = 645.16;
+const IN_TO_MM: f64 = 25.4;
+
+/// Conduit types with a Table 4 article
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConduitKind {
+    Emt,   // Article 358
+    Rmc,   // Article 344
+    Imc,   // Article 342
+    Pvc40, // Article 352, Schedule 40
+    Pvc80, // Article 352, Schedule 80
+    Fmc,   // Article 348
+    Lfmc,  // Article 350
+}
+
+impl ConduitKind {
+    /// Match `Conduit.type` as entered in the schedule, e.g. "EMT", "RIGID_STEEL", "PVC Sch 80".
+    /// Plain "PVC" is taken as Schedule 40.
+    pub fn parse(value: &str) -> Option<Self> {
+        let key: String = value.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_uppercase();
+        match key.as_str() {
+            "EMT" => Some(Self::Emt),
+            "RMC" | "GRC" | "RIGID" | "RIGIDSTEEL" | "RIGIDALUMINUM" => Some(Self::Rmc),
+            "IMC" => Some(Self::Imc),
+            "PVC" | "PVC40" | "PVCSCH40" | "PVCSCHEDULE40" | "RNC" => Some(Self::Pvc40),
+            "PVC80" | "PVCSCH80" | "PVCSCHEDULE80" => Some(Self::Pvc80),
+            "FMC" | "FLEXIBLE" => Some(Self::Fmc),
+            "LFMC" | "LIQUIDTIGHT" => Some(Self::Lfmc),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Emt => "EMT",
+            Self::Rmc => "RMC",
+            Self::Imc => "IMC",
+            Self::Pvc40 => "PVC Schedule 40",
+            Self::Pvc80 => "PVC Schedule 80",
+            Self::Fmc => "FMC",
+            Self::Lfmc => "LFMC",
+        }
+    }
+
+    // NEC Chapter 9 Table 4: (trade size, metric designator, nominal internal diameter in, total area in²)
+    fn table(&self) -> &'static [(&'static str, u16, f64, f64)] {
+        match self {
+            Self::Emt => &[
+                ("1/2", 16, 0.622, 0.304), ("3/4", 21, 0.824, 0.533), ("1", 27, 1.049, 0.864),
+                ("1-1/4", 35, 1.380, 1.496), ("1-1/2", 41, 1.610, 2.036), ("2", 53, 2.067, 3.356),
+                ("2-1/2", 63, 2.731, 5.858), ("3", 78, 3.356, 8.846), ("3-1/2", 91, 3.834, 11.545),
+                ("4", 103, 4.334, 14.753),
+            ],
+            Self::Rmc => &[
+                ("1/2", 16, 0.632, 0.314), ("3/4", 21, 0.836, 0.549), ("1", 27, 1.063, 0.887),
+                ("1-1/4", 35, 1.394, 1.526), ("1-1/2", 41, 1.624, 2.071), ("2", 53, 2.083, 3.408),
+                ("2-1/2", 63, 2.489, 4.866), ("3", 78, 3.090, 7.499), ("3-1/2", 91, 3.570, 10.010),
+                ("4", 103, 4.050, 12.882), ("5", 129, 5.073, 20.212), ("6", 155, 6.093, 29.158),
+            ],
+            Self::Imc => &[
+                ("1/2", 16, 0.660, 0.342), ("3/4", 21, 0.864, 0.586), ("1", 27, 1.105, 0.959),
+                ("1-1/4", 35, 1.448, 1.647), ("1-1/2", 41, 1.683, 2.225), ("2", 53, 2.150, 3.630),
+                ("2-1/2", 63, 2.557, 5.135), ("3", 78, 3.176, 7.922), ("3-1/2", 91, 3.671, 10.584),
+                ("4", 103, 4.166, 13.631),
+            ],
+            Self::Pvc40 => &[
+                ("1/2", 16, 0.602, 0.285), ("3/4", 21, 0.804, 0.508), ("1", 27, 1.029, 0.832),
+                ("1-1/4", 35, 1.360, 1.453), ("1-1/2", 41, 1.590, 1.986), ("2", 53, 2.047, 3.291),
+                ("2-1/2", 63, 2.445, 4.695), ("3", 78, 3.042, 7.268), ("3-1/2", 91, 3.521, 9.737),
+                ("4", 103, 3.998, 12.554), ("5", 129, 5.016, 19.761), ("6", 155, 6.031, 28.567),
+            ],
+            Self::Pvc80 => &[
+                ("1/2", 16, 0.526, 0.217), ("3/4", 21, 0.722, 0.409), ("1", 27, 0.936, 0.688),
+                ("1-1/4", 35, 1.255, 1.237), ("1-1/2", 41, 1.476, 1.711), ("2", 53, 1.913, 2.874),
+                ("2-1/2", 63, 2.290, 4.119), ("3", 78, 2.864, 6.442), ("3-1/2", 91, 3.326, 8.688),
+                ("4", 103, 3.786, 11.258), ("5", 129, 4.768, 17.855), ("6", 155, 5.709, 25.598),
+            ],
+            Self::Fmc => &[
+                ("3/8", 12, 0.384, 0.116), ("1/2", 16, 0.635, 0.317), ("3/4", 21, 0.824, 0.533),
+                ("1", 27, 1.020, 0.817), ("1-1/4", 35, 1.275, 1.277), ("1-1/2", 41, 1.538, 1.858),
+                ("2", 53, 2.040, 3.269), ("2-1/2", 63, 2.500, 4.909), ("3", 78, 3.000, 7.069),
+                ("3-1/2", 91, 3.500, 9.621), ("4", 103, 4.000, 12.566),
+            ],
+            Self::Lfmc => &[
+                ("3/8", 12, 0.494, 0.192), ("1/2", 16, 0.632, 0.314), ("3/4", 21, 0.830, 0.541),
+                ("1", 27, 1.054, 0.873), ("1-1/4", 35, 1.395, 1.528), ("1-1/2", 41, 1.588, 1.981),
+                ("2", 53, 2.033, 3.246), ("2-1/2", 63, 2.493, 4.881), ("3", 78, 3.085, 7.475),
+                ("3-1/2", 91, 3.520, 9.731), ("4", 103, 4.020, 12.692),
+            ],
+        }
+    }
+}
+
+/// One Table 4 row, converted to millimeters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConduitTradeSize {
+    pub kind: ConduitKind,
+    pub trade_size: String,
+    pub metric_designator: u16,
+    pub internal_diameter: f64, // mm
+    pub internal_area: f64, // mm², 100% of the conduit
+}
+
+/// Every trade size of a conduit type, smallest first
+pub fn conduit_trade_sizes(kind: ConduitKind) -> Vec<ConduitTradeSize> {
+    kind.table().iter()
+        .map(|&(trade_size, metric_designator, diameter, area)| ConduitTradeSize {
+            kind,
+            trade_size: trade_size.to_string(),
+            metric_designator,
+            internal_diameter: diameter * IN_TO_MM,
+            internal_area: area * SQ_IN_TO_MM2,
+        })
+        .collect()
+}
+
+/// Table 4 row for `Conduit.type` and `size`. Sizes may be trade sizes ("1-1/2", "1 1/2\"")
+/// or metric designators ("41").
+pub fn lookup_trade_size(conduit_type: &str, size: &str) -> Option<ConduitTradeSize> {
+    let kind = ConduitKind::parse(conduit_type)?;
+    let size = size.trim().trim_end_matches('"').trim_end_matches("in").trim().replace(' ', "-");
+    conduit_trade_sizes(kind).into_iter()
+        .find(|row| row.trade_size == size || row.metric_designator.to_string() == size)
+}
+
+/// Maximum fill from NEC Chapter 9 Table 1: 53% for one cable, 31% for two, 40% for three or
+/// more. Note 4 allows 60% in nipples of 600 mm (24 in) or less.
+pub fn max_fill_percentage(cable_count: usize, length_m: Option<f64>) -> (f64, &'static str) {
+    if matches!(length_m, Some(length) if length > 0.0 && length <= 0.6096) {
+        return (60.0, "NEC Chapter 9, Note 4 (nipple)");
+    }
+    match cable_count {
+        1 => (53.0, "NEC Chapter 9, Table 1 (1 cable)"),
+        2 => (31.0, "NEC Chapter 9, Table 1 (2 cables)"),
+        _ => (40.0, "NEC Chapter 9, Table 1 (over 2 cables)"),
+    }
+}
+
+/// Jam ratio 1.05 × ID / OD for three cables, using the largest cable. A ratio between 2.8
+/// and 3.2 lets the cables jam when pulled around bends (Table 1, Informational Note 2).
+pub fn jam_ratio(internal_diameter: f64, cable_diameters: &[f64]) -> Option<f64> {
+    if cable_diameters.len() != 3 {
+        return None;
+    }
+    let largest = cable_diameters.iter().copied().fold(0.0, f64::max);
+    if largest <= 0.0 || internal_diameter <= 0.0 {
+        return None;
+    }
+    Some(1.05 * internal_diameter / largest)
+}
+
+pub fn is_jam_risk(ratio: f64) -> bool {
+    (2.8..=3.2).contains(&ratio)
+}
+
+fn cable_area(diameter: f64) -> f64 {
+    std::f64::consts::PI * (diameter / 2.0).powi(2)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConduitFillCheck {
+    pub internal_area: Option<f64>, // mm²; None when neither Table 4 nor the diameter gives it
+    pub area_source: String,
+    pub cable_count: usize,
+    pub cable_area: f64, // mm²
+    pub fill_percentage: f64,
+    pub max_fill_percentage: f64,
+    pub rule: String,
+    pub jam_ratio: Option<f64>,
+    pub jam_risk: bool,
+    pub cables_without_diameter: Vec<String>, // not counted in the area or the cable count
+    pub passes: bool,
+}
+
+/// Fill of a conduit against its Table 1 limit. The Table 4 area for the conduit's type and
+/// size is used when known, otherwise the circle of the entered internal diameter.
+pub fn check_conduit_fill(
+    conduit_type: Option<&str>,
+    size: Option<&str>,
+    internal_diameter: Option<f64>,
+    length_m: Option<f64>,
+    cable_diameters: &[f64],
+) -> ConduitFillCheck {
+    let table_row = match (conduit_type, size) {
+        (Some(conduit_type), Some(size)) => lookup_trade_size(conduit_type, size),
+        _ => None,
+    };
+    let (internal_area, diameter, area_source) = match (&table_row, internal_diameter) {
+        (Some(row), _) => (
+            Some(row.internal_area),
+            Some(row.internal_diameter),
+            format!("NEC Chapter 9, Table 4 ({} {})", row.kind.label(), row.trade_size),
+        ),
+        (None, Some(diameter)) if diameter > 0.0 => (Some(cable_area(diameter)), Some(diameter), "Internal diameter".to_string()),
+        _ => (None, None, "Unknown".to_string()),
+    };
+
+    let total_cable_area: f64 = cable_diameters.iter().map(|&d| cable_area(d)).sum();
+    let fill_percentage = internal_area.map(|area| total_cable_area / area * 100.0).unwrap_or(0.0);
+    let (max_fill_percentage, rule) = max_fill_percentage(cable_diameters.len(), length_m);
+    let jam_ratio = diameter.and_then(|d| jam_ratio(d, cable_diameters));
+    let jam_risk = jam_ratio.is_some_and(is_jam_risk);
+
+    ConduitFillCheck {
+        internal_area,
+        area_source,
+        cable_count: cable_diameters.len(),
+        cable_area: total_cable_area,
+        fill_percentage,
+        max_fill_percentage,
+        rule: rule.to_string(),
+        jam_ratio,
+        jam_risk,
+        cables_without_diameter: Vec::new(),
+        passes: internal_area.is_some() && fill_percentage <= max_fill_percentage && !jam_risk,
+    }
+}
+
+/// Fill of a conduit for the cables routed through it. Cables without an outer diameter are
+/// listed rather than counted.
+pub fn check_conduit_cables(conduit: &Conduit, cables: &[&Cable]) -> ConduitFillCheck {
+    let mut diameters = Vec::new();
+    let mut cables_without_diameter = Vec::new();
+    for cable in cables {
+        match cable.outer_diameter {
+            Some(diameter) if diameter > 0.0 => diameters.push(diameter),
+            _ => cables_without_diameter.push(cable.tag.clone()),
+        }
+    }
+
+    let mut check = check_conduit_fill(
+        conduit.r#type.as_deref(),
+        conduit.size.as_deref(),
+        conduit.internal_diameter,
+        conduit.length,
+        &diameters,
+    );
+    check.cables_without_diameter = cables_without_diameter;
+    check
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConduitSizing {
+    pub trade_size: ConduitTradeSize,
+    pub check: ConduitFillCheck,
+}
+
+/// Smallest trade size of a conduit type that holds the cables within the Table 1 limit
+/// without a jam risk, or None when even the largest size is too small
+pub fn minimum_trade_size(kind: ConduitKind, cable_diameters: &[f64]) -> Option<ConduitSizing> {
+    conduit_trade_sizes(kind).into_iter().find_map(|row| {
+        let check = check_conduit_fill(None, None, Some(row.internal_diameter), None, cable_diameters);
+        // Table 4 area rather than the circle of the nominal diameter
+        let fill_percentage = check.cable_area / row.internal_area * 100.0;
+        if fill_percentage <= check.max_fill_percentage && !check.jam_risk {
+            let check = ConduitFillCheck {
+                internal_area: Some(row.internal_area),
+                area_source: format!("NEC Chapter 9, Table 4 ({} {})", kind.label(), row.trade_size),
+                fill_percentage,
+                passes: true,
+                ..check
+            };
+            Some(ConduitSizing { trade_size: row, check })
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_4_lookup_and_minimum_trade_size() {
+        let row = lookup_trade_size("RIGID_STEEL", "2").unwrap();
+        assert!((row.internal_diameter - 52.9).abs() < 0.01);
+        assert!((row.internal_area - 3.408 * 645.16).abs() < 1e-6);
+        assert_eq!(lookup_trade_size("PVC Sch 80", "1 1/2\"").unwrap().trade_size, "1-1/2");
+        assert_eq!(lookup_trade_size("EMT", "41").unwrap().trade_size, "1-1/2");
+        assert!(lookup_trade_size("Duct bank", "2").is_none());
+
+        assert_eq!(max_fill_percentage(1, None).0, 53.0);
+        assert_eq!(max_fill_percentage(2, Some(30.0)).0, 31.0);
+        assert_eq!(max_fill_percentage(5, None).0, 40.0);
+        assert_eq!(max_fill_percentage(5, Some(0.5)).0, 60.0);
+
+        // Three 10 mm cables: 235.6 mm² needs 589 mm² at 40%, so 1" EMT (557.4 mm²) is too small.
+        // 1-1/4" EMT holds them, but 1.05 × 35.05 / 10 = 3.68 is clear of the jam range.
+        let sizing = minimum_trade_size(ConduitKind::Emt, &[10.0, 10.0, 10.0]).unwrap();
+        assert_eq!(sizing.trade_size.trade_size, "1-1/4");
+        assert!(sizing.check.passes && !sizing.check.jam_risk);
+
+        // Three 12 mm cables fit 1-1/4" EMT by area, but 1.05 × 35.05 / 12 = 3.07 can jam
+        let check = check_conduit_fill(Some("EMT"), Some("1-1/4"), None, None, &[12.0, 12.0, 12.0]);
+        assert!(check.fill_percentage < 40.0);
+        assert!(check.jam_risk && !check.passes);
+        assert_eq!(minimum_trade_size(ConduitKind::Emt, &[12.0, 12.0, 12.0]).unwrap().trade_size.trade_size, "1-1/2");
+
+        // A single cable gets 53%, so a smaller conduit fits than for two
+        assert_eq!(minimum_trade_size(ConduitKind::Emt, &[18.0]).unwrap().trade_size.trade_size, "1");
+        assert_eq!(minimum_trade_size(ConduitKind::Emt, &[18.0, 5.0]).unwrap().trade_size.trade_size, "1-1/4");
+    }
+}
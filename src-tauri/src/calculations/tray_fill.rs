@@ -0,0 +1,480 @@
+/**
+ * NEC 392.22 Cable Tray Fill
+ * Selects the fill method from the tray type and the function, construction and conductor
+ * size of the cables in the tray
+ */
+
+use super::{parse_conductor_size, ElectricalCalculator};
+use crate::database::models::{Cable, Tray};
+use serde::{Deserialize, Serialize};
+
+const MM_PER_IN: f64 = 25.4;
+const CMIL_4_0: f64 = 211_600.0;
+const CMIL_1_0: f64 = 105_600.0;
+const CMIL_250: f64 = 250_000.0;
+const CMIL_1000: f64 = 1_000_000.0;
+const MAX_CONTROL_DEPTH_IN: f64 = 6.0;
+const FALLBACK_MAX_FILL: f64 = 50.0;
+
+// Tray inside widths in inches with their metric equivalents in mm, as listed in the NEC tables
+const TABLE_WIDTHS: [(f64, f64); 7] = [
+    (6.0, 150.0), (9.0, 225.0), (12.0, 300.0), (18.0, 450.0), (24.0, 600.0), (30.0, 750.0), (36.0, 900.0),
+];
+// Table 392.22(A) Column 1, ladder or ventilated trough, in² per width above
+const TABLE_A_COLUMN_1: [f64; 7] = [7.0, 10.5, 14.0, 21.0, 28.0, 35.0, 42.0];
+// Table 392.22(A) Column 3, solid bottom
+const TABLE_A_COLUMN_3: [f64; 7] = [5.5, 8.0, 11.0, 16.5, 22.0, 27.5, 33.0];
+// Table 392.22(B)(1) Column 1, single conductor cables 250 through 900 kcmil
+const TABLE_B1_COLUMN_1: [f64; 7] = [6.5, 9.5, 13.0, 19.5, 26.0, 32.5, 39.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrayKind {
+    Ventilated, // ladder, ventilated trough, perforated and wire mesh
+    SolidBottom,
+}
+
+impl TrayKind {
+    /// Match `Tray.type` as entered in the schedule, e.g. "LADDER", "Solid Bottom", "Ventilated Trough"
+    pub fn parse(value: &str) -> Option<Self> {
+        let key: String = value.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_uppercase();
+        match key.as_str() {
+            "LADDER" | "VENTILATED" | "VENTILATEDTROUGH" | "TROUGH" | "PERFORATED" | "WIREMESH" | "BASKET" => Some(Self::Ventilated),
+            "SOLID" | "SOLIDBOTTOM" => Some(Self::SolidBottom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrayFillCheck {
+    pub rule: String, // e.g. "NEC 392.22(A)(1)(b)"
+    pub description: String,
+    pub used: f64,
+    pub allowable: Option<f64>, // None when the tray dimensions needed are unknown
+    pub unit: String, // "in" for sums of diameters, "in²" for areas, "mm²" for the fallback
+    pub fill_percentage: f64, // used ÷ allowable; for the fallback, of the cross-section
+    pub max_fill_percentage: f64,
+    pub cables_without_diameter: Vec<String>,
+    pub undersized_single_conductors: Vec<String>, // below 1/0 AWG, not allowed by NEC 392.10(B)(1)
+    pub notes: Vec<String>,
+    pub passes: bool,
+}
+
+struct TrayCable<'a> {
+    cable: &'a Cable,
+    diameter: f64, // in
+    cmil: Option<f64>,
+}
+
+impl TrayCable<'_> {
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * (self.diameter / 2.0).powi(2)
+    }
+
+    fn is_control_or_signal(&self) -> bool {
+        matches!(self.cable.function.as_deref(), Some("Signal" | "Control" | "Communication" | "Instrumentation"))
+    }
+
+    fn is_single_conductor(&self) -> bool {
+        self.cable.cores == Some(1)
+    }
+
+    fn at_least(&self, cmil: f64) -> bool {
+        self.cmil.is_some_and(|c| c >= cmil)
+    }
+}
+
+/// Table value for the tray width: the largest listed width the tray reaches, allowing 5% for
+/// metric trays, capped at 36 in. None below 6 in.
+fn table_value(width_mm: f64, column: &[f64; 7]) -> Option<(f64, f64)> {
+    TABLE_WIDTHS.iter().zip(column)
+        .rev()
+        .find(|((_, metric), _)| width_mm * 1.05 >= *metric)
+        .map(|((inches, _), value)| (*inches, *value))
+}
+
+fn diameter_sum(cables: &[&TrayCable]) -> f64 {
+    cables.iter().map(|c| c.diameter).sum()
+}
+
+fn area_sum(cables: &[&TrayCable]) -> f64 {
+    cables.iter().map(|c| c.area()).sum()
+}
+
+/// Fill of a tray under NEC 392.22. Trays of an unrecognised type fall back to the cable area
+/// against 50% of width × height.
+pub fn check_tray_fill(tray: &Tray, cables: &[&Cable]) -> TrayFillCheck {
+    let calculator = ElectricalCalculator::new();
+    let mut cables_without_diameter = Vec::new();
+    let mut tray_cables = Vec::new();
+    for &cable in cables {
+        match cable.outer_diameter {
+            Some(diameter) if diameter > 0.0 => tray_cables.push(TrayCable {
+                cable,
+                diameter: diameter / MM_PER_IN,
+                cmil: cable.size.as_deref()
+                    .and_then(|size| parse_conductor_size(&size.to_uppercase()))
+                    .and_then(|size| calculator.conductor_area_cmil(&size)),
+            }),
+            _ => cables_without_diameter.push(cable.tag.clone()),
+        }
+    }
+
+    let mut check = match tray.r#type.as_deref().and_then(TrayKind::parse) {
+        None => fallback_check(tray, &tray_cables),
+        Some(_) if tray_cables.is_empty() => TrayFillCheck {
+            rule: "NEC 392.22".to_string(),
+            description: "No cables with a known diameter".to_string(),
+            used: 0.0,
+            allowable: None,
+            unit: "in²".to_string(),
+            fill_percentage: 0.0,
+            max_fill_percentage: 100.0,
+            cables_without_diameter: Vec::new(),
+            undersized_single_conductors: Vec::new(),
+            notes: Vec::new(),
+            passes: true,
+        },
+        Some(kind) => {
+            let all: Vec<&TrayCable> = tray_cables.iter().collect();
+            if all.iter().all(|c| c.is_control_or_signal()) {
+                control_signal_check(tray, kind, &all)
+            } else if kind == TrayKind::Ventilated && all.iter().all(|c| c.is_single_conductor()) {
+                single_conductor_check(tray, &all)
+            } else {
+                multiconductor_check(tray, kind, &all)
+            }
+        }
+    };
+
+    if !cables_without_diameter.is_empty() {
+        check.notes.push(format!("{} cable(s) without an outer diameter are not counted", cables_without_diameter.len()));
+    }
+    check.cables_without_diameter = cables_without_diameter;
+    check
+}
+
+fn result(rule: &str, description: String, used: f64, allowable: Option<f64>, unit: &str) -> TrayFillCheck {
+    let fill_percentage = match allowable {
+        Some(allowable) if allowable > 0.0 => used / allowable * 100.0,
+        _ => 0.0,
+    };
+    TrayFillCheck {
+        rule: rule.to_string(),
+        description,
+        used,
+        allowable,
+        unit: unit.to_string(),
+        fill_percentage,
+        max_fill_percentage: 100.0,
+        cables_without_diameter: Vec::new(),
+        undersized_single_conductors: Vec::new(),
+        notes: Vec::new(),
+        passes: matches!(allowable, Some(allowable) if used <= allowable),
+    }
+}
+
+fn missing_width(rule: &str, description: &str, used: f64, unit: &str) -> TrayFillCheck {
+    let mut check = result(rule, description.to_string(), used, None, unit);
+    check.notes.push("Tray width of at least 6 in (150 mm) is needed for this rule".to_string());
+    check
+}
+
+/// 392.22(A)(2) and (A)(4): control and signal cables only, against 50% (ventilated) or 40%
+/// (solid bottom) of the interior area, taking the depth as 6 in at most
+fn control_signal_check(tray: &Tray, kind: TrayKind, cables: &[&TrayCable]) -> TrayFillCheck {
+    let (rule, percent) = match kind {
+        TrayKind::Ventilated => ("NEC 392.22(A)(2)", 50.0),
+        TrayKind::SolidBottom => ("NEC 392.22(A)(4)", 40.0),
+    };
+    let description = format!("Control and signal cables only: sum of areas within {:.0}% of the interior area", percent);
+    let used = area_sum(cables);
+    match (tray.width, tray.height) {
+        (Some(width), Some(height)) if width > 0.0 && height > 0.0 => {
+            let depth = (height / MM_PER_IN).min(MAX_CONTROL_DEPTH_IN);
+            result(rule, description, used, Some(width / MM_PER_IN * depth * percent / 100.0), "in²")
+        }
+        _ => {
+            let mut check = result(rule, description, used, None, "in²");
+            check.notes.push("Tray width and height are needed for this rule".to_string());
+            check
+        }
+    }
+}
+
+/// 392.22(A)(1) ventilated and (A)(3) solid bottom: multiconductor power and lighting cables,
+/// or any mixture with control and signal cables
+fn multiconductor_check(tray: &Tray, kind: TrayKind, cables: &[&TrayCable]) -> TrayFillCheck {
+    let width = tray.width.filter(|w| *w > 0.0);
+    let (large, small): (Vec<&TrayCable>, Vec<&TrayCable>) = cables.iter().partition(|c| c.at_least(CMIL_4_0));
+    let (subsection, width_share, column, sd_factor, column_name) = match kind {
+        TrayKind::Ventilated => ("1", 1.0, &TABLE_A_COLUMN_1, 1.2, "Column 1"),
+        TrayKind::SolidBottom => ("3", 0.9, &TABLE_A_COLUMN_3, 1.0, "Column 3"),
+    };
+
+    let mut check = if small.is_empty() {
+        let rule = format!("NEC 392.22(A)({})(a)", subsection);
+        let description = format!(
+            "Cables 4/0 AWG and larger: sum of diameters within {:.0}% of the tray width, in a single layer",
+            width_share * 100.0
+        );
+        match width {
+            Some(width) => result(&rule, description, diameter_sum(&large), Some(width / MM_PER_IN * width_share), "in"),
+            None => missing_width(&rule, &description, diameter_sum(&large), "in"),
+        }
+    } else if large.is_empty() {
+        let rule = format!("NEC 392.22(A)({})(b)", subsection);
+        match width.and_then(|w| table_value(w, column)) {
+            Some((inches, allowable)) => result(
+                &rule,
+                format!("Cables smaller than 4/0 AWG: sum of areas within Table 392.22(A) {} for a {} in tray", column_name, inches),
+                area_sum(&small),
+                Some(allowable),
+                "in²",
+            ),
+            None => missing_width(&rule, "Cables smaller than 4/0 AWG: sum of areas within Table 392.22(A)", area_sum(&small), "in²"),
+        }
+    } else {
+        // Column 2 (or 4) is Column 1 (or 3) less the space of the large cables; compare the
+        // small cables' area plus that space with the full column value
+        let rule = format!("NEC 392.22(A)({})(c)", subsection);
+        let used = area_sum(&small) + sd_factor * diameter_sum(&large);
+        match width.and_then(|w| table_value(w, column)) {
+            Some((inches, allowable)) => result(
+                &rule,
+                format!(
+                    "Cables 4/0 AWG and larger with smaller cables: sum of areas of the smaller cables within Table 392.22(A) {} less {} × Sd for a {} in tray",
+                    column_name, sd_factor, inches
+                ),
+                used,
+                Some(allowable),
+                "in²",
+            ),
+            None => missing_width(&rule, "Cables 4/0 AWG and larger with smaller cables", used, "in²"),
+        }
+    };
+
+    if !large.is_empty() {
+        check.notes.push("Cables 4/0 AWG and larger go in a single layer with no other cables on them".to_string());
+    }
+    let without_size = cables.iter().filter(|c| c.cmil.is_none() && !c.is_control_or_signal()).count();
+    if without_size > 0 {
+        check.notes.push(format!("{} cable(s) without a recognised conductor size are taken as smaller than 4/0 AWG", without_size));
+    }
+    check
+}
+
+/// 392.22(B)(1): single conductor cables in ladder or ventilated trough trays
+fn single_conductor_check(tray: &Tray, cables: &[&TrayCable]) -> TrayFillCheck {
+    let width = tray.width.filter(|w| *w > 0.0);
+    let mut check = if cables.iter().any(|c| !c.at_least(CMIL_250)) {
+        let rule = "NEC 392.22(B)(1)(d)";
+        let description = "Single conductors 1/0 through 4/0 AWG present: sum of diameters of all single conductors within the tray width, in a single layer";
+        match width {
+            Some(width) => result(rule, description.to_string(), diameter_sum(cables), Some(width / MM_PER_IN), "in"),
+            None => missing_width(rule, description, diameter_sum(cables), "in"),
+        }
+    } else {
+        let (large, medium): (Vec<&TrayCable>, Vec<&TrayCable>) = cables.iter().partition(|c| c.at_least(CMIL_1000));
+        if medium.is_empty() {
+            let rule = "NEC 392.22(B)(1)(a)";
+            let description = "Single conductors 1000 kcmil and larger: sum of diameters within the tray width";
+            match width {
+                Some(width) => result(rule, description.to_string(), diameter_sum(&large), Some(width / MM_PER_IN), "in"),
+                None => missing_width(rule, description, diameter_sum(&large), "in"),
+            }
+        } else {
+            let (rule, used, description) = if large.is_empty() {
+                ("NEC 392.22(B)(1)(b)", area_sum(&medium), "Single conductors 250 through 900 kcmil: sum of areas within Table 392.22(B)(1) Column 1")
+            } else {
+                (
+                    "NEC 392.22(B)(1)(c)",
+                    area_sum(&medium) + 1.1 * diameter_sum(&large),
+                    "Single conductors 1000 kcmil and larger with 250 through 900 kcmil: sum of areas of the smaller within Table 392.22(B)(1) Column 1 less 1.1 × Sd",
+                )
+            };
+            match width.and_then(|w| table_value(w, &TABLE_B1_COLUMN_1)) {
+                Some((inches, allowable)) => result(rule, format!("{} for a {} in tray", description, inches), used, Some(allowable), "in²"),
+                None => missing_width(rule, description, used, "in²"),
+            }
+        }
+    };
+
+    let undersized: Vec<String> = cables.iter()
+        .filter(|c| !c.at_least(CMIL_1_0))
+        .map(|c| c.cable.tag.clone())
+        .collect();
+    if !undersized.is_empty() {
+        check.notes.push(format!("Single conductors in tray must be 1/0 AWG or larger (NEC 392.10(B)(1)): {}", undersized.join(", ")));
+        check.passes = false;
+    }
+    check.undersized_single_conductors = undersized;
+    check
+}
+
+/// Cable area against 50% of width × height, for tray types NEC 392.22 has no rule for here
+fn fallback_check(tray: &Tray, cables: &[TrayCable]) -> TrayFillCheck {
+    let used: f64 = cables.iter().map(|c| c.area() * MM_PER_IN * MM_PER_IN).sum();
+    let area = match (tray.width, tray.height) {
+        (Some(width), Some(height)) if width > 0.0 && height > 0.0 => Some(width * height),
+        _ => None,
+    };
+    let fill_percentage = area.map(|area| used / area * 100.0).unwrap_or(0.0);
+    TrayFillCheck {
+        rule: "Cross-sectional area".to_string(),
+        description: format!(
+            "Tray type not covered by NEC 392.22: sum of cable areas within {:.0}% of width × height",
+            FALLBACK_MAX_FILL
+        ),
+        used,
+        allowable: area.map(|area| area * FALLBACK_MAX_FILL / 100.0),
+        unit: "mm²".to_string(),
+        fill_percentage,
+        max_fill_percentage: FALLBACK_MAX_FILL,
+        cables_without_diameter: Vec::new(),
+        undersized_single_conductors: Vec::new(),
+        notes: Vec::new(),
+        passes: area.is_some() && fill_percentage <= FALLBACK_MAX_FILL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tray(tray_type: &str, width: f64, height: f64) -> Tray {
+        serde_json::from_value(serde_json::json!({
+            "id": 1, "project_id": 1, "revision_id": 1, "tag": "TR-1", "type": tray_type,
+            "width": width, "height": height, "fill_percentage": 0.0, "max_fill_percentage": 50.0,
+            "created_at": chrono::Utc::now(), "updated_at": chrono::Utc::now(),
+        })).unwrap()
+    }
+
+    fn cable(tag: &str, function: &str, size: &str, cores: i32, diameter_in: f64) -> Cable {
+        serde_json::from_value(serde_json::json!({
+            "id": 1, "project_id": 1, "revision_id": 1, "tag": tag, "function": function, "size": size,
+            "cores": cores, "outer_diameter": diameter_in * MM_PER_IN, "segregation_warning": false,
+            "created_at": chrono::Utc::now(), "updated_at": chrono::Utc::now(),
+        })).unwrap()
+    }
+
+    fn refs(cables: &[Cable]) -> Vec<&Cable> {
+        cables.iter().collect()
+    }
+
+    /// Twelve 3C 6 AWG power cables, 1 in OD: 9.42 in² of cable
+    fn small_power_cables() -> Vec<Cable> {
+        (0..12).map(|i| cable(&format!("P-{}", i), "Power", "6 AWG", 3, 1.0)).collect()
+    }
+
+    fn large_power_cables() -> Vec<Cable> {
+        vec![cable("F-1", "Power", "4/0 AWG", 3, 2.0), cable("F-2", "Power", "4/0 AWG", 3, 2.0)]
+    }
+
+    #[test]
+    fn test_cables_smaller_than_4_0_use_table_392_22_a() {
+        let small = small_power_cables();
+        let check = check_tray_fill(&tray("LADDER", 300.0, 100.0), &refs(&small));
+        assert_eq!(check.rule, "NEC 392.22(A)(1)(b)");
+        assert_eq!(check.allowable, Some(14.0));
+        assert!(check.passes);
+
+        // Solid bottom allows only 11 in² for the same width
+        let check = check_tray_fill(&tray("SOLID_BOTTOM", 300.0, 100.0), &refs(&small));
+        assert_eq!(check.rule, "NEC 392.22(A)(3)(b)");
+        assert_eq!(check.allowable, Some(11.0));
+        assert!(check.passes);
+    }
+
+    #[test]
+    fn test_cables_4_0_and_larger_use_the_tray_width() {
+        // Sum of diameters against the width (11.81 in), or 90% of it in solid bottom (10.63 in)
+        let large = large_power_cables();
+        let check = check_tray_fill(&tray("LADDER", 300.0, 100.0), &refs(&large));
+        assert_eq!((check.rule.as_str(), check.unit.as_str()), ("NEC 392.22(A)(1)(a)", "in"));
+        assert!((check.used - 4.0).abs() < 1e-9);
+        assert!(check.passes && check.notes[0].contains("single layer"));
+
+        let check = check_tray_fill(&tray("SOLID_BOTTOM", 300.0, 100.0), &refs(&large));
+        assert_eq!((check.rule.as_str(), check.unit.as_str()), ("NEC 392.22(A)(3)(a)", "in"));
+        assert!((check.allowable.unwrap() - 300.0 / MM_PER_IN * 0.9).abs() < 1e-9);
+        assert!(check.passes);
+
+        // Six of them need 12 in, more than a solid bottom tray of 11.81 in allows
+        let wide: Vec<Cable> = (0..6).map(|i| cable(&format!("F-{}", i), "Power", "250 kcmil", 3, 2.0)).collect();
+        assert!(!check_tray_fill(&tray("SOLID_BOTTOM", 300.0, 100.0), &refs(&wide)).passes);
+    }
+
+    #[test]
+    fn test_mixed_cable_sizes_deduct_the_large_cables() {
+        let mut mixed = small_power_cables();
+        mixed.extend(large_power_cables());
+
+        // Sd = 2 × 2 in uses 1.2 × 4 = 4.8 in² more: 14.22 > 14
+        let check = check_tray_fill(&tray("LADDER", 300.0, 100.0), &refs(&mixed));
+        assert_eq!(check.rule, "NEC 392.22(A)(1)(c)");
+        assert!((check.used - (12.0 * std::f64::consts::PI / 4.0 + 4.8)).abs() < 1e-9);
+        assert!(!check.passes);
+
+        // Solid bottom deducts 1 × Sd from Column 3: 13.42 > 11
+        let check = check_tray_fill(&tray("SOLID_BOTTOM", 300.0, 100.0), &refs(&mixed));
+        assert_eq!(check.rule, "NEC 392.22(A)(3)(c)");
+        assert!((check.used - (12.0 * std::f64::consts::PI / 4.0 + 4.0)).abs() < 1e-9);
+        assert_eq!(check.allowable, Some(11.0));
+        assert!(!check.passes);
+    }
+
+    #[test]
+    fn test_control_and_signal_cables_use_the_interior_area() {
+        // 50% of 12 in × 3.94 in deep, or 40% in solid bottom
+        let signals: Vec<Cable> = (0..4).map(|i| cable(&format!("S-{}", i), "Signal", "16 AWG", 2, 0.5)).collect();
+        let check = check_tray_fill(&tray("LADDER", 300.0, 100.0), &refs(&signals));
+        assert_eq!(check.rule, "NEC 392.22(A)(2)");
+        assert!((check.allowable.unwrap() - 300.0 / 25.4 * 100.0 / 25.4 * 0.5).abs() < 1e-9);
+
+        let check = check_tray_fill(&tray("SOLID_BOTTOM", 300.0, 100.0), &refs(&signals));
+        assert_eq!(check.rule, "NEC 392.22(A)(4)");
+        assert!((check.allowable.unwrap() - 300.0 / 25.4 * 100.0 / 25.4 * 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_single_conductors_use_392_22_b() {
+        let ladder = tray("LADDER", 300.0, 100.0);
+
+        // 500 kcmil by area against Table 392.22(B)(1) Column 1
+        let feeders: Vec<Cable> = (0..6).map(|i| cable(&format!("L-{}", i), "Power", "500 kcmil", 1, 1.2)).collect();
+        let check = check_tray_fill(&ladder, &refs(&feeders));
+        assert_eq!((check.rule.as_str(), check.allowable), ("NEC 392.22(B)(1)(b)", Some(13.0)));
+        assert!(check.undersized_single_conductors.is_empty());
+
+        // 1/0 through 4/0 by the sum of diameters, and 6 AWG is not allowed at all
+        let tails = [cable("L-7", "Power", "1/0 AWG", 1, 0.6), cable("L-8", "Power", "6 AWG", 1, 0.3)];
+        let check = check_tray_fill(&ladder, &refs(&tails));
+        assert_eq!(check.rule, "NEC 392.22(B)(1)(d)");
+        assert!(check.used < check.allowable.unwrap());
+        assert!(!check.passes && check.notes[0].contains("L-8"));
+        assert_eq!(check.undersized_single_conductors, vec!["L-8".to_string()]);
+    }
+
+    #[test]
+    fn test_missing_or_narrow_width_has_no_allowance() {
+        let small = small_power_cables();
+        for width in [0.0, 100.0] {
+            let check = check_tray_fill(&tray("LADDER", width, 100.0), &refs(&small));
+            assert_eq!(check.rule, "NEC 392.22(A)(1)(b)");
+            assert_eq!((check.allowable, check.fill_percentage), (None, 0.0));
+            assert!(!check.passes && check.notes[0].contains("6 in"));
+        }
+
+        let check = check_tray_fill(&tray("LADDER", 0.0, 100.0), &refs(&large_power_cables()));
+        assert_eq!((check.rule.as_str(), check.allowable), ("NEC 392.22(A)(1)(a)", None));
+        assert!(!check.passes);
+    }
+
+    #[test]
+    fn test_unknown_tray_type_uses_the_cross_section() {
+        let check = check_tray_fill(&tray("Channel", 300.0, 100.0), &refs(&small_power_cables()));
+        assert_eq!((check.rule.as_str(), check.max_fill_percentage), ("Cross-sectional area", 50.0));
+        assert!(check.passes);
+    }
+}
@@ -1,16 +1,28 @@
-use crate::database::{Database, models::*};
-use crate::validation::{CableValidator, ValidationSummary, ValidationResult};
-use crate::calculations::{ElectricalCalculator, VoltageDropCalculation, VoltageDropResult, ConductorMaterial};
+use crate::database::{Database, MigrationError, models::*};
+use crate::validation::{self, CableValidator, ValidationSummary, ValidationResult};
+use crate::validation::incremental::{CableValidationUpdate, IncrementalValidator, VALIDATION_UPDATED_EVENT};
+use crate::validation::rules::{RuleSetExport, ValidationRules};
+use crate::calculations::{ElectricalCalculator, VoltageDropCalculation, VoltageDropResult, ConductorMaterial, SystemType, RacewayType, CableAmpacityCheck, VoltageDropReport, FeederPathCheck, DEFAULT_FEEDER_PATH_LIMIT, creates_feeder_loop, CableShortCircuitCheck, GroundingConductorSizing, ProtectiveDeviceCheck, MotorCircuitSizing, MotorCircuitCheck, LoadAnalysisOptions, LoadAnalysisReport, PowerCableOptions, PowerCableProposal};
+use crate::calculations::conduit_fill::{self, ConduitFillCheck, ConduitKind, ConduitSizing, ConduitTradeSize};
+use crate::calculations::tray_fill::TrayFillCheck;
+use crate::export::xlsx;
+use crate::import::{self, CableImportOptions, CsvImportPreview, ImportError, ImportReport};
+use crate::import::xlsx::MarkupReport;
+use crate::routing::CableRouteProposal;
+use crate::routing::length::CableLengthReport;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use std::fs;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
 // Application state
 pub struct AppState {
     pub db: Option<Database>,
     pub current_project_id: Option<i64>,
     pub current_file_path: Option<PathBuf>,
+    // Cleared whenever the project, its rule set or its overrides change, or cables change in bulk
+    pub validator: Option<IncrementalValidator>,
 }
 
 impl Default for AppState {
@@ -19,17 +31,47 @@ impl Default for AppState {
             db: None,
             current_project_id: None,
             current_file_path: None,
+            validator: None,
         }
     }
 }
 
+impl AppState {
+    /// Incremental validator for the current project, indexed on first use
+    pub fn cable_validator(&mut self) -> Result<&mut IncrementalValidator, CommandError> {
+        if self.validator.is_none() {
+            let db = self.db.as_ref().ok_or(CommandError::NoDatabase)?;
+            let project_id = self.current_project_id.ok_or(CommandError::NoProject)?;
+            self.validator = Some(IncrementalValidator::load(db, project_id)?);
+        }
+        Ok(self.validator.as_mut().expect("validator was just loaded"))
+    }
+}
+
+/// Push re-evaluated cable results to the frontend. A failed emit only costs the live update,
+/// so it is logged rather than failing the edit.
+fn emit_validation_updates(app: &AppHandle, updates: Vec<CableValidationUpdate>) {
+    if updates.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit(VALIDATION_UPDATED_EVENT, updates) {
+        log::warn!("Failed to emit validation updates: {}", e);
+    }
+}
+
 // Error type for Tauri commands
 #[derive(Debug, thiserror::Error)]
 pub enum CommandError {
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
+    #[error("{0}")]
+    Import(#[from] ImportError),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Excel export error: {0}")]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
     #[error("ZIP error: {0}")]
     Zip(#[from] zip::result::ZipError),
     #[error("No database connection")]
@@ -76,6 +118,7 @@ pub async fn create_project(
     
     app_state.db = Some(db);
     app_state.current_project_id = project.id;
+    app_state.validator = None;
     app_state.current_file_path = None;
     
     Ok(project)
@@ -100,6 +143,7 @@ pub async fn open_project(
     
     app_state.db = Some(db);
     app_state.current_project_id = project.id;
+    app_state.validator = None;
     app_state.current_file_path = Some(path);
     
     Ok(project)
@@ -123,49 +167,14 @@ pub async fn save_project(
         return Err(CommandError::Custom("No file path specified".to_string()));
     };
 
-    // If we're working with in-memory database, save it to disk
-    if app_state.current_file_path.is_none() {
-        // Copy in-memory database to file
+    // Changes to a file-backed database are already on disk. Otherwise (in-memory
+    // project, or "Save As" to a different file) copy the whole database across,
+    // keeping row IDs so every foreign key stays valid.
+    if app_state.current_file_path.as_ref() != Some(&save_path) {
+        current_db.save_to_file(&save_path)?;
+
+        // Continue working against the saved file
         let file_db = Database::new(&save_path)?;
-        
-        // Copy data from current in-memory DB to file DB
-        let projects = current_db.get_projects()?;
-        if let Some(project) = projects.first() {
-            let file_project = file_db.insert_project(project)?;
-            let project_id = file_project.id.unwrap();
-            
-            // Copy cables
-            let cables = current_db.get_cables(project.id.unwrap())?;
-            for cable in cables {
-                let new_cable_data = NewCable {
-                    tag: cable.tag,
-                    description: cable.description,
-                    function: cable.function,
-                    voltage: cable.voltage,
-                    current: cable.current,
-                    cable_type: cable.cable_type,
-                    size: cable.size,
-                    cores: cable.cores,
-                    segregation_class: cable.segregation_class,
-                    from_location: cable.from_location,
-                    from_equipment: cable.from_equipment,
-                    to_location: cable.to_location,
-                    to_equipment: cable.to_equipment,
-                    length: cable.length,
-                    spare_percentage: cable.spare_percentage,
-                    route: cable.route,
-                    manufacturer: cable.manufacturer,
-                    part_number: cable.part_number,
-                    outer_diameter: cable.outer_diameter,
-                    tray_id: cable.tray_id,
-                    conduit_id: cable.conduit_id,
-                    notes: cable.notes,
-                };
-                file_db.insert_cable(project_id, &new_cable_data)?;
-            }
-        }
-        
-        // Replace in-memory DB with file DB
         app_state.db = Some(file_db);
     }
     
@@ -211,6 +220,7 @@ pub async fn new_project(
     // Update app state
     app_state.db = Some(db);
     app_state.current_project_id = project.id;
+    app_state.validator = None;
     app_state.current_file_path = None; // New project has no file path yet
     
     Ok(project)
@@ -246,14 +256,17 @@ pub async fn get_projects(
 // Cable commands
 #[tauri::command]
 pub async fn create_cable(
+    app: AppHandle,
     cable_data: NewCable,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Cable, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
-    Ok(db.insert_cable(project_id, &cable_data)?)
+    let cable = db.insert_cable(project_id, &cable_data)?;
+    revalidate_cable(&app, &mut app_state, cable.clone())?;
+    Ok(cable)
 }
 
 #[tauri::command]
@@ -269,25 +282,35 @@ pub async fn get_cables(
 
 #[tauri::command]
 pub async fn update_cable(
+    app: AppHandle,
     id: i64,
     updates: UpdateCable,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Cable, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     
-    Ok(db.update_cable(id, &updates)?)
+    let cable = db.update_cable(id, &updates)?;
+    revalidate_cable(&app, &mut app_state, cable.clone())?;
+    Ok(cable)
 }
 
 #[tauri::command]
 pub async fn delete_cable(
+    app: AppHandle,
     id: i64,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
+    // Re-evaluate while the cable is still indexed, so its tag-mates and route neighbours are found
+    let updates = app_state.cable_validator()?.remove_cable(id);
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     
-    db.delete_cable(id)?;
+    if let Err(error) = db.delete_cable(id) {
+        app_state.validator = None;
+        return Err(error.into());
+    }
+    emit_validation_updates(&app, updates);
     Ok(())
 }
 
@@ -304,6 +327,273 @@ pub async fn get_next_cable_tag(
     Ok(db.get_next_cable_tag(project_id, &prefix)?)
 }
 
+// Route segment commands
+#[tauri::command]
+pub async fn get_cable_route_segments(
+    cable_id: i64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CableRouteSegment>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    
+    Ok(db.get_cable_route_segments(cable_id)?)
+}
+
+#[tauri::command]
+pub async fn set_cable_route_segments(
+    app: AppHandle,
+    cable_id: i64,
+    segments: Vec<NewCableRouteSegment>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CableRouteSegment>, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    let segments = write_cable_route(db, project_id, cable_id, &segments)?;
+    let cable = db.get_cable_by_id(cable_id)?;
+    revalidate_cable(&app, &mut app_state, cable)?;
+    Ok(segments)
+}
+
+/// Insert a raceway into a cable's route before the segment at `position` (0-based), or at the
+/// end when `position` is past the last segment
+#[tauri::command]
+pub async fn insert_cable_route_segment(
+    app: AppHandle,
+    cable_id: i64,
+    position: usize,
+    segment: NewCableRouteSegment,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CableRouteSegment>, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    let mut route: Vec<NewCableRouteSegment> = db.get_cable_route_segments(cable_id)?
+        .into_iter()
+        .map(|s| NewCableRouteSegment { conduit_id: s.conduit_id, tray_id: s.tray_id })
+        .collect();
+    route.insert(position.min(route.len()), segment);
+    
+    let segments = write_cable_route(db, project_id, cable_id, &route)?;
+    let cable = db.get_cable_by_id(cable_id)?;
+    revalidate_cable(&app, &mut app_state, cable)?;
+    Ok(segments)
+}
+
+/// Put a cable's existing segments in the order of `segment_ids`
+#[tauri::command]
+pub async fn reorder_cable_route_segments(
+    app: AppHandle,
+    cable_id: i64,
+    segment_ids: Vec<i64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CableRouteSegment>, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    let current = db.get_cable_route_segments(cable_id)?;
+    let mut sorted_ids = segment_ids.clone();
+    sorted_ids.sort_unstable();
+    let mut current_ids: Vec<i64> = current.iter().filter_map(|s| s.id).collect();
+    current_ids.sort_unstable();
+    if sorted_ids != current_ids {
+        return Err(CommandError::Custom("The new order must list each segment of the route exactly once".to_string()));
+    }
+    
+    let route: Vec<NewCableRouteSegment> = segment_ids.iter()
+        .filter_map(|id| current.iter().find(|s| s.id == Some(*id)))
+        .map(|s| NewCableRouteSegment { conduit_id: s.conduit_id, tray_id: s.tray_id })
+        .collect();
+    
+    let segments = write_cable_route(db, project_id, cable_id, &route)?;
+    let cable = db.get_cable_by_id(cable_id)?;
+    revalidate_cable(&app, &mut app_state, cable)?;
+    Ok(segments)
+}
+
+/// Route the selected cables through the raceway network without storing anything
+#[tauri::command]
+pub async fn preview_cable_routes(
+    cable_ids: Vec<i64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CableRouteProposal>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    Ok(db.preview_cable_routes(project_id, &cable_ids)?)
+}
+
+/// Store the routes from an accepted preview. Fails without changes if a cable or raceway is
+/// no longer in the project.
+#[tauri::command]
+pub async fn accept_cable_routes(
+    proposals: Vec<CableRouteProposal>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<Cable>, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    let cable_ids: HashSet<i64> = db.get_cables(project_id)?.iter().filter_map(|c| c.id).collect();
+    for proposal in proposals.iter().filter(|p| p.skipped_reason.is_none()) {
+        if !cable_ids.contains(&proposal.cable_id) {
+            return Err(CommandError::Custom(format!("Cable {} not found in this project", proposal.cable_tag)));
+        }
+        check_route_segments(db, project_id, &proposal.segments)?;
+    }
+    
+    let cables = db.accept_cable_routes(&proposals)?;
+    app_state.validator = None;
+    Ok(cables)
+}
+
+/// Check that every segment names one conduit or tray of the project, then store the route
+fn write_cable_route(
+    db: &Database,
+    project_id: i64,
+    cable_id: i64,
+    segments: &[NewCableRouteSegment],
+) -> Result<Vec<CableRouteSegment>, CommandError> {
+    check_route_segments(db, project_id, segments)?;
+    Ok(db.set_cable_route_segments(cable_id, segments)?)
+}
+
+fn check_route_segments(db: &Database, project_id: i64, segments: &[NewCableRouteSegment]) -> Result<(), CommandError> {
+    let conduit_ids: HashSet<i64> = db.get_conduits(project_id)?.iter().filter_map(|c| c.id).collect();
+    let tray_ids: HashSet<i64> = db.get_trays(project_id)?.iter().filter_map(|t| t.id).collect();
+    
+    for segment in segments {
+        match (segment.conduit_id, segment.tray_id) {
+            (Some(id), None) if conduit_ids.contains(&id) => {}
+            (None, Some(id)) if tray_ids.contains(&id) => {}
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(CommandError::Custom("Each route segment needs either a conduit or a tray".to_string()));
+            }
+            _ => return Err(CommandError::Custom("Route segment refers to a raceway outside this project".to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Re-validate a cable that was written, along the route segments now stored for it
+fn revalidate_cable(app: &AppHandle, app_state: &mut AppState, cable: Cable) -> Result<(), CommandError> {
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let segments = match cable.id {
+        Some(id) => db.get_cable_route_segments(id)?,
+        None => Vec::new(),
+    };
+    let updates = app_state.cable_validator()?.upsert_cable(cable, &segments);
+    emit_validation_updates(app, updates);
+    Ok(())
+}
+
+// Cable length commands
+#[tauri::command]
+pub async fn get_cable_length_allowances(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CableLengthAllowances, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    Ok(db.get_cable_length_allowances(project_id)?)
+}
+
+/// Store the project's length allowances; every routed cable's calculated length is rerun
+#[tauri::command]
+pub async fn update_cable_length_allowances(
+    allowances: CableLengthAllowances,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CableLengthAllowances, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    let values = [allowances.drop_rise, allowances.termination_makeup, allowances.elevation_change];
+    if values.iter().any(|value| !value.is_finite() || *value < 0.0) {
+        return Err(CommandError::Custom("Length allowances must be zero or more".to_string()));
+    }
+    
+    Ok(db.set_cable_length_allowances(project_id, &allowances)?)
+}
+
+#[tauri::command]
+pub async fn get_cable_length_report(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CableLengthReport>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    Ok(db.get_cable_length_report(project_id)?)
+}
+
+// Cable import commands
+#[tauri::command]
+pub async fn preview_cable_csv(file_path: String) -> Result<CsvImportPreview, CommandError> {
+    let file = fs::File::open(&file_path)?;
+    Ok(import::preview_csv(file)?)
+}
+
+#[tauri::command]
+pub async fn import_cables_csv(
+    file_path: String,
+    options: CableImportOptions,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ImportReport, CommandError> {
+    let file = fs::File::open(&file_path)?;
+    let rows = import::read_csv(file, &options.mappings)?;
+
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let report = db.import_cables(project_id, &rows, options.dry_run, options.clear_blanks)?;
+    if !options.dry_run {
+        app_state.validator = None;
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn import_schedule_markup(
+    file_path: String,
+    dry_run: bool,
+    clear_blanks: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<MarkupReport, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let file = std::io::BufReader::new(fs::File::open(&file_path)?);
+    let rows = import::xlsx::diff_workbook(file, db, project_id, clear_blanks)?;
+    let report = db.apply_markup(project_id, rows, dry_run)?;
+    if !dry_run {
+        app_state.validator = None;
+    }
+    Ok(report)
+}
+
+// Schedule export commands
+#[tauri::command]
+pub async fn export_schedules_xlsx(
+    file_path: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let (title, sheets) = xlsx::project_sheets(db, project_id)?;
+    xlsx::build_workbook(&title, &sheets)?.save(&file_path)?;
+    Ok(file_path)
+}
+
 // File dialog commands
 #[tauri::command]
 pub async fn show_open_dialog() -> Result<Option<String>, CommandError> {
@@ -335,9 +625,7 @@ pub async fn validate_all_cables(
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
-    let cables = db.get_cables(project_id)?;
-    let validator = CableValidator::new();
-    let summary = validator.validate_all_cables(&cables);
+    let summary = CableValidator::for_project(db, project_id)?.validate_project(db, project_id)?;
     
     Ok(summary)
 }
@@ -347,17 +635,31 @@ pub async fn validate_cable(
     cable_id: i64,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Vec<ValidationResult>, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
+    // Cable-list rules, route segregation included, come from the incremental validator
+    let mut results = app_state.cable_validator()?.cable_results(cable_id);
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
-    let cables = db.get_cables(project_id)?;
-    let target_cable = cables.iter()
-        .find(|c| c.id == Some(cable_id))
-        .ok_or_else(|| CommandError::Custom("Cable not found".to_string()))?;
+    let target_cable = match db.get_cable_by_id(cable_id) {
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Err(CommandError::Custom("Cable not found".to_string())),
+        other => other?,
+    };
     
-    let validator = CableValidator::new();
-    let results = validator.validate_cable(target_cable, &cables);
+    let library = db.get_cable_library_items(None, None)?;
+    let calculator = ElectricalCalculator::new();
+    let validator = CableValidator::for_project(db, project_id)?;
+    let short_circuit = calculator.check_cable_short_circuit(&target_cable, &db.get_fault_levels(project_id)?, &library);
+    results.extend(validator.validate_short_circuit(&[short_circuit]));
+    let protection: Vec<_> = db.check_protective_devices(project_id)?
+        .into_iter()
+        .filter(|check| check.cable_id == Some(cable_id))
+        .collect();
+    results.extend(validator.validate_protective_devices(&protection));
+    let device_rating = protection.first().map(|check| check.trip_rating);
+    let grounding = calculator.check_cable_grounding_conductor(&target_cable, &db.get_loads(project_id)?, &library, device_rating);
+    results.extend(validator.validate_grounding_conductors(&[grounding]));
+    validation::apply_overrides(&mut results, &db.get_validation_overrides(project_id)?, std::slice::from_ref(&target_cable));
     
     Ok(results)
 }
@@ -390,13 +692,67 @@ pub async fn get_validation_summary(
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
-    let cables = db.get_cables(project_id)?;
-    let validator = CableValidator::new();
-    let summary = validator.validate_all_cables(&cables);
+    let summary = CableValidator::for_project(db, project_id)?.validate_project(db, project_id)?;
     
     Ok((summary.error_count, summary.warning_count, summary.info_count))
 }
 
+/// Accept a validation finding with a justification. Only results that allow an override and
+/// are currently reported can be overridden.
+#[tauri::command]
+pub async fn override_validation_result(
+    override_data: NewValidationOverride,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ValidationOverride, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    if override_data.justification.trim().is_empty() {
+        return Err(CommandError::Custom("A justification is required to override a validation result".to_string()));
+    }
+    let summary = CableValidator::for_project(db, project_id)?.validate_project(db, project_id)?;
+    let result = summary.results.iter()
+        .find(|r| {
+            r.cable_id == Some(override_data.cable_id)
+                && r.validation_type == override_data.validation_type
+                && r.field == override_data.field
+                && !r.suppressed
+        })
+        .ok_or_else(|| CommandError::Custom("No matching validation result to override".to_string()))?;
+    if !result.override_allowed {
+        return Err(CommandError::Custom(format!("'{}' cannot be overridden", result.message)));
+    }
+
+    let validation_override = db.insert_validation_override(project_id, &override_data)?;
+    app_state.validator = None;
+    Ok(validation_override)
+}
+
+#[tauri::command]
+pub async fn get_validation_overrides(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ValidationOverride>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.get_validation_overrides(project_id)?)
+}
+
+#[tauri::command]
+pub async fn revoke_validation_override(
+    id: i64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ValidationOverride, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+
+    let validation_override = db.revoke_validation_override(id)?;
+    app_state.validator = None;
+    Ok(validation_override)
+}
+
 // Initialize default project on startup
 #[tauri::command]
 pub async fn initialize_app(
@@ -410,6 +766,7 @@ pub async fn initialize_app(
         
         app_state.db = Some(db);
         app_state.current_project_id = project.id;
+        app_state.validator = None;
         
         return Ok(project);
     }
@@ -561,6 +918,7 @@ pub async fn export_workflow_zip(
 // Electrical calculation commands
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn calculate_voltage_drop(
     voltage: f64,
     current: f64,
@@ -568,6 +926,10 @@ pub async fn calculate_voltage_drop(
     conductor_size: String,
     material: String,
     power_factor: Option<f64>,
+    system_type: Option<SystemType>,
+    raceway_type: Option<RacewayType>,
+    parallel_sets: Option<i32>,
+    conductor_temperature: Option<f64>,
 ) -> Result<VoltageDropResult, CommandError> {
     let calculator = ElectricalCalculator::new();
     
@@ -584,13 +946,50 @@ pub async fn calculate_voltage_drop(
         conductor_size,
         material: conductor_material,
         power_factor: power_factor.unwrap_or(0.85),
-        number_of_conductors: 2, // Assume single-phase for now
+        system_type: system_type.unwrap_or(SystemType::SinglePhaseTwoWire),
+        raceway_type: raceway_type.unwrap_or(RacewayType::Pvc),
+        parallel_sets: parallel_sets.unwrap_or(1),
+        conductor_temperature,
     };
     
     calculator.calculate_voltage_drop(&calculation)
         .map_err(|e| CommandError::Custom(e))
 }
 
+#[tauri::command]
+pub async fn size_grounding_conductor(
+    device_rating: f64,
+    material: String,
+    phase_size: Option<String>,
+    minimum_phase_size: Option<String>,
+) -> Result<GroundingConductorSizing, CommandError> {
+    let conductor_material = match material.to_lowercase().as_str() {
+        "aluminum" | "al" => ConductorMaterial::Aluminum,
+        _ => ConductorMaterial::Copper,
+    };
+
+    ElectricalCalculator::new()
+        .size_grounding_conductor(device_rating, &conductor_material, phase_size.as_deref(), minimum_phase_size.as_deref())
+        .map_err(CommandError::Custom)
+}
+
+#[tauri::command]
+pub async fn size_motor_circuit(
+    horsepower: f64,
+    voltage: f64,
+    phases: Option<i32>,
+    material: String,
+) -> Result<MotorCircuitSizing, CommandError> {
+    let conductor_material = match material.to_lowercase().as_str() {
+        "aluminum" | "al" => ConductorMaterial::Aluminum,
+        _ => ConductorMaterial::Copper,
+    };
+
+    ElectricalCalculator::new()
+        .size_motor_circuit(horsepower, voltage, phases.unwrap_or(3), &conductor_material)
+        .map_err(CommandError::Custom)
+}
+
 #[tauri::command]
 pub async fn calculate_minimum_conductor_size(
     voltage: f64,
@@ -599,6 +998,7 @@ pub async fn calculate_minimum_conductor_size(
     material: String,
     max_voltage_drop_percent: f64,
     power_factor: Option<f64>,
+    system_type: Option<SystemType>,
 ) -> Result<String, CommandError> {
     let calculator = ElectricalCalculator::new();
     
@@ -615,6 +1015,7 @@ pub async fn calculate_minimum_conductor_size(
         &conductor_material,
         max_voltage_drop_percent,
         power_factor.unwrap_or(0.85),
+        system_type.unwrap_or(SystemType::SinglePhaseTwoWire),
     ).map_err(|e| CommandError::Custom(e))
 }
 
@@ -636,133 +1037,238 @@ pub async fn calculate_current_from_power(
 }
 
 #[tauri::command]
-pub async fn update_cable_voltage_drop(
+pub async fn check_cable_ampacity(
     cable_id: i64,
+    ambient_temperature: Option<f64>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<Option<f64>, CommandError> {
-    let mut app_state = state.lock().unwrap();
+) -> Result<CableAmpacityCheck, CommandError> {
+    let app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
-    
-    // Get cable details
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
     let cable = db.get_cable_by_id(cable_id)?;
-    
-    // Only calculate if we have sufficient data
-    if let (Some(voltage), Some(length), Some(size)) = (&cable.voltage, &cable.length, &cable.size) {
-        // Estimate current based on cable function and size (simplified)
-        let estimated_current = estimate_cable_current(&cable.function, size);
-        
-        if estimated_current > 0.0 {
-            let calculator = ElectricalCalculator::new();
-            
-            let calculation = VoltageDropCalculation {
-                voltage: *voltage,
-                current: estimated_current,
-                distance: *length,
-                conductor_size: size.to_string(),
-                material: ConductorMaterial::Copper, // Default assumption
-                power_factor: 0.85,
-                number_of_conductors: 2,
-            };
-            
-            match calculator.calculate_voltage_drop(&calculation) {
-                Ok(result) => {
-                    // Update cable with calculated voltage drop
-                    let update_cable = UpdateCable {
-                        tag: None,
-                        description: None,
-                        function: None,
-                        voltage: None,
-                        current: None,
-                        cable_type: None,
-                        size: None,
-                        cores: None,
-                        segregation_class: None,
-                        from_location: None,
-                        from_equipment: None,
-                        to_location: None,
-                        to_equipment: None,
-                        length: None,
-                        spare_percentage: None,
-                        route: None,
-                        manufacturer: None,
-                        part_number: None,
-                        outer_diameter: None,
-                        voltage_drop_percentage: Some(result.voltage_drop_percentage),
-                        segregation_warning: None,
-                        tray_id: None,
-                        conduit_id: None,
-                        notes: None,
-                    };
-                    
-                    db.update_cable(cable_id, &update_cable)?;
-                    Ok(Some(result.voltage_drop_percentage))
-                }
-                Err(_) => Ok(None)
-            }
-        } else {
-            Ok(None)
-        }
-    } else {
-        Ok(None)
-    }
-}
+    let cables = db.get_cables(project_id)?;
+    let loads = db.get_loads(project_id)?;
+    let library = db.get_cable_library_items(None, None)?;
 
-// Helper function to estimate current based on cable function and size
-fn estimate_cable_current(function: &Option<String>, size: &str) -> f64 {
-    match function.as_ref().map(|s| s.as_str()) {
-        Some("Power") | Some("Lighting") => {
-            // Rough estimates based on conductor size for power cables
-            match size.to_uppercase().as_str() {
-                s if s.contains("18") => 10.0,
-                s if s.contains("16") => 13.0,
-                s if s.contains("14") => 15.0,
-                s if s.contains("12") => 20.0,
-                s if s.contains("10") => 30.0,
-                s if s.contains("8") => 40.0,
-                s if s.contains("6") => 55.0,
-                s if s.contains("4") => 70.0,
-                s if s.contains("2") => 95.0,
-                _ => 20.0, // Default assumption
-            }
-        }
-        Some("Signal") | Some("Control") | Some("Communication") => {
-            // Signal cables typically carry much less current
-            match size.to_uppercase().as_str() {
-                s if s.contains("18") | s.contains("20") | s.contains("22") => 0.1,
-                s if s.contains("16") => 0.2,
-                s if s.contains("14") => 0.5,
-                _ => 0.1, // Very low current for signals
-            }
-        }
-        _ => 0.0, // Unknown function, can't estimate
-    }
+    Ok(ElectricalCalculator::new().check_cable_ampacity(&cable, &cables, &loads, &library, ambient_temperature.unwrap_or(30.0)))
 }
 
-// I/O Point commands
 #[tauri::command]
-pub async fn create_io_point(
-    io_point_data: NewIOPoint,
+pub async fn check_all_cable_ampacity(
+    ambient_temperature: Option<f64>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<IOPoint, CommandError> {
+) -> Result<Vec<CableAmpacityCheck>, CommandError> {
     let app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
-    
-    Ok(db.insert_io_point(project_id, &io_point_data)?)
+
+    let cables = db.get_cables(project_id)?;
+    let loads = db.get_loads(project_id)?;
+    let library = db.get_cable_library_items(None, None)?;
+    let calculator = ElectricalCalculator::new();
+
+    Ok(cables.iter()
+        .map(|cable| calculator.check_cable_ampacity(cable, &cables, &loads, &library, ambient_temperature.unwrap_or(30.0)))
+        .collect())
 }
 
 #[tauri::command]
-pub async fn get_io_points(
+pub async fn update_cable_voltage_drop(
+    app: AppHandle,
+    cable_id: i64,
     state: State<'_, Mutex<AppState>>,
-) -> Result<Vec<IOPoint>, CommandError> {
-    let app_state = state.lock().unwrap();
+) -> Result<Option<f64>, CommandError> {
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
-    
-    Ok(db.get_io_points(project_id)?)
-}
 
-#[tauri::command]
+    let cable = db.get_cable_by_id(cable_id)?;
+    let loads = db.get_loads(project_id)?;
+    let library = db.get_cable_library_items(None, None)?;
+    let raceways = db.cable_raceway_types(project_id)?;
+    let check = ElectricalCalculator::new().check_cable_voltage_drop(&cable, &loads, &library, &raceways);
+
+    let Some(result) = check.result else {
+        return Ok(None);
+    };
+    let update_cable = UpdateCable {
+        tag: None,
+        description: None,
+        function: None,
+        voltage: None,
+        current: None,
+        cable_type: None,
+        size: None,
+        cores: None,
+        segregation_class: None,
+        from_location: None,
+        from_equipment: None,
+        to_location: None,
+        to_equipment: None,
+        length: None,
+        spare_percentage: None,
+        route: None,
+        manufacturer: None,
+        part_number: None,
+        outer_diameter: None,
+        voltage_drop_percentage: Some(result.voltage_drop_percentage),
+        segregation_warning: None,
+        tray_id: None,
+        conduit_id: None,
+        notes: None,
+    };
+    let cable = db.update_cable(cable_id, &update_cable)?;
+    revalidate_cable(&app, &mut app_state, cable)?;
+    Ok(Some(result.voltage_drop_percentage))
+}
+
+#[tauri::command]
+pub async fn recalculate_voltage_drops(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<VoltageDropReport, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let report = db.recalculate_voltage_drops(project_id)?;
+    app_state.validator = None;
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn set_cable_parent(
+    app: AppHandle,
+    cable_id: i64,
+    parent_cable_id: Option<i64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Cable, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let cables = db.get_cables(project_id)?;
+    if !cables.iter().any(|c| c.id == Some(cable_id)) {
+        return Err(CommandError::Custom(format!("Cable {} is not in this project", cable_id)));
+    }
+    if let Some(parent_id) = parent_cable_id {
+        if !cables.iter().any(|c| c.id == Some(parent_id)) {
+            return Err(CommandError::Custom(format!("Feeder cable {} is not in this project", parent_id)));
+        }
+        if creates_feeder_loop(cable_id, parent_id, &cables) {
+            return Err(CommandError::Custom("A cable cannot be fed from itself or one of its downstream cables".to_string()));
+        }
+    }
+
+    let cable = db.set_cable_parent(cable_id, parent_cable_id)?;
+    revalidate_cable(&app, &mut app_state, cable.clone())?;
+    Ok(cable)
+}
+
+#[tauri::command]
+pub async fn check_feeder_voltage_drop(
+    limit_percentage: Option<f64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<FeederPathCheck>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let cables = db.get_cables(project_id)?;
+    let loads = db.get_loads(project_id)?;
+    let library = db.get_cable_library_items(None, None)?;
+    let raceways = db.cable_raceway_types(project_id)?;
+    let calculator = ElectricalCalculator::new();
+    let limit = limit_percentage.unwrap_or(DEFAULT_FEEDER_PATH_LIMIT);
+
+    Ok(loads.iter()
+        .map(|load| calculator.check_feeder_path(load, &cables, &loads, &library, &raceways, limit))
+        .collect())
+}
+
+// Fault level commands
+#[tauri::command]
+pub async fn get_fault_levels(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<FaultLevel>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.get_fault_levels(project_id)?)
+}
+
+#[tauri::command]
+pub async fn create_fault_level(
+    fault_level_data: NewFaultLevel,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<FaultLevel, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.insert_fault_level(project_id, &fault_level_data)?)
+}
+
+#[tauri::command]
+pub async fn update_fault_level(
+    id: i64,
+    updates: UpdateFaultLevel,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<FaultLevel, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+
+    Ok(db.update_fault_level(id, &updates)?)
+}
+
+#[tauri::command]
+pub async fn delete_fault_level(
+    id: i64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+
+    Ok(db.delete_fault_level(id)?)
+}
+
+#[tauri::command]
+pub async fn check_short_circuit_withstand(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<CableShortCircuitCheck>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.check_short_circuit_withstand(project_id)?)
+}
+
+// I/O Point commands
+#[tauri::command]
+pub async fn create_io_point(
+    io_point_data: NewIOPoint,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<IOPoint, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    Ok(db.insert_io_point(project_id, &io_point_data)?)
+}
+
+#[tauri::command]
+pub async fn get_io_points(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<IOPoint>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    Ok(db.get_io_points(project_id)?)
+}
+
+#[tauri::command]
 pub async fn update_io_point(
     id: i64,
     updates: UpdateIOPoint,
@@ -873,17 +1379,334 @@ pub async fn get_load_summary(
     Ok(db.get_load_summary(project_id)?)
 }
 
+#[tauri::command]
+pub async fn get_load_analysis(
+    power_factor: Option<f64>,
+    diversity_factor: Option<f64>,
+    service_voltage: Option<f64>,
+    service_phases: Option<i32>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<LoadAnalysisReport, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let defaults = LoadAnalysisOptions::default();
+    let options = LoadAnalysisOptions {
+        power_factor: power_factor.unwrap_or(defaults.power_factor),
+        diversity_factor: diversity_factor.unwrap_or(defaults.diversity_factor),
+        service_voltage,
+        service_phases,
+    };
+    let loads = db.get_loads(project_id)?;
+    let cables = db.get_cables(project_id)?;
+
+    ElectricalCalculator::new()
+        .analyze_loads(&loads, &cables, &options)
+        .map_err(CommandError::Custom)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn preview_power_cables(
+    load_ids: Vec<i64>,
+    tag_prefix: Option<String>,
+    material: Option<String>,
+    max_voltage_drop_percent: Option<f64>,
+    ambient_temperature: Option<f64>,
+    default_length: Option<f64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<PowerCableProposal>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let defaults = PowerCableOptions::default();
+    let options = PowerCableOptions {
+        tag_prefix: tag_prefix.unwrap_or(defaults.tag_prefix),
+        material: match material.as_deref().map(str::to_lowercase).as_deref() {
+            Some("aluminum") | Some("al") => ConductorMaterial::Aluminum,
+            _ => ConductorMaterial::Copper,
+        },
+        max_voltage_drop_percent: max_voltage_drop_percent.unwrap_or(defaults.max_voltage_drop_percent),
+        ambient_temperature: ambient_temperature.unwrap_or(defaults.ambient_temperature),
+        default_length,
+    };
+
+    Ok(db.preview_power_cables(project_id, &load_ids, &options)?)
+}
+
+/// Store the cables from an accepted preview. Fails without changes if a load has been given
+/// a cable since the preview or a tag is already taken.
+#[tauri::command]
+pub async fn accept_power_cables(
+    proposals: Vec<PowerCableProposal>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<Cable>, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    let loads = db.get_loads(project_id)?;
+    let mut tags: HashSet<String> = db.get_cables(project_id)?
+        .into_iter()
+        .map(|cable| cable.tag)
+        .collect();
+    for proposal in &proposals {
+        let Some(cable) = &proposal.cable else {
+            continue;
+        };
+        let load = loads.iter()
+            .find(|l| l.id.is_some() && l.id == proposal.load_id)
+            .ok_or_else(|| CommandError::Custom(format!("Load {} not found in this project", proposal.load_tag)))?;
+        if load.cable_id.is_some() {
+            return Err(CommandError::Custom(format!("Load {} already has a cable", load.tag)));
+        }
+        if !tags.insert(cable.tag.clone()) {
+            return Err(CommandError::Custom(format!("Cable tag {} is already in use", cable.tag)));
+        }
+    }
+
+    let cables = db.accept_power_cables(project_id, &proposals)?;
+    app_state.validator = None;
+    Ok(cables)
+}
+
+// Protective device commands
+#[tauri::command]
+pub async fn create_protective_device(
+    device_data: NewProtectiveDevice,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ProtectiveDevice, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.insert_protective_device(project_id, &device_data)?)
+}
+
+#[tauri::command]
+pub async fn get_protective_devices(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ProtectiveDevice>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.get_protective_devices(project_id)?)
+}
+
+#[tauri::command]
+pub async fn update_protective_device(
+    id: i64,
+    updates: UpdateProtectiveDevice,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ProtectiveDevice, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+
+    Ok(db.update_protective_device(id, &updates)?)
+}
+
+#[tauri::command]
+pub async fn delete_protective_device(
+    id: i64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+
+    db.delete_protective_device(id)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn check_motor_circuits(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<MotorCircuitCheck>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.check_motor_circuits(project_id)?)
+}
+
+#[tauri::command]
+pub async fn check_protective_devices(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ProtectiveDeviceCheck>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.check_protective_devices(project_id)?)
+}
+
+// Validation rule set commands
+#[tauri::command]
+pub async fn get_validation_rule_sets(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ValidationRuleSet>, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.get_validation_rule_sets(project_id)?)
+}
+
+/// Rules validation currently applies: the active rule set, or the built-in defaults
+#[tauri::command]
+pub async fn get_active_validation_rules(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ValidationRules, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    Ok(db.get_active_validation_rule_set(project_id)?
+        .map(|rule_set| rule_set.rules)
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn create_validation_rule_set(
+    rule_set_data: NewValidationRuleSet,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ValidationRuleSet, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    rule_set_data.rules.check().map_err(CommandError::Custom)?;
+    ensure_unique_rule_set_name(db, project_id, &rule_set_data.name, None)?;
+    let rule_set = db.insert_validation_rule_set(project_id, &rule_set_data)?;
+    app_state.validator = None;
+    Ok(rule_set)
+}
+
+#[tauri::command]
+pub async fn update_validation_rule_set(
+    id: i64,
+    updates: UpdateValidationRuleSet,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ValidationRuleSet, CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    if let Some(rules) = &updates.rules {
+        rules.check().map_err(CommandError::Custom)?;
+    }
+    if let Some(name) = &updates.name {
+        ensure_unique_rule_set_name(db, project_id, name, Some(id))?;
+    }
+    let rule_set = db.update_validation_rule_set(id, &updates)?;
+    app_state.validator = None;
+    Ok(rule_set)
+}
+
+/// Choose the rule set validation evaluates; `None` returns to the built-in NEC rules
+#[tauri::command]
+pub async fn set_active_validation_rule_set(
+    id: Option<i64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    match db.set_active_validation_rule_set(project_id, id) {
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Err(CommandError::Custom("Rule set not found".to_string())),
+        other => other?,
+    }
+    app_state.validator = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_validation_rule_set(
+    id: i64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+
+    db.delete_validation_rule_set(id)?;
+    app_state.validator = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn export_validation_rule_set(
+    id: i64,
+    file_path: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+
+    let rule_set = db.get_validation_rule_set_by_id(id)?;
+    let export = RuleSetExport {
+        name: rule_set.name,
+        description: rule_set.description,
+        rules: rule_set.rules,
+    };
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| CommandError::Custom(format!("Failed to serialize rule set: {}", e)))?;
+    fs::write(&file_path, json)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn import_validation_rule_set(
+    file_path: String,
+    activate: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ValidationRuleSet, CommandError> {
+    let json = fs::read_to_string(&file_path)?;
+    let import: RuleSetExport = serde_json::from_str(&json)
+        .map_err(|e| CommandError::Custom(format!("Invalid rule set file: {}", e)))?;
+    import.rules.check().map_err(CommandError::Custom)?;
+
+    let mut app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+
+    ensure_unique_rule_set_name(db, project_id, &import.name, None)?;
+    let rule_set = db.insert_validation_rule_set(project_id, &NewValidationRuleSet {
+        name: import.name,
+        description: import.description,
+        is_active: activate,
+        rules: import.rules,
+    })?;
+    app_state.validator = None;
+    Ok(rule_set)
+}
+
+fn ensure_unique_rule_set_name(db: &Database, project_id: i64, name: &str, exclude_id: Option<i64>) -> Result<(), CommandError> {
+    let taken = db.get_validation_rule_sets(project_id)?
+        .iter()
+        .any(|rule_set| rule_set.name.eq_ignore_ascii_case(name) && rule_set.id != exclude_id);
+    if taken {
+        return Err(CommandError::Custom(format!("A rule set named '{}' already exists", name)));
+    }
+    Ok(())
+}
+
 // Conduit commands
 #[tauri::command]
 pub async fn create_conduit(
     conduit_data: NewConduit,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Conduit, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
-    Ok(db.insert_conduit(project_id, &conduit_data)?)
+    let conduit = db.insert_conduit(project_id, &conduit_data)?;
+    // Route text naming the new conduit is now routed through it
+    app_state.validator = None;
+    Ok(conduit)
 }
 
 #[tauri::command]
@@ -903,10 +1726,13 @@ pub async fn update_conduit(
     updates: UpdateConduit,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Conduit, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     
-    Ok(db.update_conduit(id, &updates)?)
+    let conduit = db.update_conduit(id, &updates)?;
+    // Routes through the conduit carry its tag, and its size decides the fill checks of its cables
+    app_state.validator = None;
+    Ok(conduit)
 }
 
 #[tauri::command]
@@ -914,10 +1740,11 @@ pub async fn delete_conduit(
     id: i64,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     
     db.delete_conduit(id)?;
+    app_state.validator = None;
     Ok(())
 }
 
@@ -949,11 +1776,14 @@ pub async fn create_tray(
     tray_data: NewTray,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Tray, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
-    Ok(db.insert_tray(&tray_data, project_id, 1)?)
+    let tray = db.insert_tray(&tray_data, project_id, 1)?;
+    // Route text naming the new tray is now routed through it
+    app_state.validator = None;
+    Ok(tray)
 }
 
 #[tauri::command]
@@ -962,10 +1792,15 @@ pub async fn update_tray(
     updates: UpdateTray,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Tray, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     
-    Ok(db.update_tray(id, &updates)?)
+    let tray = db.update_tray(id, &updates)?;
+    if updates.tag.is_some() {
+        // Routes through the tray now carry its new tag
+        app_state.validator = None;
+    }
+    Ok(tray)
 }
 
 #[tauri::command]
@@ -973,10 +1808,11 @@ pub async fn delete_tray(
     id: i64,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     
     db.delete_tray(id)?;
+    app_state.validator = None;
     Ok(())
 }
 
@@ -1024,23 +1860,73 @@ pub async fn recalculate_all_fills(
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
-    // Recalculate all conduit fills
-    let conduits = db.get_conduits(project_id)?;
-    for conduit in conduits {
-        if let Some(id) = conduit.id {
-            db.calculate_conduit_fill_percentage(id)?;
-        }
+    db.recalculate_all_fills(project_id)?;
+    Ok(())
+}
+
+/// Conduit fill against NEC Chapter 9 Table 1, with the jam ratio when it holds three cables
+#[tauri::command]
+pub async fn check_conduit_fill(
+    conduit_id: i64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ConduitFillCheck, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    
+    Ok(db.check_conduit_fill(conduit_id)?)
+}
+
+/// Tray fill under the NEC 392.22 method for the tray type and its cables, with the rule applied
+#[tauri::command]
+pub async fn check_tray_fill(
+    tray_id: i64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<TrayFillCheck, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    
+    Ok(db.check_tray_fill(tray_id)?)
+}
+
+/// NEC Chapter 9 Table 4 trade sizes for a conduit type, e.g. "EMT" or "PVC Sch 80"
+#[tauri::command]
+pub async fn get_conduit_trade_sizes(conduit_type: String) -> Result<Vec<ConduitTradeSize>, CommandError> {
+    let kind = ConduitKind::parse(&conduit_type)
+        .ok_or_else(|| CommandError::Custom(format!("No NEC Chapter 9 Table 4 data for conduit type '{}'", conduit_type)))?;
+    Ok(conduit_fill::conduit_trade_sizes(kind))
+}
+
+/// Smallest trade size of a conduit type for the given cables, from their outer diameters
+#[tauri::command]
+pub async fn calculate_minimum_conduit_size(
+    conduit_type: String,
+    cable_ids: Vec<i64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ConduitSizing, CommandError> {
+    let app_state = state.lock().unwrap();
+    let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
+    let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
+    
+    let kind = ConduitKind::parse(&conduit_type)
+        .ok_or_else(|| CommandError::Custom(format!("No NEC Chapter 9 Table 4 data for conduit type '{}'", conduit_type)))?;
+    let cables: Vec<Cable> = db.get_cables(project_id)?
+        .into_iter()
+        .filter(|c| c.id.is_some_and(|id| cable_ids.contains(&id)))
+        .collect();
+    if cables.is_empty() {
+        return Err(CommandError::Custom("Select at least one cable".to_string()));
     }
     
-    // Recalculate all tray fills
-    let trays = db.get_trays(project_id)?;
-    for tray in trays {
-        if let Some(id) = tray.id {
-            db.calculate_tray_fill_percentage(id)?;
+    let mut diameters = Vec::new();
+    for cable in &cables {
+        match cable.outer_diameter {
+            Some(diameter) if diameter > 0.0 => diameters.push(diameter),
+            _ => return Err(CommandError::Custom(format!("Cable {} has no outer diameter", cable.tag))),
         }
     }
     
-    Ok(())
+    conduit_fill::minimum_trade_size(kind, &diameters)
+        .ok_or_else(|| CommandError::Custom(format!("The cables do not fit the largest {} trade size", kind.label())))
 }
 
 // Revision tracking commands
@@ -1272,7 +2158,7 @@ pub async fn import_cable_from_library(
     tag: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Cable, CommandError> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let db = app_state.db.as_ref().ok_or(CommandError::NoDatabase)?;
     let project_id = app_state.current_project_id.ok_or(CommandError::NoProject)?;
     
@@ -1281,6 +2167,7 @@ pub async fn import_cable_from_library(
         .map_err(|_| CommandError::Custom("No current revision found".to_string()))?;
     
     let cable = db.import_cable_from_library(project_id, revision_id, library_id, tag)?;
+    app_state.validator = None;
     Ok(cable)
 }
 
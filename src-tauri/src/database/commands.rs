@@ -1,6 +1,18 @@
 use super::{Database, models::*};
-use rusqlite::{params, Result};
+use crate::calculations::conduit_fill::{self, ConduitFillCheck};
+use crate::calculations::tray_fill::{self, TrayFillCheck};
+use crate::calculations::{
+    is_motor_load, CableShortCircuitCheck, ElectricalCalculator, MotorCircuitCheck,
+    PowerCableOptions, PowerCableProposal, ProtectiveDeviceCheck, RacewayType, VoltageDropReport, DEFAULT_CLEARING_TIME,
+};
+use crate::routing::{CableRouteProposal, RacewayNetwork};
+use crate::routing::length::{calculate_cable_length, CableLengthReport, RouteLeg};
+use crate::validation::{override_fingerprint, rules::ValidationRules, CableValidator};
+use crate::import::{CableImportRow, ImportAction, ImportReport, ImportRowReport};
+use crate::import::xlsx::{EntityKind, EntityUpdate, MarkupReport, MarkupRow};
+use rusqlite::{params, OptionalExtension, Result};
 use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 
 impl Database {
     // Project operations
@@ -164,6 +176,9 @@ impl Database {
             now.to_rfc3339()
         ])?;
 
+        if cable.route.is_some() {
+            self.sync_route_segments(id)?;
+        }
         self.get_cable_by_id(id)
     }
 
@@ -172,7 +187,8 @@ impl Database {
             "SELECT id, project_id, revision_id, tag, description, function, voltage, current, cable_type, size, cores,
              segregation_class, from_location, from_equipment, to_location, to_equipment, length, 
              spare_percentage, calculated_length, route, manufacturer, part_number, outer_diameter,
-             voltage_drop_percentage, segregation_warning, tray_id, conduit_id, notes, created_at, updated_at
+             voltage_drop_percentage, segregation_warning, tray_id, conduit_id, notes, created_at, updated_at,
+             parent_cable_id
              FROM cables WHERE project_id = ?1 ORDER BY tag"
         )?;
 
@@ -205,6 +221,7 @@ impl Database {
                 segregation_warning: row.get::<_, i32>(24)? != 0,
                 tray_id: row.get(25)?,
                 conduit_id: row.get(26)?,
+                parent_cable_id: row.get(30)?,
                 notes: row.get(27)?,
                 created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(28)?)
                     .unwrap().with_timezone(&Utc),
@@ -225,7 +242,8 @@ impl Database {
             "SELECT id, project_id, revision_id, tag, description, function, voltage, current, cable_type, size, cores,
              segregation_class, from_location, from_equipment, to_location, to_equipment, length, 
              spare_percentage, calculated_length, route, manufacturer, part_number, outer_diameter,
-             voltage_drop_percentage, segregation_warning, tray_id, conduit_id, notes, created_at, updated_at
+             voltage_drop_percentage, segregation_warning, tray_id, conduit_id, notes, created_at, updated_at,
+             parent_cable_id
              FROM cables WHERE id = ?1"
         )?;
 
@@ -258,6 +276,7 @@ impl Database {
                 segregation_warning: row.get::<_, i32>(24)? != 0,
                 tray_id: row.get(25)?,
                 conduit_id: row.get(26)?,
+                parent_cable_id: row.get(30)?,
                 notes: row.get(27)?,
                 created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(28)?)
                     .unwrap().with_timezone(&Utc),
@@ -270,19 +289,34 @@ impl Database {
     pub fn update_cable(&self, id: i64, updates: &UpdateCable) -> Result<Cable> {
         let now = Utc::now();
         
-        // For now, implement a simple version that updates all provided fields
-        // In a real implementation, you'd build a dynamic query
         let mut stmt = self.connection.prepare(
             "UPDATE cables SET 
              tag = COALESCE(?1, tag),
              description = COALESCE(?2, description),
              function = COALESCE(?3, function),
              voltage = COALESCE(?4, voltage),
-             from_equipment = COALESCE(?5, from_equipment),
-             to_equipment = COALESCE(?6, to_equipment),
-             length = COALESCE(?7, length),
-             updated_at = ?8
-             WHERE id = ?9"
+             current = COALESCE(?5, current),
+             cable_type = COALESCE(?6, cable_type),
+             size = COALESCE(?7, size),
+             cores = COALESCE(?8, cores),
+             segregation_class = COALESCE(?9, segregation_class),
+             from_location = COALESCE(?10, from_location),
+             from_equipment = COALESCE(?11, from_equipment),
+             to_location = COALESCE(?12, to_location),
+             to_equipment = COALESCE(?13, to_equipment),
+             length = COALESCE(?14, length),
+             spare_percentage = COALESCE(?15, spare_percentage),
+             route = COALESCE(?16, route),
+             manufacturer = COALESCE(?17, manufacturer),
+             part_number = COALESCE(?18, part_number),
+             outer_diameter = COALESCE(?19, outer_diameter),
+             voltage_drop_percentage = COALESCE(?20, voltage_drop_percentage),
+             segregation_warning = COALESCE(?21, segregation_warning),
+             tray_id = COALESCE(?22, tray_id),
+             conduit_id = COALESCE(?23, conduit_id),
+             notes = COALESCE(?24, notes),
+             updated_at = ?25
+             WHERE id = ?26"
         )?;
 
         stmt.execute(params![
@@ -290,19 +324,70 @@ impl Database {
             updates.description,
             updates.function,
             updates.voltage,
+            updates.current,
+            updates.cable_type,
+            updates.size,
+            updates.cores,
+            updates.segregation_class,
+            updates.from_location,
             updates.from_equipment,
+            updates.to_location,
             updates.to_equipment,
             updates.length,
+            updates.spare_percentage,
+            updates.route,
+            updates.manufacturer,
+            updates.part_number,
+            updates.outer_diameter,
+            updates.voltage_drop_percentage,
+            updates.segregation_warning,
+            updates.tray_id,
+            updates.conduit_id,
+            updates.notes,
             now.to_rfc3339(),
             id
         ])?;
 
+        if updates.route.is_some() {
+            self.sync_route_segments(id)?;
+        } else {
+            // The NEC 392.22 tray method also follows the cable's function, size and cores
+            let fill_inputs_changed = updates.outer_diameter.is_some()
+                || updates.function.is_some()
+                || updates.size.is_some()
+                || updates.cores.is_some();
+            if fill_inputs_changed {
+                self.recalculate_segment_fills(&self.get_cable_route_segments(id)?)?;
+            }
+            if updates.spare_percentage.is_some() {
+                self.recalculate_cable_lengths(&[id])?;
+            }
+        }
         self.get_cable_by_id(id)
     }
 
+    /// Delete a cable and unlink everything that referred to it, so loads it fed can be given a new one
     pub fn delete_cable(&self, id: i64) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let segments = self.get_cable_route_segments(id)?;
+        self.connection.execute("UPDATE cables SET parent_cable_id = NULL WHERE parent_cable_id = ?1", [id])?;
+        self.connection.execute("UPDATE loads SET cable_id = NULL, feeder_cable = NULL WHERE cable_id = ?1", [id])?;
+        self.connection.execute("UPDATE io_points SET cable_id = NULL WHERE cable_id = ?1", [id])?;
+        self.connection.execute("UPDATE protective_devices SET cable_id = NULL WHERE cable_id = ?1", [id])?;
+        self.connection.execute("UPDATE validation_overrides SET cable_id = NULL WHERE cable_id = ?1", [id])?;
+        self.connection.execute("DELETE FROM cable_route_segments WHERE cable_id = ?1", [id])?;
         self.connection.execute("DELETE FROM cables WHERE id = ?1", [id])?;
-        Ok(())
+        self.recalculate_segment_fills(&segments)?;
+        transaction.commit()
+    }
+
+    /// Set or clear the upstream feeder of a cable. Callers check for feeder loops first.
+    pub fn set_cable_parent(&self, id: i64, parent_cable_id: Option<i64>) -> Result<Cable> {
+        self.connection.execute(
+            "UPDATE cables SET parent_cable_id = ?1, updated_at = ?2 WHERE id = ?3",
+            params![parent_cable_id, Utc::now().to_rfc3339(), id],
+        )?;
+        self.get_cable_by_id(id)
     }
 
     pub fn get_next_cable_tag(&self, project_id: i64, prefix: &str) -> Result<String> {
@@ -538,8 +623,8 @@ impl Database {
         let mut stmt = self.connection.prepare(
             "INSERT INTO loads (project_id, revision_id, tag, description, load_type, power_kw, 
              power_hp, voltage, current, power_factor, efficiency, demand_factor, connected_load_kw,
-             demand_load_kw, cable_id, feeder_cable, starter_type, protection_type, notes, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)"
+             demand_load_kw, cable_id, feeder_cable, starter_type, protection_type, notes, created_at, updated_at, phases, continuous)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)"
         )?;
 
         let id = stmt.insert(params![
@@ -563,7 +648,9 @@ impl Database {
             load.protection_type,
             load.notes,
             now.to_rfc3339(),
-            now.to_rfc3339()
+            now.to_rfc3339(),
+            load.phases,
+            load.continuous
         ])?;
 
         self.get_load_by_id(id)
@@ -574,7 +661,7 @@ impl Database {
             "SELECT id, project_id, revision_id, tag, description, load_type, power_kw, power_hp,
              voltage, current, power_factor, efficiency, demand_factor, connected_load_kw, 
              demand_load_kw, cable_id, feeder_cable, starter_type, protection_type, notes, 
-             created_at, updated_at
+             created_at, updated_at, phases, continuous
              FROM loads WHERE project_id = ?1 ORDER BY tag"
         )?;
 
@@ -589,10 +676,12 @@ impl Database {
                 power_kw: row.get(6)?,
                 power_hp: row.get(7)?,
                 voltage: row.get(8)?,
+                phases: row.get(22)?,
                 current: row.get(9)?,
                 power_factor: row.get(10)?,
                 efficiency: row.get(11)?,
                 demand_factor: row.get(12)?,
+                continuous: row.get(23)?,
                 connected_load_kw: row.get(13)?,
                 demand_load_kw: row.get(14)?,
                 cable_id: row.get(15)?,
@@ -619,7 +708,7 @@ impl Database {
             "SELECT id, project_id, revision_id, tag, description, load_type, power_kw, power_hp,
              voltage, current, power_factor, efficiency, demand_factor, connected_load_kw, 
              demand_load_kw, cable_id, feeder_cable, starter_type, protection_type, notes, 
-             created_at, updated_at
+             created_at, updated_at, phases, continuous
              FROM loads WHERE id = ?1"
         )?;
 
@@ -634,10 +723,12 @@ impl Database {
                 power_kw: row.get(6)?,
                 power_hp: row.get(7)?,
                 voltage: row.get(8)?,
+                phases: row.get(22)?,
                 current: row.get(9)?,
                 power_factor: row.get(10)?,
                 efficiency: row.get(11)?,
                 demand_factor: row.get(12)?,
+                continuous: row.get(23)?,
                 connected_load_kw: row.get(13)?,
                 demand_load_kw: row.get(14)?,
                 cable_id: row.get(15)?,
@@ -659,6 +750,15 @@ impl Database {
         // Get current load for calculations
         let current_load = self.get_load_by_id(id)?;
         
+        // The current is calculated again when any of its inputs change, unless the update sets it
+        let current_inputs_changed = updates.load_type.is_some()
+            || updates.power_kw.is_some()
+            || updates.power_hp.is_some()
+            || updates.voltage.is_some()
+            || updates.phases.is_some()
+            || updates.power_factor.is_some()
+            || updates.efficiency.is_some();
+
         // Create a temporary NewLoad for calculations
         let temp_load = NewLoad {
             tag: updates.tag.clone().unwrap_or(current_load.tag),
@@ -667,10 +767,12 @@ impl Database {
             power_kw: updates.power_kw.or(current_load.power_kw),
             power_hp: updates.power_hp.or(current_load.power_hp),
             voltage: updates.voltage.or(current_load.voltage),
-            current: updates.current.or(current_load.current),
+            phases: updates.phases.or(current_load.phases),
+            current: updates.current.or(if current_inputs_changed { None } else { current_load.current }),
             power_factor: updates.power_factor.or(current_load.power_factor),
             efficiency: updates.efficiency.or(current_load.efficiency),
             demand_factor: updates.demand_factor.or(current_load.demand_factor),
+            continuous: updates.continuous.or(current_load.continuous),
             cable_id: updates.cable_id.or(current_load.cable_id),
             feeder_cable: updates.feeder_cable.clone().or(current_load.feeder_cable),
             starter_type: updates.starter_type.clone().or(current_load.starter_type),
@@ -700,7 +802,9 @@ impl Database {
              starter_type = COALESCE(?15, starter_type),
              protection_type = COALESCE(?16, protection_type),
              notes = COALESCE(?17, notes),
-             updated_at = ?18
+             updated_at = ?18,
+             phases = COALESCE(?20, phases),
+             continuous = COALESCE(?21, continuous)
              WHERE id = ?19"
         )?;
 
@@ -723,13 +827,16 @@ impl Database {
             updates.protection_type,
             updates.notes,
             now.to_rfc3339(),
-            id
+            id,
+            updates.phases,
+            updates.continuous
         ])?;
 
         self.get_load_by_id(id)
     }
 
     pub fn delete_load(&self, id: i64) -> Result<()> {
+        self.connection.execute("UPDATE protective_devices SET load_id = NULL WHERE load_id = ?1", [id])?;
         self.connection.execute("DELETE FROM loads WHERE id = ?1", [id])?;
         Ok(())
     }
@@ -746,6 +853,15 @@ impl Database {
             }
         }
 
+        // Motors take their current from the NEC Table 430.248/430.250 full-load current
+        // rather than the nameplate (430.6(A)(1))
+        let phases = load.phases.unwrap_or(3);
+        if calculated_current.is_none() && is_motor_load(load.load_type.as_deref()) {
+            if let (Some(hp), Some(voltage)) = (load.power_hp, load.voltage) {
+                calculated_current = ElectricalCalculator::motor_full_load_current(hp, voltage, phases).ok();
+            }
+        }
+
         // Calculate current from power if not provided
         if calculated_current.is_none() {
             if let (Some(kw), Some(voltage)) = (connected_kw, load.voltage) {
@@ -755,8 +871,8 @@ impl Database {
                     
                     // For 3-phase: I = P / (sqrt(3) * V * PF * eff)
                     // For single-phase: I = P / (V * PF * eff)
-                    // Assume 3-phase for now (could be made configurable)
-                    calculated_current = Some((kw * 1000.0) / (1.732 * voltage * power_factor * efficiency));
+                    let phase_factor = if phases == 1 { 1.0 } else { 1.732 };
+                    calculated_current = Some((kw * 1000.0) / (phase_factor * voltage * power_factor * efficiency));
                 }
             }
         }
@@ -784,18 +900,354 @@ impl Database {
         })
     }
 
+    /// Propose a power cable for each selected load without storing anything. Tags continue
+    /// from `get_next_cable_tag`, skipping loads that get no cable.
+    pub fn preview_power_cables(&self, project_id: i64, load_ids: &[i64], options: &PowerCableOptions) -> Result<Vec<PowerCableProposal>> {
+        let loads = self.get_loads(project_id)?;
+        let library = self.get_cable_library_items(None, None)?;
+        let calculator = ElectricalCalculator::new();
+
+        let first_tag = self.get_next_cable_tag(project_id, &options.tag_prefix)?;
+        let mut number: i32 = first_tag.rsplit('-').next().and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        let mut proposals = Vec::new();
+        for load in loads.iter().filter(|l| l.id.is_some_and(|id| load_ids.contains(&id))) {
+            let tag = format!("{}-{:03}", options.tag_prefix, number);
+            let proposal = calculator.propose_power_cable(load, tag, &library, options);
+            if proposal.cable.is_some() {
+                number += 1;
+            }
+            proposals.push(proposal);
+        }
+        Ok(proposals)
+    }
+
+    /// Insert the cables of accepted proposals and link each load to its cable, all or nothing
+    pub fn accept_power_cables(&self, project_id: i64, proposals: &[PowerCableProposal]) -> Result<Vec<Cable>> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let now = Utc::now().to_rfc3339();
+        let mut created = Vec::new();
+
+        for proposal in proposals {
+            let (Some(load_id), Some(new_cable)) = (proposal.load_id, &proposal.cable) else {
+                continue;
+            };
+            let cable = self.insert_cable(project_id, new_cable)?;
+            self.connection.execute(
+                "UPDATE loads SET cable_id = ?1, feeder_cable = ?2, updated_at = ?3 WHERE id = ?4 AND project_id = ?5",
+                params![cable.id, cable.tag, now, load_id, project_id],
+            )?;
+            created.push(cable);
+        }
+        transaction.commit()?;
+
+        Ok(created)
+    }
+
+    // Route segment operations
+    pub fn get_cable_route_segments(&self, cable_id: i64) -> Result<Vec<CableRouteSegment>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT s.id, s.cable_id, s.sequence, s.conduit_id, s.tray_id, COALESCE(c.tag, t.tag)
+             FROM cable_route_segments s
+             LEFT JOIN conduits c ON c.id = s.conduit_id
+             LEFT JOIN trays t ON t.id = s.tray_id
+             WHERE s.cable_id = ?1 ORDER BY s.sequence"
+        )?;
+
+        let segment_iter = stmt.query_map([cable_id], Self::row_to_route_segment)?;
+        segment_iter.collect()
+    }
+
+    /// Route segments of every cable in a project, ordered by cable and sequence
+    pub fn get_project_route_segments(&self, project_id: i64) -> Result<Vec<CableRouteSegment>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT s.id, s.cable_id, s.sequence, s.conduit_id, s.tray_id, COALESCE(c.tag, t.tag)
+             FROM cable_route_segments s
+             JOIN cables k ON k.id = s.cable_id
+             LEFT JOIN conduits c ON c.id = s.conduit_id
+             LEFT JOIN trays t ON t.id = s.tray_id
+             WHERE k.project_id = ?1 ORDER BY s.cable_id, s.sequence"
+        )?;
+
+        let segment_iter = stmt.query_map([project_id], Self::row_to_route_segment)?;
+        segment_iter.collect()
+    }
+
+    /// Replace a cable's route with the given raceways in order. The route text is rewritten to
+    /// their tags, and the fill of every raceway the cable left or joined is recalculated.
+    pub fn set_cable_route_segments(&self, cable_id: i64, segments: &[NewCableRouteSegment]) -> Result<Vec<CableRouteSegment>> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let current = self.store_route_segments(cable_id, segments)?;
+        transaction.commit()?;
+
+        Ok(current)
+    }
+
+    /// Auto-route the selected cables through the project's raceway network without storing
+    /// anything. Cables are routed in tag order, each one occupying its raceways for the next.
+    pub fn preview_cable_routes(&self, project_id: i64, cable_ids: &[i64]) -> Result<Vec<CableRouteProposal>> {
+        let cables = self.get_cables(project_id)?;
+        let mut network = RacewayNetwork::new(
+            &self.get_conduits(project_id)?,
+            &self.get_trays(project_id)?,
+            &cables,
+            &self.get_project_route_segments(project_id)?,
+        );
+        let validator = CableValidator::for_project(self, project_id)?;
+
+        Ok(cables.iter()
+            .filter(|cable| cable.id.is_some_and(|id| cable_ids.contains(&id)))
+            .map(|cable| network.route(cable, &validator))
+            .collect())
+    }
+
+    /// Store the routes of accepted proposals, which also recalculates each cable's length,
+    /// all or nothing. Skipped proposals are ignored.
+    pub fn accept_cable_routes(&self, proposals: &[CableRouteProposal]) -> Result<Vec<Cable>> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let mut routed = Vec::new();
+
+        for proposal in proposals.iter().filter(|p| p.skipped_reason.is_none() && !p.segments.is_empty()) {
+            self.store_route_segments(proposal.cable_id, &proposal.segments)?;
+            routed.push(self.get_cable_by_id(proposal.cable_id)?);
+        }
+        transaction.commit()?;
+
+        Ok(routed)
+    }
+
+    fn store_route_segments(&self, cable_id: i64, segments: &[NewCableRouteSegment]) -> Result<Vec<CableRouteSegment>> {
+        let previous = self.get_cable_route_segments(cable_id)?;
+        self.write_route_segments(cable_id, segments)?;
+        self.rewrite_route_text(&[cable_id])?;
+        self.recalculate_cable_lengths(&[cable_id])?;
+        let current = self.get_cable_route_segments(cable_id)?;
+        self.recalculate_segment_fills(&previous)?;
+        self.recalculate_segment_fills(&current)?;
+        Ok(current)
+    }
+
+    /// Rebuild a cable's segments from its route text. Each part is matched by whole tag to a
+    /// conduit, then a tray, of the cable's project; parts matching neither are left in the text.
+    fn sync_route_segments(&self, cable_id: i64) -> Result<()> {
+        let cable = self.get_cable_by_id(cable_id)?;
+        let previous = self.get_cable_route_segments(cable_id)?;
+
+        let mut segments = Vec::new();
+        for tag in cable.route.as_deref().map(route_raceway_tags).into_iter().flatten() {
+            segments.extend(self.find_raceway(cable.project_id, tag)?);
+        }
+        self.write_route_segments(cable_id, &segments)?;
+        self.recalculate_cable_lengths(&[cable_id])?;
+
+        self.recalculate_segment_fills(&previous)?;
+        self.recalculate_segment_fills(&self.get_cable_route_segments(cable_id)?)
+    }
+
+    fn find_raceway(&self, project_id: i64, tag: &str) -> Result<Option<NewCableRouteSegment>> {
+        let conduit_id: Option<i64> = self.connection.query_row(
+            "SELECT id FROM conduits WHERE project_id = ?1 AND tag = ?2 COLLATE NOCASE",
+            params![project_id, tag],
+            |row| row.get(0),
+        ).optional()?;
+        if conduit_id.is_some() {
+            return Ok(Some(NewCableRouteSegment { conduit_id, tray_id: None }));
+        }
+
+        let tray_id: Option<i64> = self.connection.query_row(
+            "SELECT id FROM trays WHERE project_id = ?1 AND tag = ?2 COLLATE NOCASE",
+            params![project_id, tag],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(tray_id.map(|id| NewCableRouteSegment { conduit_id: None, tray_id: Some(id) }))
+    }
+
+    fn write_route_segments(&self, cable_id: i64, segments: &[NewCableRouteSegment]) -> Result<()> {
+        self.connection.execute("DELETE FROM cable_route_segments WHERE cable_id = ?1", [cable_id])?;
+
+        let mut stmt = self.connection.prepare(
+            "INSERT INTO cable_route_segments (cable_id, sequence, conduit_id, tray_id) VALUES (?1, ?2, ?3, ?4)"
+        )?;
+        for (index, segment) in segments.iter().enumerate() {
+            stmt.execute(params![cable_id, index as i32 + 1, segment.conduit_id, segment.tray_id])?;
+        }
+        Ok(())
+    }
+
+    /// Set the route text of cables to the tags of their segments
+    fn rewrite_route_text(&self, cable_ids: &[i64]) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        for &cable_id in cable_ids {
+            let tags: Vec<String> = self.get_cable_route_segments(cable_id)?
+                .into_iter()
+                .map(|segment| segment.raceway_tag)
+                .collect();
+            let route = if tags.is_empty() { None } else { Some(tags.join(", ")) };
+            self.connection.execute(
+                "UPDATE cables SET route = ?1, updated_at = ?2 WHERE id = ?3",
+                params![route, now, cable_id],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn cables_routed_through(&self, conduit_id: Option<i64>, tray_id: Option<i64>) -> Result<Vec<i64>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT DISTINCT cable_id FROM cable_route_segments WHERE conduit_id = ?1 OR tray_id = ?2 ORDER BY cable_id"
+        )?;
+        let id_iter = stmt.query_map(params![conduit_id, tray_id], |row| row.get(0))?;
+        id_iter.collect()
+    }
+
+    /// Cables whose route text names a raceway that was just created or renamed are routed through it
+    fn link_routes_to_raceway(&self, project_id: i64, tag: &str) -> Result<()> {
+        for cable in self.get_cables(project_id)? {
+            let names_raceway = cable.route.as_deref()
+                .is_some_and(|route| route_raceway_tags(route).any(|part| part.eq_ignore_ascii_case(tag)));
+            if let (true, Some(id)) = (names_raceway, cable.id) {
+                self.sync_route_segments(id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take a conduit or tray out of every route through it, before it is deleted
+    fn remove_raceway_from_routes(&self, conduit_id: Option<i64>, tray_id: Option<i64>) -> Result<()> {
+        let cable_ids = self.cables_routed_through(conduit_id, tray_id)?;
+        for &cable_id in &cable_ids {
+            let remaining: Vec<NewCableRouteSegment> = self.get_cable_route_segments(cable_id)?
+                .into_iter()
+                .filter(|segment| (conduit_id.is_none() || segment.conduit_id != conduit_id)
+                    && (tray_id.is_none() || segment.tray_id != tray_id))
+                .map(|segment| NewCableRouteSegment { conduit_id: segment.conduit_id, tray_id: segment.tray_id })
+                .collect();
+            self.write_route_segments(cable_id, &remaining)?;
+        }
+        self.rewrite_route_text(&cable_ids)?;
+        self.recalculate_cable_lengths(&cable_ids)
+    }
+
+    fn recalculate_segment_fills(&self, segments: &[CableRouteSegment]) -> Result<()> {
+        let conduit_ids: HashSet<i64> = segments.iter().filter_map(|segment| segment.conduit_id).collect();
+        let tray_ids: HashSet<i64> = segments.iter().filter_map(|segment| segment.tray_id).collect();
+        for id in conduit_ids {
+            self.calculate_conduit_fill_percentage(id)?;
+        }
+        for id in tray_ids {
+            self.calculate_tray_fill_percentage(id)?;
+        }
+        Ok(())
+    }
+
+    fn row_to_route_segment(row: &rusqlite::Row) -> Result<CableRouteSegment> {
+        Ok(CableRouteSegment {
+            id: Some(row.get(0)?),
+            cable_id: row.get(1)?,
+            sequence: row.get(2)?,
+            conduit_id: row.get(3)?,
+            tray_id: row.get(4)?,
+            raceway_tag: row.get(5)?,
+        })
+    }
+
+    // Cable length operations
+    pub fn get_cable_length_allowances(&self, project_id: i64) -> Result<CableLengthAllowances> {
+        let allowances = self.connection.query_row(
+            "SELECT drop_rise, termination_makeup, elevation_change FROM cable_length_allowances WHERE project_id = ?1",
+            [project_id],
+            |row| Ok(CableLengthAllowances {
+                drop_rise: row.get(0)?,
+                termination_makeup: row.get(1)?,
+                elevation_change: row.get(2)?,
+            }),
+        ).optional()?;
+        Ok(allowances.unwrap_or_default())
+    }
+
+    /// Store a project's allowances and recalculate the length of every routed cable
+    pub fn set_cable_length_allowances(&self, project_id: i64, allowances: &CableLengthAllowances) -> Result<CableLengthAllowances> {
+        let transaction = self.connection.unchecked_transaction()?;
+        self.connection.execute(
+            "INSERT INTO cable_length_allowances (project_id, drop_rise, termination_makeup, elevation_change, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(project_id) DO UPDATE SET
+             drop_rise = excluded.drop_rise,
+             termination_makeup = excluded.termination_makeup,
+             elevation_change = excluded.elevation_change,
+             updated_at = excluded.updated_at",
+            params![project_id, allowances.drop_rise, allowances.termination_makeup, allowances.elevation_change, Utc::now().to_rfc3339()],
+        )?;
+        let cable_ids: Vec<i64> = self.get_cables(project_id)?.iter().filter_map(|cable| cable.id).collect();
+        self.recalculate_cable_lengths(&cable_ids)?;
+        transaction.commit()?;
+
+        self.get_cable_length_allowances(project_id)
+    }
+
+    /// Length breakdown of every routed cable, with the difference from its entered length
+    pub fn get_cable_length_report(&self, project_id: i64) -> Result<Vec<CableLengthReport>> {
+        let allowances = self.get_cable_length_allowances(project_id)?;
+        let mut reports = Vec::new();
+        for cable in self.get_cables(project_id)? {
+            let legs = self.cable_route_legs(cable.id.unwrap_or_default())?;
+            if !legs.is_empty() {
+                reports.push(calculate_cable_length(&cable, &legs, &allowances));
+            }
+        }
+        Ok(reports)
+    }
+
+    fn cable_route_legs(&self, cable_id: i64) -> Result<Vec<RouteLeg>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT COALESCE(c.tag, t.tag), COALESCE(c.length, t.length), t.elevation
+             FROM cable_route_segments s
+             LEFT JOIN conduits c ON c.id = s.conduit_id
+             LEFT JOIN trays t ON t.id = s.tray_id
+             WHERE s.cable_id = ?1 ORDER BY s.sequence"
+        )?;
+
+        let leg_iter = stmt.query_map([cable_id], |row| {
+            Ok(RouteLeg {
+                tag: row.get(0)?,
+                length: row.get(1)?,
+                tray_elevation: row.get(2)?,
+            })
+        })?;
+        leg_iter.collect()
+    }
+
+    /// Store the engine's length for cables whose route, raceway lengths or spare changed.
+    /// A cable with an empty route, or through a raceway without a length, gets no length.
+    fn recalculate_cable_lengths(&self, cable_ids: &[i64]) -> Result<()> {
+        let mut allowances: HashMap<i64, CableLengthAllowances> = HashMap::new();
+        for &cable_id in cable_ids {
+            let cable = self.get_cable_by_id(cable_id)?;
+            let project_allowances = match allowances.get(&cable.project_id) {
+                Some(project_allowances) => project_allowances.clone(),
+                None => {
+                    let project_allowances = self.get_cable_length_allowances(cable.project_id)?;
+                    allowances.insert(cable.project_id, project_allowances.clone());
+                    project_allowances
+                }
+            };
+            let report = calculate_cable_length(&cable, &self.cable_route_legs(cable_id)?, &project_allowances);
+            self.connection.execute(
+                "UPDATE cables SET calculated_length = ?1 WHERE id = ?2",
+                params![report.calculated_length, cable_id],
+            )?;
+        }
+        Ok(())
+    }
+
     // Conduit operations
     pub fn insert_conduit(&self, project_id: i64, conduit: &NewConduit) -> Result<Conduit> {
         let now = Utc::now();
         let revision_id = self.get_current_revision_id(project_id)?;
 
-        // Calculate initial fill percentage based on cables
-        let fill_percentage = self.calculate_conduit_fill_percentage_by_tag(project_id, &conduit.tag)?;
-
         let mut stmt = self.connection.prepare(
             "INSERT INTO conduits (project_id, revision_id, tag, type, size, internal_diameter, 
-             fill_percentage, max_fill_percentage, from_location, to_location, notes, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"
+             fill_percentage, max_fill_percentage, from_location, to_location, notes, created_at, updated_at, length)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"
         )?;
 
         let id = stmt.insert(params![
@@ -805,15 +1257,18 @@ impl Database {
             conduit.r#type,
             conduit.size,
             conduit.internal_diameter,
-            fill_percentage,
-            40.0, // Default max fill percentage (NEC standard)
+            0.0, // Cables already naming this conduit are linked below
+            40.0, // Set from the cable count with the fill below
             conduit.from_location,
             conduit.to_location,
             conduit.notes,
             now.to_rfc3339(),
-            now.to_rfc3339()
+            now.to_rfc3339(),
+            conduit.length
         ])?;
 
+        self.link_routes_to_raceway(project_id, &conduit.tag)?;
+        self.calculate_conduit_fill_percentage(id)?;
         self.get_conduit_by_id(id)
     }
 
@@ -821,7 +1276,7 @@ impl Database {
         let mut stmt = self.connection.prepare(
             "SELECT id, project_id, revision_id, tag, type, size, internal_diameter, 
              fill_percentage, max_fill_percentage, from_location, to_location, notes, 
-             created_at, updated_at
+             created_at, updated_at, length
              FROM conduits WHERE project_id = ?1 ORDER BY tag"
         )?;
 
@@ -834,6 +1289,7 @@ impl Database {
                 r#type: row.get(4)?,
                 size: row.get(5)?,
                 internal_diameter: row.get(6)?,
+                length: row.get(14)?,
                 fill_percentage: row.get(7)?,
                 max_fill_percentage: row.get(8)?,
                 from_location: row.get(9)?,
@@ -857,7 +1313,7 @@ impl Database {
         let mut stmt = self.connection.prepare(
             "SELECT id, project_id, revision_id, tag, type, size, internal_diameter, 
              fill_percentage, max_fill_percentage, from_location, to_location, notes, 
-             created_at, updated_at
+             created_at, updated_at, length
              FROM conduits WHERE id = ?1"
         )?;
 
@@ -870,6 +1326,7 @@ impl Database {
                 r#type: row.get(4)?,
                 size: row.get(5)?,
                 internal_diameter: row.get(6)?,
+                length: row.get(14)?,
                 fill_percentage: row.get(7)?,
                 max_fill_percentage: row.get(8)?,
                 from_location: row.get(9)?,
@@ -895,7 +1352,8 @@ impl Database {
              from_location = COALESCE(?5, from_location),
              to_location = COALESCE(?6, to_location),
              notes = COALESCE(?7, notes),
-             updated_at = ?8
+             updated_at = ?8,
+             length = COALESCE(?10, length)
              WHERE id = ?9"
         )?;
 
@@ -908,71 +1366,39 @@ impl Database {
             updates.to_location,
             updates.notes,
             now.to_rfc3339(),
-            id
+            id,
+            updates.length
         ])?;
 
-        // Recalculate fill percentage after update
-        let conduit = self.get_conduit_by_id(id)?;
-        let fill_percentage = self.calculate_conduit_fill_percentage_by_tag(conduit.project_id, &conduit.tag)?;
-        
-        self.connection.execute(
-            "UPDATE conduits SET fill_percentage = ?1 WHERE id = ?2",
-            params![fill_percentage, id]
-        )?;
+        if let Some(tag) = &updates.tag {
+            let conduit = self.get_conduit_by_id(id)?;
+            self.rewrite_route_text(&self.cables_routed_through(Some(id), None)?)?;
+            self.link_routes_to_raceway(conduit.project_id, tag)?;
+        }
+        if updates.length.is_some() {
+            self.recalculate_cable_lengths(&self.cables_routed_through(Some(id), None)?)?;
+        }
 
+        // Recalculate fill percentage after update
+        self.calculate_conduit_fill_percentage(id)?;
         self.get_conduit_by_id(id)
     }
 
     pub fn delete_conduit(&self, id: i64) -> Result<()> {
+        self.remove_raceway_from_routes(Some(id), None)?;
         self.connection.execute("DELETE FROM conduits WHERE id = ?1", [id])?;
         Ok(())
     }
 
-    // Helper function to calculate conduit fill percentage
-    fn calculate_conduit_fill_percentage_by_tag(&self, project_id: i64, conduit_tag: &str) -> Result<f64> {
-        // Get the conduit's internal diameter
-        let conduit_internal_area = self.get_conduit_internal_area(conduit_tag)?;
-        
-        if conduit_internal_area <= 0.0 {
-            return Ok(0.0);
-        }
-
-        // Calculate total cable area in this conduit (route field contains conduit tag)
-        let mut stmt = self.connection.prepare(
-            "SELECT COALESCE(SUM(PI() * POWER(outer_diameter/2, 2)), 0) as total_cable_area
-             FROM cables 
-             WHERE project_id = ?1 AND route LIKE ?"
-        )?;
-
-        let search_pattern = format!("%{}%", conduit_tag);
-        let total_cable_area: f64 = stmt.query_row([project_id.to_string(), search_pattern], |row| {
-            Ok(row.get(0)?)
-        })?;
-
-        // Calculate fill percentage
-        let fill_percentage = (total_cable_area / conduit_internal_area) * 100.0;
-        Ok(fill_percentage.min(100.0).max(0.0))
-    }
-
-    fn get_conduit_internal_area(&self, conduit_tag: &str) -> Result<f64> {
-        let mut stmt = self.connection.prepare(
-            "SELECT internal_diameter FROM conduits WHERE tag = ?"
-        )?;
-
-        match stmt.query_row([conduit_tag], |row| {
-            let diameter: Option<f64> = row.get(0)?;
-            Ok(diameter.unwrap_or(0.0))
-        }) {
-            Ok(diameter) => {
-                if diameter > 0.0 {
-                    // Area = π * (diameter/2)²
-                    Ok(std::f64::consts::PI * (diameter / 2.0).powi(2))
-                } else {
-                    Ok(0.0)
-                }
-            },
-            Err(_) => Ok(0.0), // Conduit not found or no diameter specified
-        }
+    /// Fill of a conduit against its NEC Chapter 9 limit for the cables routed through it.
+    /// Cables without an outer diameter are listed rather than counted.
+    pub fn check_conduit_fill(&self, conduit_id: i64) -> Result<ConduitFillCheck> {
+        let conduit = self.get_conduit_by_id(conduit_id)?;
+        let cables = self.cables_routed_through(Some(conduit_id), None)?
+            .into_iter()
+            .map(|cable_id| self.get_cable_by_id(cable_id))
+            .collect::<Result<Vec<Cable>>>()?;
+        Ok(conduit_fill::check_conduit_cables(&conduit, &cables.iter().collect::<Vec<_>>()))
     }
 
     pub fn get_conduit_summary(&self, project_id: i64) -> Result<(i32, f64, i32)> {
@@ -1089,7 +1515,7 @@ impl Database {
             tray_data.height,
             tray_data.length,
             0.0, // initial fill percentage
-            50.0, // default max fill percentage for trays (50% per NEC)
+            50.0, // Set from the NEC 392.22 method with the fill below
             tray_data.material,
             tray_data.finish,
             tray_data.from_location,
@@ -1102,6 +1528,8 @@ impl Database {
             now.to_rfc3339()
         ])?;
 
+        self.link_routes_to_raceway(project_id, &tray_data.tag)?;
+        self.calculate_tray_fill_percentage(id)?;
         self.get_tray_by_id(id)
     }
 
@@ -1145,70 +1573,34 @@ impl Database {
             id
         ])?;
 
-        // Recalculate fill percentage after update
-        let tray = self.get_tray_by_id(id)?;
-        let fill_percentage = self.calculate_tray_fill_percentage_by_tag(tray.project_id, &tray.tag)?;
-        
-        self.connection.execute(
-            "UPDATE trays SET fill_percentage = ?1 WHERE id = ?2",
-            params![fill_percentage, id]
-        )?;
+        if let Some(tag) = &updates.tag {
+            let tray = self.get_tray_by_id(id)?;
+            self.rewrite_route_text(&self.cables_routed_through(None, Some(id))?)?;
+            self.link_routes_to_raceway(tray.project_id, tag)?;
+        }
+        if updates.length.is_some() || updates.elevation.is_some() {
+            self.recalculate_cable_lengths(&self.cables_routed_through(None, Some(id))?)?;
+        }
 
+        // Recalculate fill percentage after update
+        self.calculate_tray_fill_percentage(id)?;
         self.get_tray_by_id(id)
     }
 
     pub fn delete_tray(&self, id: i64) -> Result<()> {
+        self.remove_raceway_from_routes(None, Some(id))?;
         self.connection.execute("DELETE FROM trays WHERE id = ?1", [id])?;
         Ok(())
     }
 
-    // Helper function to calculate tray fill percentage
-    fn calculate_tray_fill_percentage_by_tag(&self, project_id: i64, tray_tag: &str) -> Result<f64> {
-        // Get the tray's cross-sectional area (width × height)
-        let tray_area = self.get_tray_cross_sectional_area(tray_tag)?;
-        
-        if tray_area <= 0.0 {
-            return Ok(0.0);
-        }
-
-        // Calculate total cable cross-sectional area in this tray
-        // Assuming cables assigned to trays use tray_id or route field
-        let mut stmt = self.connection.prepare(
-            "SELECT COALESCE(SUM(PI() * POWER(outer_diameter/2, 2)), 0) as total_cable_area
-             FROM cables 
-             WHERE project_id = ?1 AND route LIKE ?"
-        )?;
-
-        let search_pattern = format!("%{}%", tray_tag);
-        let total_cable_area: f64 = stmt.query_row([project_id.to_string(), search_pattern], |row| {
-            Ok(row.get(0)?)
-        })?;
-
-        // Calculate fill percentage
-        let fill_percentage = (total_cable_area / tray_area) * 100.0;
-        Ok(fill_percentage.min(100.0).max(0.0))
-    }
-
-    fn get_tray_cross_sectional_area(&self, tray_tag: &str) -> Result<f64> {
-        let mut stmt = self.connection.prepare(
-            "SELECT width, height FROM trays WHERE tag = ?"
-        )?;
-
-        match stmt.query_row([tray_tag], |row| {
-            let width: Option<f64> = row.get(0)?;
-            let height: Option<f64> = row.get(1)?;
-            Ok((width.unwrap_or(0.0), height.unwrap_or(0.0)))
-        }) {
-            Ok((width, height)) => {
-                if width > 0.0 && height > 0.0 {
-                    // Cross-sectional area = width × height
-                    Ok(width * height)
-                } else {
-                    Ok(0.0)
-                }
-            },
-            Err(_) => Ok(0.0), // Tray not found or no dimensions specified
-        }
+    /// Fill of a tray under the NEC 392.22 method for its type and the cables routed through it
+    pub fn check_tray_fill(&self, tray_id: i64) -> Result<TrayFillCheck> {
+        let tray = self.get_tray_by_id(tray_id)?;
+        let cables = self.cables_routed_through(None, Some(tray_id))?
+            .into_iter()
+            .map(|cable_id| self.get_cable_by_id(cable_id))
+            .collect::<Result<Vec<Cable>>>()?;
+        Ok(tray_fill::check_tray_fill(&tray, &cables.iter().collect::<Vec<_>>()))
     }
 
     pub fn get_tray_summary(&self, project_id: i64) -> Result<(i32, f64, i32)> {
@@ -1227,31 +1619,774 @@ impl Database {
 
     // Public methods for fill percentage recalculation
     pub fn calculate_conduit_fill_percentage(&self, conduit_id: i64) -> Result<f64> {
-        let conduit = self.get_conduit_by_id(conduit_id)?;
-        let fill_percentage = self.calculate_conduit_fill_percentage_by_tag(conduit.project_id, &conduit.tag)?;
+        let check = self.check_conduit_fill(conduit_id)?;
+        let fill_percentage = check.fill_percentage.min(100.0).max(0.0);
         
-        // Update the conduit with the new fill percentage
+        // The maximum follows the cable count, per NEC Chapter 9 Table 1
         self.connection.execute(
-            "UPDATE conduits SET fill_percentage = ?1 WHERE id = ?2",
-            params![fill_percentage, conduit_id]
+            "UPDATE conduits SET fill_percentage = ?1, max_fill_percentage = ?2 WHERE id = ?3",
+            params![fill_percentage, check.max_fill_percentage, conduit_id]
         )?;
         
         Ok(fill_percentage)
     }
 
     pub fn calculate_tray_fill_percentage(&self, tray_id: i64) -> Result<f64> {
-        let tray = self.get_tray_by_id(tray_id)?;
-        let fill_percentage = self.calculate_tray_fill_percentage_by_tag(tray.project_id, &tray.tag)?;
+        let check = self.check_tray_fill(tray_id)?;
+        let fill_percentage = check.fill_percentage.max(0.0);
         
-        // Update the tray with the new fill percentage
+        // NEC 392.22 methods give the fill as a share of the allowance, with 100% the maximum
         self.connection.execute(
-            "UPDATE trays SET fill_percentage = ?1 WHERE id = ?2",
-            params![fill_percentage, tray_id]
+            "UPDATE trays SET fill_percentage = ?1, max_fill_percentage = ?2 WHERE id = ?3",
+            params![fill_percentage, check.max_fill_percentage, tray_id]
         )?;
         
         Ok(fill_percentage)
     }
 
+    pub fn recalculate_all_fills(&self, project_id: i64) -> Result<()> {
+        for conduit in self.get_conduits(project_id)? {
+            if let Some(id) = conduit.id {
+                self.calculate_conduit_fill_percentage(id)?;
+            }
+        }
+
+        for tray in self.get_trays(project_id)? {
+            if let Some(id) = tray.id {
+                self.calculate_tray_fill_percentage(id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Table 9 raceway column for each cable, from the conduit types and tray materials along
+    /// its route or its directly linked conduit or tray. A route through several raceways takes
+    /// the one with the highest impedance.
+    pub fn cable_raceway_types(&self, project_id: i64) -> Result<HashMap<i64, RacewayType>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT s.cable_id, c.type, t.material FROM cable_route_segments s
+             JOIN cables k ON k.id = s.cable_id
+             LEFT JOIN conduits c ON c.id = s.conduit_id
+             LEFT JOIN trays t ON t.id = s.tray_id
+             WHERE k.project_id = ?1
+             UNION ALL
+             SELECT k.id, c.type, t.material FROM cables k
+             LEFT JOIN conduits c ON c.id = k.conduit_id
+             LEFT JOIN trays t ON t.id = k.tray_id
+             WHERE k.project_id = ?1"
+        )?;
+        let rows = stmt.query_map([project_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, Option<String>>(2)?))
+        })?;
+
+        let mut raceways: HashMap<i64, RacewayType> = HashMap::new();
+        for row in rows {
+            let (cable_id, conduit_type, tray_material) = row?;
+            let raceway = conduit_type.as_deref().and_then(RacewayType::for_conduit)
+                .or_else(|| tray_material.as_deref().and_then(RacewayType::for_tray));
+            if let Some(raceway) = raceway {
+                let entry = raceways.entry(cable_id).or_insert(raceway);
+                *entry = (*entry).max(raceway);
+            }
+        }
+        Ok(raceways)
+    }
+
+    /// Recalculate voltage drop for every cable from the current of the load it feeds and the
+    /// conductor material of its library item, storing the percentage on each cable that could
+    /// be calculated. Cables that are skipped keep their previous value.
+    pub fn recalculate_voltage_drops(&self, project_id: i64) -> Result<VoltageDropReport> {
+        let cables = self.get_cables(project_id)?;
+        let loads = self.get_loads(project_id)?;
+        let library = self.get_cable_library_items(None, None)?;
+        let raceways = self.cable_raceway_types(project_id)?;
+        let calculator = ElectricalCalculator::new();
+
+        let checks: Vec<_> = cables.iter()
+            .map(|cable| calculator.check_cable_voltage_drop(cable, &loads, &library, &raceways))
+            .collect();
+
+        let transaction = self.connection.unchecked_transaction()?;
+        let now = Utc::now();
+        for check in &checks {
+            if let (Some(id), Some(result)) = (check.cable_id, &check.result) {
+                transaction.execute(
+                    "UPDATE cables SET voltage_drop_percentage = ?1, updated_at = ?2 WHERE id = ?3",
+                    params![result.voltage_drop_percentage, now.to_rfc3339(), id],
+                )?;
+            }
+        }
+        transaction.commit()?;
+
+        Ok(VoltageDropReport::new(checks))
+    }
+
+    // Fault level operations
+    pub fn insert_fault_level(&self, project_id: i64, fault_level: &NewFaultLevel) -> Result<FaultLevel> {
+        let now = Utc::now();
+
+        self.connection.execute(
+            "INSERT INTO fault_levels (project_id, bus, fault_current_ka, clearing_time, notes, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                project_id,
+                fault_level.bus,
+                fault_level.fault_current_ka,
+                fault_level.clearing_time.unwrap_or(DEFAULT_CLEARING_TIME),
+                fault_level.notes,
+                now.to_rfc3339(),
+                now.to_rfc3339()
+            ],
+        )?;
+
+        self.get_fault_level_by_id(self.connection.last_insert_rowid())
+    }
+
+    pub fn get_fault_levels(&self, project_id: i64) -> Result<Vec<FaultLevel>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, project_id, bus, fault_current_ka, clearing_time, notes, created_at, updated_at
+             FROM fault_levels WHERE project_id = ?1 ORDER BY bus"
+        )?;
+
+        let fault_level_iter = stmt.query_map([project_id], Self::row_to_fault_level)?;
+
+        let mut fault_levels = Vec::new();
+        for fault_level in fault_level_iter {
+            fault_levels.push(fault_level?);
+        }
+        Ok(fault_levels)
+    }
+
+    pub fn get_fault_level_by_id(&self, id: i64) -> Result<FaultLevel> {
+        self.connection.query_row(
+            "SELECT id, project_id, bus, fault_current_ka, clearing_time, notes, created_at, updated_at
+             FROM fault_levels WHERE id = ?1",
+            [id],
+            Self::row_to_fault_level,
+        )
+    }
+
+    pub fn update_fault_level(&self, id: i64, updates: &UpdateFaultLevel) -> Result<FaultLevel> {
+        self.connection.execute(
+            "UPDATE fault_levels SET
+             bus = COALESCE(?1, bus),
+             fault_current_ka = COALESCE(?2, fault_current_ka),
+             clearing_time = COALESCE(?3, clearing_time),
+             notes = COALESCE(?4, notes),
+             updated_at = ?5
+             WHERE id = ?6",
+            params![
+                updates.bus,
+                updates.fault_current_ka,
+                updates.clearing_time,
+                updates.notes,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )?;
+
+        self.get_fault_level_by_id(id)
+    }
+
+    pub fn delete_fault_level(&self, id: i64) -> Result<()> {
+        self.connection.execute("DELETE FROM fault_levels WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn row_to_fault_level(row: &rusqlite::Row) -> Result<FaultLevel> {
+        Ok(FaultLevel {
+            id: Some(row.get(0)?),
+            project_id: row.get(1)?,
+            bus: row.get(2)?,
+            fault_current_ka: row.get(3)?,
+            clearing_time: row.get(4)?,
+            notes: row.get(5)?,
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                .unwrap().with_timezone(&Utc),
+            updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+                .unwrap().with_timezone(&Utc),
+        })
+    }
+
+    /// Short-circuit withstand of every cable against the fault level of the bus it is fed from
+    pub fn check_short_circuit_withstand(&self, project_id: i64) -> Result<Vec<CableShortCircuitCheck>> {
+        let cables = self.get_cables(project_id)?;
+        let fault_levels = self.get_fault_levels(project_id)?;
+        let library = self.get_cable_library_items(None, None)?;
+        let calculator = ElectricalCalculator::new();
+
+        Ok(cables.iter()
+            .map(|cable| calculator.check_cable_short_circuit(cable, &fault_levels, &library))
+            .collect())
+    }
+
+    // Protective device operations
+    pub fn insert_protective_device(&self, project_id: i64, device: &NewProtectiveDevice) -> Result<ProtectiveDevice> {
+        let now = Utc::now();
+
+        self.connection.execute(
+            "INSERT INTO protective_devices (project_id, tag, device_type, frame_rating, trip_rating, trip_curve,
+             load_id, cable_id, notes, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                project_id,
+                device.tag,
+                device.device_type.as_str(),
+                device.frame_rating,
+                device.trip_rating,
+                device.trip_curve,
+                device.load_id,
+                device.cable_id,
+                device.notes,
+                now.to_rfc3339(),
+                now.to_rfc3339()
+            ],
+        )?;
+
+        self.get_protective_device_by_id(self.connection.last_insert_rowid())
+    }
+
+    pub fn get_protective_devices(&self, project_id: i64) -> Result<Vec<ProtectiveDevice>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, project_id, tag, device_type, frame_rating, trip_rating, trip_curve, load_id, cable_id,
+             notes, created_at, updated_at
+             FROM protective_devices WHERE project_id = ?1 ORDER BY tag"
+        )?;
+
+        let device_iter = stmt.query_map([project_id], Self::row_to_protective_device)?;
+
+        let mut devices = Vec::new();
+        for device in device_iter {
+            devices.push(device?);
+        }
+        Ok(devices)
+    }
+
+    pub fn get_protective_device_by_id(&self, id: i64) -> Result<ProtectiveDevice> {
+        self.connection.query_row(
+            "SELECT id, project_id, tag, device_type, frame_rating, trip_rating, trip_curve, load_id, cable_id,
+             notes, created_at, updated_at
+             FROM protective_devices WHERE id = ?1",
+            [id],
+            Self::row_to_protective_device,
+        )
+    }
+
+    pub fn update_protective_device(&self, id: i64, updates: &UpdateProtectiveDevice) -> Result<ProtectiveDevice> {
+        self.connection.execute(
+            "UPDATE protective_devices SET
+             tag = COALESCE(?1, tag),
+             device_type = COALESCE(?2, device_type),
+             frame_rating = COALESCE(?3, frame_rating),
+             trip_rating = COALESCE(?4, trip_rating),
+             trip_curve = COALESCE(?5, trip_curve),
+             load_id = CASE WHEN ?6 THEN ?7 ELSE load_id END,
+             cable_id = CASE WHEN ?8 THEN ?9 ELSE cable_id END,
+             notes = COALESCE(?10, notes),
+             updated_at = ?11
+             WHERE id = ?12",
+            params![
+                updates.tag,
+                updates.device_type.map(|t| t.as_str()),
+                updates.frame_rating,
+                updates.trip_rating,
+                updates.trip_curve,
+                updates.load_id.is_some(),
+                updates.load_id.flatten(),
+                updates.cable_id.is_some(),
+                updates.cable_id.flatten(),
+                updates.notes,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )?;
+
+        self.get_protective_device_by_id(id)
+    }
+
+    pub fn delete_protective_device(&self, id: i64) -> Result<()> {
+        self.connection.execute("DELETE FROM protective_devices WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn row_to_protective_device(row: &rusqlite::Row) -> Result<ProtectiveDevice> {
+        let device_type: String = row.get(3)?;
+        Ok(ProtectiveDevice {
+            id: Some(row.get(0)?),
+            project_id: row.get(1)?,
+            tag: row.get(2)?,
+            device_type: device_type.parse().map_err(|e: String| {
+                rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, e.into())
+            })?,
+            frame_rating: row.get(4)?,
+            trip_rating: row.get(5)?,
+            trip_curve: row.get(6)?,
+            load_id: row.get(7)?,
+            cable_id: row.get(8)?,
+            notes: row.get(9)?,
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(10)?)
+                .unwrap().with_timezone(&Utc),
+            updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(11)?)
+                .unwrap().with_timezone(&Utc),
+        })
+    }
+
+    /// NEC 430.22 conductor check for every motor load
+    pub fn check_motor_circuits(&self, project_id: i64) -> Result<Vec<MotorCircuitCheck>> {
+        let cables = self.get_cables(project_id)?;
+        let loads = self.get_loads(project_id)?;
+        let library = self.get_cable_library_items(None, None)?;
+        let calculator = ElectricalCalculator::new();
+
+        Ok(loads.iter()
+            .filter(|load| is_motor_load(load.load_type.as_deref()))
+            .map(|load| calculator.check_motor_circuit(load, &cables, &loads, &library))
+            .collect())
+    }
+
+    /// NEC 240.4 conductor protection and 430.52 motor branch-circuit checks for every device
+    pub fn check_protective_devices(&self, project_id: i64) -> Result<Vec<ProtectiveDeviceCheck>> {
+        let devices = self.get_protective_devices(project_id)?;
+        let cables = self.get_cables(project_id)?;
+        let loads = self.get_loads(project_id)?;
+        let library = self.get_cable_library_items(None, None)?;
+        let calculator = ElectricalCalculator::new();
+
+        Ok(devices.iter()
+            .map(|device| calculator.check_protective_device(device, &cables, &loads, &library))
+            .collect())
+    }
+
+    // Validation rule set operations
+    pub fn insert_validation_rule_set(&self, project_id: i64, rule_set: &NewValidationRuleSet) -> Result<ValidationRuleSet> {
+        let now = Utc::now();
+        let transaction = self.connection.unchecked_transaction()?;
+
+        if rule_set.is_active {
+            transaction.execute("UPDATE validation_rule_sets SET is_active = 0 WHERE project_id = ?1", [project_id])?;
+        }
+        transaction.execute(
+            "INSERT INTO validation_rule_sets (project_id, name, description, is_active, rules, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                project_id,
+                rule_set.name,
+                rule_set.description,
+                rule_set.is_active,
+                Self::rules_to_json(&rule_set.rules)?,
+                now.to_rfc3339(),
+                now.to_rfc3339()
+            ],
+        )?;
+        let id = self.connection.last_insert_rowid();
+        transaction.commit()?;
+
+        self.get_validation_rule_set_by_id(id)
+    }
+
+    pub fn get_validation_rule_sets(&self, project_id: i64) -> Result<Vec<ValidationRuleSet>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, project_id, name, description, is_active, rules, created_at, updated_at
+             FROM validation_rule_sets WHERE project_id = ?1 ORDER BY name"
+        )?;
+
+        let rule_set_iter = stmt.query_map([project_id], Self::row_to_validation_rule_set)?;
+
+        let mut rule_sets = Vec::new();
+        for rule_set in rule_set_iter {
+            rule_sets.push(rule_set?);
+        }
+        Ok(rule_sets)
+    }
+
+    pub fn get_validation_rule_set_by_id(&self, id: i64) -> Result<ValidationRuleSet> {
+        self.connection.query_row(
+            "SELECT id, project_id, name, description, is_active, rules, created_at, updated_at
+             FROM validation_rule_sets WHERE id = ?1",
+            [id],
+            Self::row_to_validation_rule_set,
+        )
+    }
+
+    /// The rule set `CableValidator` evaluates, if the project has chosen one
+    pub fn get_active_validation_rule_set(&self, project_id: i64) -> Result<Option<ValidationRuleSet>> {
+        match self.connection.query_row(
+            "SELECT id, project_id, name, description, is_active, rules, created_at, updated_at
+             FROM validation_rule_sets WHERE project_id = ?1 AND is_active = 1",
+            [project_id],
+            Self::row_to_validation_rule_set,
+        ) {
+            Ok(rule_set) => Ok(Some(rule_set)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn update_validation_rule_set(&self, id: i64, updates: &UpdateValidationRuleSet) -> Result<ValidationRuleSet> {
+        let rules = updates.rules.as_ref().map(Self::rules_to_json).transpose()?;
+
+        self.connection.execute(
+            "UPDATE validation_rule_sets SET
+             name = COALESCE(?1, name),
+             description = COALESCE(?2, description),
+             rules = COALESCE(?3, rules),
+             updated_at = ?4
+             WHERE id = ?5",
+            params![
+                updates.name,
+                updates.description,
+                rules,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )?;
+
+        self.get_validation_rule_set_by_id(id)
+    }
+
+    /// Make one rule set the project's active set, or go back to the built-in rules with `None`
+    pub fn set_active_validation_rule_set(&self, project_id: i64, id: Option<i64>) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute("UPDATE validation_rule_sets SET is_active = 0 WHERE project_id = ?1", [project_id])?;
+        if let Some(id) = id {
+            let updated = transaction.execute(
+                "UPDATE validation_rule_sets SET is_active = 1 WHERE id = ?1 AND project_id = ?2",
+                params![id, project_id],
+            )?;
+            if updated == 0 {
+                return Err(rusqlite::Error::QueryReturnedNoRows);
+            }
+        }
+        transaction.commit()
+    }
+
+    pub fn delete_validation_rule_set(&self, id: i64) -> Result<()> {
+        self.connection.execute("DELETE FROM validation_rule_sets WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn rules_to_json(rules: &ValidationRules) -> Result<String> {
+        serde_json::to_string(rules).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+    }
+
+    fn row_to_validation_rule_set(row: &rusqlite::Row) -> Result<ValidationRuleSet> {
+        let rules: String = row.get(5)?;
+        Ok(ValidationRuleSet {
+            id: Some(row.get(0)?),
+            project_id: row.get(1)?,
+            name: row.get(2)?,
+            description: row.get(3)?,
+            is_active: row.get(4)?,
+            rules: serde_json::from_str(&rules).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+            })?,
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                .unwrap().with_timezone(&Utc),
+            updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+                .unwrap().with_timezone(&Utc),
+        })
+    }
+
+    // Validation override operations
+    /// Record an override against the cable as it is now; it lapses once those fields change
+    pub fn insert_validation_override(&self, project_id: i64, validation_override: &NewValidationOverride) -> Result<ValidationOverride> {
+        let cable = self.get_cable_by_id(validation_override.cable_id)?;
+
+        self.connection.execute(
+            "INSERT INTO validation_overrides (project_id, cable_id, cable_tag, validation_type, field,
+             justification, user_name, fingerprint, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                project_id,
+                validation_override.cable_id,
+                cable.tag,
+                validation_override.validation_type.as_str(),
+                validation_override.field,
+                validation_override.justification,
+                validation_override.user_name,
+                override_fingerprint(&cable, &validation_override.validation_type),
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+
+        self.get_validation_override_by_id(self.connection.last_insert_rowid())
+    }
+
+    /// Every override made in the project, newest first, including revoked and expired ones
+    pub fn get_validation_overrides(&self, project_id: i64) -> Result<Vec<ValidationOverride>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, project_id, cable_id, cable_tag, validation_type, field, justification, user_name,
+             fingerprint, created_at, revoked_at
+             FROM validation_overrides WHERE project_id = ?1 ORDER BY created_at DESC, id DESC"
+        )?;
+
+        let override_iter = stmt.query_map([project_id], Self::row_to_validation_override)?;
+
+        let mut overrides = Vec::new();
+        for validation_override in override_iter {
+            overrides.push(validation_override?);
+        }
+        Ok(overrides)
+    }
+
+    pub fn get_validation_override_by_id(&self, id: i64) -> Result<ValidationOverride> {
+        self.connection.query_row(
+            "SELECT id, project_id, cable_id, cable_tag, validation_type, field, justification, user_name,
+             fingerprint, created_at, revoked_at
+             FROM validation_overrides WHERE id = ?1",
+            [id],
+            Self::row_to_validation_override,
+        )
+    }
+
+    /// Withdraw an override; the row stays for the audit trail
+    pub fn revoke_validation_override(&self, id: i64) -> Result<ValidationOverride> {
+        self.connection.execute(
+            "UPDATE validation_overrides SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+
+        self.get_validation_override_by_id(id)
+    }
+
+    fn row_to_validation_override(row: &rusqlite::Row) -> Result<ValidationOverride> {
+        let validation_type: String = row.get(4)?;
+        Ok(ValidationOverride {
+            id: Some(row.get(0)?),
+            project_id: row.get(1)?,
+            cable_id: row.get(2)?,
+            cable_tag: row.get(3)?,
+            validation_type: validation_type.parse().map_err(|e: String| {
+                rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, e.into())
+            })?,
+            field: row.get(5)?,
+            justification: row.get(6)?,
+            user_name: row.get(7)?,
+            fingerprint: row.get(8)?,
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(9)?)
+                .unwrap().with_timezone(&Utc),
+            revoked_at: row.get::<_, Option<String>>(10)?
+                .map(|revoked_at| chrono::DateTime::parse_from_rfc3339(&revoked_at).unwrap().with_timezone(&Utc)),
+        })
+    }
+
+    // Import operations
+    /// Set fields of one entity back to NULL, which the COALESCE updates cannot do. Column
+    /// names come from the import field tables, never from the file, and tags are never cleared.
+    pub fn clear_fields(&self, kind: EntityKind, id: i64, columns: &[&str]) -> Result<()> {
+        let columns: Vec<&str> = columns.iter().copied().filter(|&column| column != "tag").collect();
+        if columns.is_empty() {
+            return Ok(());
+        }
+        let assignments: Vec<String> = columns.iter().map(|column| format!("{} = NULL", column)).collect();
+        self.connection.execute(
+            &format!("UPDATE {} SET {}, updated_at = ?1 WHERE id = ?2", kind.table(), assignments.join(", ")),
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+
+        // Rerun what the matching update would have recalculated
+        let cleared = |column: &str| columns.contains(&column);
+        match kind {
+            EntityKind::Cable => {
+                if cleared("route") {
+                    self.sync_route_segments(id)?;
+                } else {
+                    if ["outer_diameter", "function", "size", "cores"].iter().any(|&column| cleared(column)) {
+                        self.recalculate_segment_fills(&self.get_cable_route_segments(id)?)?;
+                    }
+                    if cleared("spare_percentage") {
+                        self.recalculate_cable_lengths(&[id])?;
+                    }
+                }
+            }
+            EntityKind::Conduit => {
+                if cleared("length") {
+                    self.recalculate_cable_lengths(&self.cables_routed_through(Some(id), None)?)?;
+                }
+                self.calculate_conduit_fill_percentage(id)?;
+            }
+            EntityKind::Tray => {
+                if cleared("length") || cleared("elevation") {
+                    self.recalculate_cable_lengths(&self.cables_routed_through(None, Some(id))?)?;
+                }
+                self.calculate_tray_fill_percentage(id)?;
+            }
+            EntityKind::Load => {
+                let current_inputs = ["current", "load_type", "power_kw", "power_hp", "voltage", "power_factor", "efficiency"];
+                let recalculate_current = current_inputs.iter().any(|&column| cleared(column));
+                if recalculate_current || cleared("demand_factor") {
+                    let load = self.get_load_by_id(id)?;
+                    let (connected_load_kw, demand_load_kw, current) = self.calculate_load_values(&NewLoad {
+                        tag: load.tag,
+                        description: None,
+                        load_type: load.load_type,
+                        power_kw: load.power_kw,
+                        power_hp: load.power_hp,
+                        voltage: load.voltage,
+                        phases: load.phases,
+                        current: if recalculate_current { None } else { load.current },
+                        power_factor: load.power_factor,
+                        efficiency: load.efficiency,
+                        demand_factor: load.demand_factor,
+                        continuous: load.continuous,
+                        cable_id: None,
+                        feeder_cable: None,
+                        starter_type: None,
+                        protection_type: None,
+                        notes: None,
+                    });
+                    self.connection.execute(
+                        "UPDATE loads SET connected_load_kw = ?1, demand_load_kw = ?2, current = ?3 WHERE id = ?4",
+                        params![connected_load_kw, demand_load_kw, current, id],
+                    )?;
+                }
+            }
+            EntityKind::IOPoint => {}
+        }
+        Ok(())
+    }
+
+    /// Create or update cables by tag in a single transaction. Nothing is kept on a dry run
+    /// or when any row fails, so the report always describes an all-or-nothing import.
+    pub fn import_cables(&self, project_id: i64, rows: &[CableImportRow], dry_run: bool, clear_blanks: bool) -> Result<ImportReport> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let existing: HashMap<String, i64> = self.get_cables(project_id)?
+            .into_iter()
+            .filter_map(|cable| cable.id.map(|id| (cable.tag, id)))
+            .collect();
+        let mut seen_tags = HashSet::new();
+        let mut report = ImportReport::new(dry_run);
+
+        for row in rows {
+            let mut entry = ImportRowReport {
+                row: row.row,
+                tag: row.tag.clone(),
+                action: ImportAction::Error,
+                cable_id: None,
+                errors: row.errors.clone(),
+            };
+
+            if let Some(cable) = &row.cable {
+                if !seen_tags.insert(cable.tag.clone()) {
+                    entry.errors.push(format!("Tag '{}' appears more than once in the file", cable.tag));
+                } else {
+                    let result = match existing.get(&cable.tag) {
+                        Some(&id) => self.update_cable(id, &UpdateCable {
+                            tag: None,
+                            description: cable.description.clone(),
+                            function: cable.function.clone(),
+                            voltage: cable.voltage,
+                            current: cable.current,
+                            cable_type: cable.cable_type.clone(),
+                            size: cable.size.clone(),
+                            cores: cable.cores,
+                            segregation_class: cable.segregation_class.clone(),
+                            from_location: cable.from_location.clone(),
+                            from_equipment: cable.from_equipment.clone(),
+                            to_location: cable.to_location.clone(),
+                            to_equipment: cable.to_equipment.clone(),
+                            length: cable.length,
+                            spare_percentage: cable.spare_percentage,
+                            route: cable.route.clone(),
+                            manufacturer: cable.manufacturer.clone(),
+                            part_number: cable.part_number.clone(),
+                            outer_diameter: cable.outer_diameter,
+                            voltage_drop_percentage: None,
+                            segregation_warning: None,
+                            tray_id: None,
+                            conduit_id: None,
+                            notes: cable.notes.clone(),
+                        }).and_then(|updated| {
+                            if clear_blanks {
+                                let columns: Vec<&str> = row.blank_fields.iter().map(|field| field.column()).collect();
+                                self.clear_fields(EntityKind::Cable, id, &columns)?;
+                            }
+                            Ok((ImportAction::Update, updated.id))
+                        }),
+                        None => self.insert_cable(project_id, cable)
+                            .map(|created| (ImportAction::Create, created.id)),
+                    };
+
+                    match result {
+                        Ok((action, id)) => {
+                            entry.action = action;
+                            entry.cable_id = id;
+                        }
+                        Err(e) => entry.errors.push(e.to_string()),
+                    }
+                }
+            }
+
+            report.push(entry);
+        }
+
+        if dry_run || report.failed > 0 {
+            transaction.rollback()?;
+        } else {
+            transaction.commit()?;
+            report.committed = true;
+        }
+
+        Ok(report)
+    }
+
+    /// Apply a schedule markup in a single transaction, recording every changed field as a
+    /// revision change. Nothing is kept on a dry run or when any row has errors.
+    pub fn apply_markup(&self, project_id: i64, rows: Vec<MarkupRow>, dry_run: bool) -> Result<MarkupReport> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let revision_id = self.get_current_revision_id(project_id)?;
+        let mut report = MarkupReport::new(dry_run);
+
+        for mut row in rows {
+            if let (true, Some(id), false) = (row.errors.is_empty(), row.entity_id, row.changes.is_empty()) {
+                let result = match row.update.take() {
+                    Some(EntityUpdate::Cable(updates)) => self.update_cable(id, &updates).map(|_| ()),
+                    Some(EntityUpdate::IOPoint(updates)) => self.update_io_point(id, &updates).map(|_| ()),
+                    Some(EntityUpdate::Load(updates)) => self.update_load(id, &updates).map(|_| ()),
+                    Some(EntityUpdate::Conduit(updates)) => self.update_conduit(id, &updates).map(|_| ()),
+                    Some(EntityUpdate::Tray(updates)) => self.update_tray(id, &updates).map(|_| ()),
+                    None => Ok(()),
+                };
+                // Changes without a new value are cleared cells
+                let result = result.and_then(|_| {
+                    let cleared: Vec<&str> = row.changes.iter()
+                        .filter(|change| change.new_value.is_none())
+                        .map(|change| change.field.as_str())
+                        .collect();
+                    self.clear_fields(row.kind, id, &cleared)
+                });
+                let result = result.and_then(|_| {
+                    for change in &row.changes {
+                        self.insert_revision_change(revision_id, &NewRevisionChange {
+                            entity_type: row.kind.entity_type().to_string(),
+                            entity_id: id,
+                            entity_tag: Some(row.tag.clone()),
+                            change_type: "update".to_string(),
+                            field_name: Some(change.field.clone()),
+                            old_value: change.old_value.clone(),
+                            new_value: change.new_value.clone(),
+                        })?;
+                    }
+                    Ok(())
+                });
+                if let Err(e) = result {
+                    row.errors.push(e.to_string());
+                }
+            }
+            report.push(row);
+        }
+
+        if dry_run || report.failed > 0 {
+            transaction.rollback()?;
+        } else {
+            transaction.commit()?;
+            report.committed = true;
+        }
+
+        Ok(report)
+    }
+
     // Advanced Revision Tracking Operations
     pub fn create_revision(&self, project_id: i64, new_revision: &NewRevision) -> Result<Revision> {
         let now = Utc::now();
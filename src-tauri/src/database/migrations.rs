@@ -0,0 +1,901 @@
+/**
+ * Schema Migrations
+ * Versioned, ordered upgrade steps for .cfp project files
+ *
+ * The schema version is stored in SQLite's `PRAGMA user_version`. Files written
+ * before migrations existed report version 0 and are upgraded from the baseline.
+ */
+
+use rusqlite::{Connection, DatabaseName, OptionalExtension, Transaction};
+use std::path::{Path, PathBuf};
+
+/// Schema version written by this build of CableForge
+pub const CURRENT_SCHEMA_VERSION: i32 = 14;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Project file uses schema version {found}, but this version of CableForge only supports up to version {supported}. Please update CableForge to open it.")]
+    NewerSchema { found: i32, supported: i32 },
+}
+
+struct Migration {
+    version: i32,
+    description: &'static str,
+    up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// Every migration in order. Never edit a released step - append a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Baseline schema",
+        up: create_baseline_schema,
+    },
+    Migration {
+        version: 2,
+        description: "Add finish, elevation, support spacing and load rating to trays",
+        up: add_tray_installation_details,
+    },
+    Migration {
+        version: 3,
+        description: "Add parent feeder reference to cables",
+        up: add_cable_parent_feeder,
+    },
+    Migration {
+        version: 4,
+        description: "Add prospective fault levels per bus",
+        up: create_fault_levels,
+    },
+    Migration {
+        version: 5,
+        description: "Add protective devices for loads and feeders",
+        up: create_protective_devices,
+    },
+    Migration {
+        version: 6,
+        description: "Add phase count to loads",
+        up: add_load_phases,
+    },
+    Migration {
+        version: 7,
+        description: "Add continuous duty flag to loads",
+        up: add_load_continuous,
+    },
+    Migration {
+        version: 8,
+        description: "Create validation rule sets table",
+        up: create_validation_rule_sets,
+    },
+    Migration {
+        version: 9,
+        description: "Create validation overrides table",
+        up: create_validation_overrides,
+    },
+    Migration {
+        version: 10,
+        description: "Create cable route segments from route strings",
+        up: create_cable_route_segments,
+    },
+    Migration {
+        version: 11,
+        description: "Add length to conduits",
+        up: add_conduit_length,
+    },
+    Migration {
+        version: 12,
+        description: "Create cable length allowances table",
+        up: create_cable_length_allowances,
+    },
+    Migration {
+        version: 13,
+        description: "Keep validation overrides of deleted cables",
+        up: keep_overrides_of_deleted_cables,
+    },
+    Migration {
+        version: 14,
+        description: "Mark intrinsically safe classes in validation rule sets",
+        up: mark_intrinsically_safe_classes,
+    },
+];
+
+pub fn schema_version(connection: &Connection) -> rusqlite::Result<i32> {
+    connection.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring the database up to `CURRENT_SCHEMA_VERSION`.
+///
+/// When `file_path` is given and an existing project needs upgrading, a copy of the
+/// file is written next to it (see `backup_path`) before any step runs. Each step
+/// runs in its own transaction together with the version bump, so a failure leaves
+/// the file at the last completed version.
+pub fn run_migrations(connection: &mut Connection, file_path: Option<&Path>) -> Result<(), MigrationError> {
+    let version = schema_version(connection)?;
+
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(MigrationError::NewerSchema {
+            found: version,
+            supported: CURRENT_SCHEMA_VERSION,
+        });
+    }
+
+    if version == CURRENT_SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    if let Some(path) = file_path {
+        if has_user_tables(connection)? {
+            connection.backup(DatabaseName::Main, backup_path(path, version), None)?;
+        }
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        log::info!("Applying schema migration {}: {}", migration.version, migration.description);
+
+        let tx = connection.transaction()?;
+        (migration.up)(&tx)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Location of the pre-upgrade copy, e.g. `plant.cfp` -> `plant.cfp.schema-v1.bak`
+pub fn backup_path(path: &Path, version: i32) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".schema-v{}.bak", version));
+    path.with_file_name(file_name)
+}
+
+fn has_user_tables(connection: &Connection) -> rusqlite::Result<bool> {
+    let count: i64 = connection.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn column_exists(tx: &Transaction, table: &str, column: &str) -> rusqlite::Result<bool> {
+    let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn add_column_if_missing(tx: &Transaction, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    if !column_exists(tx, table, column)? {
+        tx.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+// Version 1 - the schema as it existed before versioning. Uses IF NOT EXISTS so
+// unversioned files created by earlier builds pass through unchanged.
+fn create_baseline_schema(tx: &Transaction) -> rusqlite::Result<()> {
+    // Projects table
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT,
+            client TEXT,
+            engineer TEXT,
+            major_revision TEXT NOT NULL DEFAULT 'Draft',
+            minor_revision INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Revisions table
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            major_revision TEXT NOT NULL,
+            minor_revision INTEGER NOT NULL,
+            description TEXT,
+            is_checkpoint INTEGER NOT NULL DEFAULT 0,
+            is_auto_save INTEGER NOT NULL DEFAULT 0,
+            user_name TEXT,
+            change_count INTEGER NOT NULL DEFAULT 0,
+            parent_revision_id INTEGER,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (parent_revision_id) REFERENCES revisions (id),
+            UNIQUE(project_id, major_revision, minor_revision)
+        )",
+        [],
+    )?;
+
+    // Revision changes table - tracks individual field changes
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS revision_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            revision_id INTEGER NOT NULL,
+            entity_type TEXT NOT NULL, -- 'cable', 'io_point', 'load', 'conduit', 'tray'
+            entity_id INTEGER NOT NULL,
+            entity_tag TEXT, -- for easier reference
+            change_type TEXT NOT NULL, -- 'create', 'update', 'delete'
+            field_name TEXT, -- null for create/delete
+            old_value TEXT,
+            new_value TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (revision_id) REFERENCES revisions (id)
+        )",
+        [],
+    )?;
+
+    // Cables table
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS cables (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            revision_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            description TEXT,
+            function TEXT,
+            voltage REAL,
+            current REAL,
+            cable_type TEXT,
+            size TEXT,
+            cores INTEGER,
+            segregation_class TEXT,
+            from_location TEXT,
+            from_equipment TEXT,
+            to_location TEXT,
+            to_equipment TEXT,
+            length REAL,
+            spare_percentage REAL,
+            calculated_length REAL,
+            route TEXT,
+            manufacturer TEXT,
+            part_number TEXT,
+            outer_diameter REAL,
+            voltage_drop_percentage REAL,
+            segregation_warning INTEGER DEFAULT 0,
+            tray_id INTEGER,
+            conduit_id INTEGER,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (revision_id) REFERENCES revisions (id),
+            FOREIGN KEY (tray_id) REFERENCES trays (id),
+            FOREIGN KEY (conduit_id) REFERENCES conduits (id),
+            UNIQUE(project_id, tag)
+        )",
+        [],
+    )?;
+
+    // I/O Points table
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS io_points (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            revision_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            description TEXT,
+            signal_type TEXT,
+            io_type TEXT,
+            plc_name TEXT,
+            rack INTEGER,
+            slot INTEGER,
+            channel INTEGER,
+            terminal_block TEXT,
+            cable_id INTEGER,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (revision_id) REFERENCES revisions (id),
+            FOREIGN KEY (cable_id) REFERENCES cables (id),
+            UNIQUE(project_id, tag)
+        )",
+        [],
+    )?;
+
+    // Conduits table
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS conduits (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            revision_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            type TEXT,
+            size TEXT,
+            internal_diameter REAL,
+            fill_percentage REAL DEFAULT 0,
+            max_fill_percentage REAL DEFAULT 40,
+            from_location TEXT,
+            to_location TEXT,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (revision_id) REFERENCES revisions (id),
+            UNIQUE(project_id, tag)
+        )",
+        [],
+    )?;
+
+    // Loads table
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS loads (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            revision_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            description TEXT,
+            load_type TEXT,
+            power_kw REAL,
+            power_hp REAL,
+            voltage REAL,
+            current REAL,
+            power_factor REAL,
+            efficiency REAL,
+            demand_factor REAL,
+            connected_load_kw REAL,
+            demand_load_kw REAL,
+            cable_id INTEGER,
+            feeder_cable TEXT,
+            starter_type TEXT,
+            protection_type TEXT,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (revision_id) REFERENCES revisions (id),
+            FOREIGN KEY (cable_id) REFERENCES cables (id),
+            UNIQUE(project_id, tag)
+        )",
+        [],
+    )?;
+
+    // Trays table
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS trays (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            revision_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            type TEXT,
+            material TEXT,
+            width REAL,
+            height REAL,
+            length REAL,
+            fill_percentage REAL DEFAULT 0,
+            max_fill_percentage REAL DEFAULT 50,
+            from_location TEXT,
+            to_location TEXT,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (revision_id) REFERENCES revisions (id),
+            UNIQUE(project_id, tag)
+        )",
+        [],
+    )?;
+
+    // Project templates table - stores template metadata
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS project_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT,
+            category TEXT DEFAULT 'Custom',
+            version TEXT DEFAULT '1.0',
+            created_by TEXT,
+            is_public BOOLEAN DEFAULT 0,
+            is_builtin BOOLEAN DEFAULT 0,
+            template_data TEXT NOT NULL,
+            preview_image TEXT,
+            tags TEXT,
+            usage_count INTEGER DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(name, version)
+        )",
+        [],
+    )?;
+
+    // Cable library table - stores cable type specifications
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS cable_library (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            manufacturer TEXT,
+            part_number TEXT,
+            cable_type TEXT NOT NULL,
+            size TEXT NOT NULL,
+            cores INTEGER NOT NULL,
+            voltage_rating REAL,
+            current_rating REAL,
+            outer_diameter REAL,
+            weight_per_meter REAL,
+            temperature_rating INTEGER,
+            conductor_material TEXT DEFAULT 'Copper',
+            insulation_type TEXT,
+            jacket_material TEXT,
+            shielding TEXT,
+            armor TEXT,
+            fire_rating TEXT,
+            category TEXT DEFAULT 'Power',
+            description TEXT,
+            specifications TEXT,
+            datasheet_url TEXT,
+            cost_per_meter REAL,
+            is_active BOOLEAN DEFAULT 1,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(manufacturer, part_number)
+        )",
+        [],
+    )?;
+
+    // Create indices for better performance
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cables_project ON cables(project_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cables_tag ON cables(tag)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cables_revision ON cables(revision_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cables_tray ON cables(tray_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cables_conduit ON cables(conduit_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_io_points_project ON io_points(project_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_io_points_revision ON io_points(revision_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_conduits_project ON conduits(project_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_conduits_revision ON conduits(revision_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_loads_project ON loads(project_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_loads_revision ON loads(revision_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_loads_cable ON loads(cable_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_trays_project ON trays(project_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_trays_revision ON trays(revision_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_revisions_project ON revisions(project_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_revisions_parent ON revisions(parent_revision_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_revision_changes_revision ON revision_changes(revision_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_revision_changes_entity ON revision_changes(entity_type, entity_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_templates_category ON project_templates(category)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_templates_builtin ON project_templates(is_builtin)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cable_library_type ON cable_library(cable_type)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cable_library_manufacturer ON cable_library(manufacturer)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cable_library_category ON cable_library(category)", [])?;
+
+    Ok(())
+}
+
+// Version 2 - tray columns that the tray model and queries expect
+fn add_tray_installation_details(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "trays", "finish", "TEXT")?;
+    add_column_if_missing(tx, "trays", "elevation", "REAL")?;
+    add_column_if_missing(tx, "trays", "support_spacing", "REAL")?;
+    add_column_if_missing(tx, "trays", "load_rating", "REAL")?;
+    Ok(())
+}
+
+// Version 3 - upstream feeder of each cable, for cumulative voltage drop along a distribution path
+fn add_cable_parent_feeder(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "cables", "parent_cable_id", "INTEGER REFERENCES cables (id)")?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cables_parent ON cables(parent_cable_id)", [])?;
+    Ok(())
+}
+
+// Version 4 - available fault current and clearing time at each bus, for short-circuit withstand
+fn create_fault_levels(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS fault_levels (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            bus TEXT NOT NULL,
+            fault_current_ka REAL NOT NULL,
+            clearing_time REAL NOT NULL,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            UNIQUE(project_id, bus)
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_fault_levels_project ON fault_levels(project_id)", [])?;
+    Ok(())
+}
+
+// Version 5 - breakers and fuses protecting loads and feeder cables
+fn create_protective_devices(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS protective_devices (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            device_type TEXT NOT NULL,
+            frame_rating REAL,
+            trip_rating REAL NOT NULL,
+            trip_curve TEXT,
+            load_id INTEGER,
+            cable_id INTEGER,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (load_id) REFERENCES loads (id),
+            FOREIGN KEY (cable_id) REFERENCES cables (id),
+            UNIQUE(project_id, tag)
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_protective_devices_project ON protective_devices(project_id)", [])?;
+    Ok(())
+}
+
+// Version 6 - explicit phase count, for motor full-load current and circuit calculations
+fn add_load_phases(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "loads", "phases", "INTEGER")?;
+    Ok(())
+}
+
+// Version 7 - continuous loads are sized at 125% in the load analysis
+fn add_load_continuous(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "loads", "continuous", "INTEGER")?;
+    Ok(())
+}
+
+// Version 8 - per-project segregation classes, incompatibilities and voltage bands, stored as JSON
+fn create_validation_rule_sets(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS validation_rule_sets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            is_active BOOLEAN NOT NULL DEFAULT 0,
+            rules TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            UNIQUE(project_id, name)
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_validation_rule_sets_project ON validation_rule_sets(project_id)", [])?;
+    Ok(())
+}
+
+// Version 9 - justified overrides of validation results, kept as an audit trail
+fn create_validation_overrides(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS validation_overrides (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            cable_id INTEGER NOT NULL,
+            validation_type TEXT NOT NULL,
+            field TEXT,
+            justification TEXT NOT NULL,
+            user_name TEXT,
+            fingerprint TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            revoked_at TEXT,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (cable_id) REFERENCES cables (id)
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_validation_overrides_project ON validation_overrides(project_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_validation_overrides_cable ON validation_overrides(cable_id)", [])?;
+    Ok(())
+}
+
+// Version 10 - ordered conduits and trays of each cable. Existing comma-separated routes are
+// split and each part matched to a conduit, then a tray, of the same project by whole tag;
+// parts that match neither stay in the route text only.
+fn create_cable_route_segments(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS cable_route_segments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            cable_id INTEGER NOT NULL,
+            sequence INTEGER NOT NULL,
+            conduit_id INTEGER,
+            tray_id INTEGER,
+            FOREIGN KEY (cable_id) REFERENCES cables (id),
+            FOREIGN KEY (conduit_id) REFERENCES conduits (id),
+            FOREIGN KEY (tray_id) REFERENCES trays (id),
+            CHECK ((conduit_id IS NULL) <> (tray_id IS NULL)),
+            UNIQUE(cable_id, sequence)
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cable_route_segments_cable ON cable_route_segments(cable_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cable_route_segments_conduit ON cable_route_segments(conduit_id)", [])?;
+    tx.execute("CREATE INDEX IF NOT EXISTS idx_cable_route_segments_tray ON cable_route_segments(tray_id)", [])?;
+
+    let routes: Vec<(i64, i64, String)> = {
+        let mut stmt = tx.prepare("SELECT id, project_id, route FROM cables WHERE route IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let mut find_conduit = tx.prepare("SELECT id FROM conduits WHERE project_id = ?1 AND tag = ?2 COLLATE NOCASE")?;
+    let mut find_tray = tx.prepare("SELECT id FROM trays WHERE project_id = ?1 AND tag = ?2 COLLATE NOCASE")?;
+    let mut insert = tx.prepare(
+        "INSERT INTO cable_route_segments (cable_id, sequence, conduit_id, tray_id) VALUES (?1, ?2, ?3, ?4)"
+    )?;
+    for (cable_id, project_id, route) in routes {
+        let mut sequence = 0;
+        for tag in route.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+            let conduit_id: Option<i64> = find_conduit.query_row(rusqlite::params![project_id, tag], |row| row.get(0)).optional()?;
+            let tray_id: Option<i64> = match conduit_id {
+                Some(_) => None,
+                None => find_tray.query_row(rusqlite::params![project_id, tag], |row| row.get(0)).optional()?,
+            };
+            if conduit_id.is_some() || tray_id.is_some() {
+                sequence += 1;
+                insert.execute(rusqlite::params![cable_id, sequence, conduit_id, tray_id])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Version 11 - conduit run length, so conduits can be weighted edges of the raceway network
+fn add_conduit_length(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "conduits", "length", "REAL")?;
+    Ok(())
+}
+
+// Version 12 - one row per project; projects without a row use the default allowances
+fn create_cable_length_allowances(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS cable_length_allowances (
+            project_id INTEGER PRIMARY KEY,
+            drop_rise REAL NOT NULL,
+            termination_makeup REAL NOT NULL,
+            elevation_change REAL NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects (id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Version 13 - overrides outlive their cable for the audit trail, so the cable link becomes
+// optional and the tag is copied onto each override. SQLite can't relax NOT NULL in place,
+// so the table is rebuilt.
+fn keep_overrides_of_deleted_cables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE validation_overrides_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            cable_id INTEGER,
+            cable_tag TEXT NOT NULL,
+            validation_type TEXT NOT NULL,
+            field TEXT,
+            justification TEXT NOT NULL,
+            user_name TEXT,
+            fingerprint TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            revoked_at TEXT,
+            FOREIGN KEY (project_id) REFERENCES projects (id),
+            FOREIGN KEY (cable_id) REFERENCES cables (id)
+        );
+        INSERT INTO validation_overrides_new (id, project_id, cable_id, cable_tag, validation_type, field,
+            justification, user_name, fingerprint, created_at, revoked_at)
+        SELECT o.id, o.project_id, c.id, COALESCE(c.tag, ''), o.validation_type, o.field,
+            o.justification, o.user_name, o.fingerprint, o.created_at, o.revoked_at
+        FROM validation_overrides o LEFT JOIN cables c ON c.id = o.cable_id;
+        DROP TABLE validation_overrides;
+        ALTER TABLE validation_overrides_new RENAME TO validation_overrides;
+        CREATE INDEX IF NOT EXISTS idx_validation_overrides_project ON validation_overrides(project_id);
+        CREATE INDEX IF NOT EXISTS idx_validation_overrides_cable ON validation_overrides(cable_id);",
+    )
+}
+
+// Version 14 - IS separation used to apply to any class containing "IS"; rule sets now say
+// which classes are intrinsically safe. Classes named "IS ..." are marked, which leaves out
+// look-alikes such as "Non-IS Signal". Rules that don't parse are left for the user to fix.
+fn mark_intrinsically_safe_classes(tx: &Transaction) -> rusqlite::Result<()> {
+    let rule_sets: Vec<(i64, String)> = {
+        let mut stmt = tx.prepare("SELECT id, rules FROM validation_rule_sets")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    for (id, rules) in rule_sets {
+        let Ok(mut rules) = serde_json::from_str::<serde_json::Value>(&rules) else {
+            continue;
+        };
+        let Some(classes) = rules.get_mut("segregation_classes").and_then(|classes| classes.as_array_mut()) else {
+            continue;
+        };
+        for class in classes.iter_mut().filter_map(|class| class.as_object_mut()) {
+            let intrinsically_safe = class.get("name")
+                .and_then(|name| name.as_str())
+                .is_some_and(|name| name.split_whitespace().next() == Some("IS"));
+            class.entry("intrinsically_safe").or_insert(serde_json::Value::Bool(intrinsically_safe));
+        }
+        tx.execute(
+            "UPDATE validation_rule_sets SET rules = ?1 WHERE id = ?2",
+            rusqlite::params![rules.to_string(), id],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+
+    fn temp_project_path() -> PathBuf {
+        std::env::temp_dir().join(format!("cableforge-migration-{}.cfp", uuid::Uuid::new_v4()))
+    }
+
+    fn remove_project_files(path: &Path) {
+        std::fs::remove_file(path).ok();
+        for version in 0..=CURRENT_SCHEMA_VERSION {
+            std::fs::remove_file(backup_path(path, version)).ok();
+        }
+    }
+
+    #[test]
+    fn test_new_database_is_at_current_version() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        run_migrations(&mut connection, None).unwrap();
+
+        assert_eq!(schema_version(&connection).unwrap(), CURRENT_SCHEMA_VERSION);
+
+        // Running again is a no-op
+        run_migrations(&mut connection, None).unwrap();
+        assert_eq!(schema_version(&connection).unwrap(), CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_unversioned_file_is_upgraded_after_backup() {
+        let path = temp_project_path();
+        {
+            // Trays table as written by builds before versioning
+            let connection = Connection::open(&path).unwrap();
+            connection.execute_batch(
+                "CREATE TABLE trays (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    project_id INTEGER NOT NULL,
+                    revision_id INTEGER NOT NULL,
+                    tag TEXT NOT NULL,
+                    type TEXT,
+                    material TEXT,
+                    width REAL,
+                    height REAL,
+                    length REAL,
+                    fill_percentage REAL DEFAULT 0,
+                    max_fill_percentage REAL DEFAULT 50,
+                    from_location TEXT,
+                    to_location TEXT,
+                    notes TEXT,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+                INSERT INTO trays (project_id, revision_id, tag, type, width, height, created_at, updated_at)
+                VALUES (1, 1, 'T-01', 'Ladder', 600, 100, '2024-01-01T00:00:00+00:00', '2024-01-01T00:00:00+00:00');",
+            ).unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let trays = db.get_trays(1).unwrap();
+        assert_eq!(trays.len(), 1);
+        assert_eq!(trays[0].tag, "T-01");
+        assert_eq!(trays[0].finish, None);
+        assert_eq!(schema_version(&db.connection).unwrap(), CURRENT_SCHEMA_VERSION);
+
+        // The pre-upgrade copy is untouched
+        let backup = Connection::open(backup_path(&path, 0)).unwrap();
+        assert_eq!(schema_version(&backup).unwrap(), 0);
+        let finish_columns: i64 = backup
+            .query_row("SELECT COUNT(*) FROM pragma_table_info('trays') WHERE name = 'finish'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(finish_columns, 0);
+
+        drop(db);
+        drop(backup);
+        remove_project_files(&path);
+    }
+
+    #[test]
+    fn test_current_file_is_not_backed_up_again() {
+        let path = temp_project_path();
+        drop(Database::new(&path).unwrap());
+        drop(Database::new(&path).unwrap());
+
+        for version in 0..=CURRENT_SCHEMA_VERSION {
+            assert!(!backup_path(&path, version).exists());
+        }
+
+        remove_project_files(&path);
+    }
+
+    #[test]
+    fn test_newer_file_is_rejected() {
+        let path = temp_project_path();
+        {
+            let connection = Connection::open(&path).unwrap();
+            connection.pragma_update(None, "user_version", CURRENT_SCHEMA_VERSION + 1).unwrap();
+        }
+
+        match Database::new(&path) {
+            Err(MigrationError::NewerSchema { found, supported }) => {
+                assert_eq!(found, CURRENT_SCHEMA_VERSION + 1);
+                assert_eq!(supported, CURRENT_SCHEMA_VERSION);
+            }
+            Err(other) => panic!("unexpected error: {}", other),
+            Ok(_) => panic!("newer schema should not open"),
+        }
+
+        remove_project_files(&path);
+    }
+
+    #[test]
+    fn test_route_strings_become_segments() {
+        let mut db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        db.connection.execute_batch(&format!(
+            "INSERT INTO conduits (project_id, revision_id, tag, created_at, updated_at)
+             VALUES ({project_id}, 1, 'C1', '2024-01-01T00:00:00+00:00', '2024-01-01T00:00:00+00:00'),
+                    ({project_id}, 1, 'C10', '2024-01-01T00:00:00+00:00', '2024-01-01T00:00:00+00:00');
+             INSERT INTO trays (project_id, revision_id, tag, created_at, updated_at)
+             VALUES ({project_id}, 1, 'TR-1', '2024-01-01T00:00:00+00:00', '2024-01-01T00:00:00+00:00');
+             INSERT INTO cables (project_id, revision_id, tag, route, created_at, updated_at)
+             VALUES ({project_id}, 1, 'C-001', 'tr-1, C10 ,, JB-4, C1', '2024-01-01T00:00:00+00:00', '2024-01-01T00:00:00+00:00');
+             DROP TABLE cable_route_segments;
+             PRAGMA user_version = 9;"
+        )).unwrap();
+
+        run_migrations(&mut db.connection, None).unwrap();
+
+        let segments = db.get_project_route_segments(project_id).unwrap();
+        let route: Vec<(i32, &str)> = segments.iter().map(|s| (s.sequence, s.raceway_tag.as_str())).collect();
+        assert_eq!(route, vec![(1, "TR-1"), (2, "C10"), (3, "C1")]);
+        // The route text is left as entered
+        assert_eq!(db.get_cables(project_id).unwrap()[0].route.as_deref(), Some("tr-1, C10 ,, JB-4, C1"));
+    }
+
+    #[test]
+    fn test_rule_sets_mark_intrinsically_safe_classes() {
+        let mut db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let rules = serde_json::json!({
+            "segregation_classes": [
+                { "name": "IS Signal", "description": null },
+                { "name": "Non-IS Signal", "description": null },
+                { "name": "DISTRIBUTION", "description": null },
+            ],
+            "class_incompatibilities": [],
+            "voltage_bands": [{ "name": "All", "max_voltage": null }],
+            "voltage_band_incompatibilities": [],
+        });
+        db.connection.execute(
+            "INSERT INTO validation_rule_sets (project_id, name, is_active, rules, created_at, updated_at)
+             VALUES (?1, 'Client', 1, ?2, '2024-01-01T00:00:00+00:00', '2024-01-01T00:00:00+00:00')",
+            rusqlite::params![project_id, rules.to_string()],
+        ).unwrap();
+        db.connection.pragma_update(None, "user_version", 13).unwrap();
+
+        run_migrations(&mut db.connection, None).unwrap();
+
+        let rules = db.get_active_validation_rule_set(project_id).unwrap().unwrap().rules;
+        let marked: Vec<(&str, bool)> = rules.segregation_classes.iter()
+            .map(|class| (class.name.as_str(), class.intrinsically_safe))
+            .collect();
+        assert_eq!(marked, vec![("IS Signal", true), ("Non-IS Signal", false), ("DISTRIBUTION", false)]);
+    }
+
+    #[test]
+    fn test_backup_path_keeps_original_name() {
+        let path = Path::new("/projects/plant.cfp");
+        assert_eq!(backup_path(path, 1), PathBuf::from("/projects/plant.cfp.schema-v1.bak"));
+    }
+}
@@ -1,318 +1,376 @@
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, DatabaseName, Result};
 use std::path::Path;
 
 pub mod models;
 pub mod commands;
+pub mod migrations;
+
+pub use migrations::MigrationError;
 
 pub struct Database {
     connection: Connection,
 }
 
 impl Database {
-    pub fn new(db_path: &Path) -> Result<Self> {
-        let connection = Connection::open(db_path)?;
-        let db = Database { connection };
-        db.create_tables()?;
-        Ok(db)
+    /// Open a project file, upgrading its schema in place if it was written by an older build
+    pub fn new(db_path: &Path) -> std::result::Result<Self, MigrationError> {
+        let mut connection = Connection::open(db_path)?;
+        migrations::run_migrations(&mut connection, Some(db_path))?;
+        Ok(Database { connection })
+    }
+
+    pub fn in_memory() -> std::result::Result<Self, MigrationError> {
+        let mut connection = Connection::open_in_memory()?;
+        migrations::run_migrations(&mut connection, None)?;
+        Ok(Database { connection })
+    }
+
+    /// Copy the entire database (every table, row IDs included) to a file
+    /// using SQLite's online backup API. Any existing file at `path` is replaced.
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        self.connection.backup(DatabaseName::Main, path, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::models::*;
+    use crate::import::xlsx::EntityKind;
+    use std::path::PathBuf;
+
+    fn temp_project_path() -> PathBuf {
+        std::env::temp_dir().join(format!("cableforge-test-{}.cfp", uuid::Uuid::new_v4()))
+    }
+
+    /// Compare records through their serialized form, since the models do not implement PartialEq
+    fn assert_same<T: serde::Serialize>(left: &T, right: &T) {
+        assert_eq!(serde_json::to_value(left).unwrap(), serde_json::to_value(right).unwrap());
+    }
+
+    fn new_cable(tag: &str) -> NewCable {
+        NewCable {
+            tag: tag.to_string(),
+            description: Some("Pump feeder".to_string()),
+            function: Some("Power".to_string()),
+            voltage: Some(480.0),
+            current: Some(42.0),
+            cable_type: Some("XHHW-2".to_string()),
+            size: Some("6 AWG".to_string()),
+            cores: Some(3),
+            segregation_class: Some("Power 480VAC".to_string()),
+            from_location: Some("MCC-1".to_string()),
+            from_equipment: Some("MCC-1 Bkr 3".to_string()),
+            to_location: Some("Pump Skid".to_string()),
+            to_equipment: Some("P-101".to_string()),
+            length: Some(250.0),
+            spare_percentage: Some(10.0),
+            route: Some("T-01".to_string()),
+            manufacturer: Some("Southwire".to_string()),
+            part_number: Some("SW-6-3".to_string()),
+            outer_diameter: Some(18.5),
+            tray_id: None,
+            conduit_id: None,
+            notes: Some("Round-trip test".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_save_to_file_round_trips_every_table() {
+        let db = Database::in_memory().unwrap();
+        let project = db.create_default_project().unwrap();
+        let project_id = project.id.unwrap();
+
+        let revision = db.create_revision(project_id, &NewRevision {
+            major_revision: "A".to_string(),
+            minor_revision: 1,
+            description: Some("Issued for review".to_string()),
+            is_checkpoint: true,
+            is_auto_save: false,
+            user_name: Some("tester".to_string()),
+            parent_revision_id: db.get_current_revision_id(project_id).ok(),
+        }).unwrap();
+        let revision_id = revision.id.unwrap();
+
+        let tray = db.insert_tray(&NewTray {
+            tag: "T-01".to_string(),
+            r#type: Some("Ladder".to_string()),
+            width: Some(600.0),
+            height: Some(100.0),
+            length: Some(120.0),
+            material: Some("Aluminum".to_string()),
+            finish: Some("Mill".to_string()),
+            from_location: Some("MCC-1".to_string()),
+            to_location: Some("Pump Skid".to_string()),
+            elevation: Some(12.5),
+            support_spacing: Some(8.0),
+            load_rating: Some(75.0),
+            notes: None,
+        }, project_id, revision_id).unwrap();
+        let tray_id = tray.id.unwrap();
+
+        let conduit = db.insert_conduit(project_id, &NewConduit {
+            tag: "CD-01".to_string(),
+            r#type: Some("RMC".to_string()),
+            size: Some("2".to_string()),
+            internal_diameter: Some(52.9),
+            length: Some(45.0),
+            from_location: Some("Pump Skid".to_string()),
+            to_location: Some("P-101".to_string()),
+            notes: None,
+        }).unwrap();
+
+        let cable = db.insert_cable(project_id, &new_cable("C-001")).unwrap();
+        db.connection.execute(
+            "UPDATE cables SET tray_id = ?1, conduit_id = ?2 WHERE id = ?3",
+            rusqlite::params![tray_id, conduit.id, cable.id],
+        ).unwrap();
+        let cable_id = cable.id.unwrap();
+
+        db.insert_io_point(project_id, &NewIOPoint {
+            tag: "PT-101".to_string(),
+            description: Some("Discharge pressure".to_string()),
+            signal_type: Some("4-20mA".to_string()),
+            io_type: Some("AI".to_string()),
+            plc_name: Some("PLC-1".to_string()),
+            rack: Some(0),
+            slot: Some(3),
+            channel: Some(1),
+            terminal_block: Some("TB-1".to_string()),
+            cable_id: Some(cable_id),
+            notes: None,
+        }).unwrap();
+
+        db.insert_load(project_id, &NewLoad {
+            tag: "P-101".to_string(),
+            description: Some("Transfer pump".to_string()),
+            load_type: Some("Motor".to_string()),
+            power_kw: None,
+            power_hp: Some(25.0),
+            voltage: Some(480.0),
+            phases: Some(3),
+            current: None,
+            power_factor: Some(0.85),
+            efficiency: Some(0.92),
+            demand_factor: Some(0.8),
+            continuous: Some(true),
+            cable_id: Some(cable_id),
+            feeder_cable: Some("C-001".to_string()),
+            starter_type: Some("FVNR".to_string()),
+            protection_type: Some("MCP".to_string()),
+            notes: None,
+        }).unwrap();
+
+        db.insert_revision_change(revision_id, &NewRevisionChange {
+            entity_type: "cable".to_string(),
+            entity_id: cable_id,
+            entity_tag: Some("C-001".to_string()),
+            change_type: "update".to_string(),
+            field_name: Some("length".to_string()),
+            old_value: Some("200".to_string()),
+            new_value: Some("250".to_string()),
+        }).unwrap();
+
+        let library_item = db.create_cable_library_item(&NewCableLibraryItem {
+            name: "6 AWG 3C XHHW-2".to_string(),
+            manufacturer: Some("Southwire".to_string()),
+            part_number: Some("SW-6-3".to_string()),
+            cable_type: "XHHW-2".to_string(),
+            size: "6 AWG".to_string(),
+            cores: 3,
+            voltage_rating: Some(600.0),
+            current_rating: Some(65.0),
+            outer_diameter: Some(18.5),
+            weight_per_meter: Some(0.9),
+            temperature_rating: Some(90),
+            conductor_material: "Copper".to_string(),
+            insulation_type: Some("XLPE".to_string()),
+            jacket_material: Some("PVC".to_string()),
+            shielding: None,
+            armor: None,
+            fire_rating: Some("FT4".to_string()),
+            category: "Power".to_string(),
+            description: None,
+            specifications: None,
+            datasheet_url: None,
+            cost_per_meter: Some(12.5),
+            is_active: Some(true),
+        }).unwrap();
+
+        let path = temp_project_path();
+        db.save_to_file(&path).unwrap();
+        let saved = Database::new(&path).unwrap();
+
+        assert_same(&db.get_projects().unwrap(), &saved.get_projects().unwrap());
+        assert_same(&db.get_revision_history(project_id, None).unwrap(), &saved.get_revision_history(project_id, None).unwrap());
+        assert_same(&db.get_revision_changes(revision_id).unwrap(), &saved.get_revision_changes(revision_id).unwrap());
+        assert_same(&db.get_cables(project_id).unwrap(), &saved.get_cables(project_id).unwrap());
+        assert_same(&db.get_io_points(project_id).unwrap(), &saved.get_io_points(project_id).unwrap());
+        assert_same(&db.get_loads(project_id).unwrap(), &saved.get_loads(project_id).unwrap());
+        assert_same(&db.get_conduits(project_id).unwrap(), &saved.get_conduits(project_id).unwrap());
+        assert_same(&db.get_trays(project_id).unwrap(), &saved.get_trays(project_id).unwrap());
+        assert_same(&db.get_project_route_segments(project_id).unwrap(), &saved.get_project_route_segments(project_id).unwrap());
+        assert_same(
+            &db.get_cable_library_item(library_item.id.unwrap()).unwrap(),
+            &saved.get_cable_library_item(library_item.id.unwrap()).unwrap(),
+        );
+
+        // Foreign keys point at the same rows after the save
+        let saved_cable = saved.get_cable_by_id(cable_id).unwrap();
+        assert_eq!(saved_cable.tray_id, Some(tray_id));
+        assert_eq!(saved_cable.conduit_id, conduit.id);
+        assert_eq!(saved.get_cable_route_segments(cable_id).unwrap()[0].tray_id, Some(tray_id));
+        assert_eq!(saved.get_io_points(project_id).unwrap()[0].cable_id, Some(cable_id));
+        assert_eq!(saved.get_loads(project_id).unwrap()[0].cable_id, Some(cable_id));
+        assert_eq!(saved.get_revision_changes(revision_id).unwrap()[0].revision_id, revision_id);
+
+        drop(saved);
+        std::fs::remove_file(&path).ok();
     }
 
-    pub fn in_memory() -> Result<Self> {
-        let connection = Connection::open_in_memory()?;
-        let db = Database { connection };
-        db.create_tables()?;
-        Ok(db)
+    #[test]
+    fn test_save_to_file_replaces_existing_file() {
+        let path = temp_project_path();
+
+        let first = Database::in_memory().unwrap();
+        let first_project = first.create_default_project().unwrap();
+        first.insert_cable(first_project.id.unwrap(), &new_cable("OLD-001")).unwrap();
+        first.save_to_file(&path).unwrap();
+
+        let second = Database::in_memory().unwrap();
+        let second_project = second.create_default_project().unwrap();
+        second.insert_cable(second_project.id.unwrap(), &new_cable("NEW-001")).unwrap();
+        second.save_to_file(&path).unwrap();
+
+        let saved = Database::new(&path).unwrap();
+        let cables = saved.get_cables(second_project.id.unwrap()).unwrap();
+        assert_eq!(cables.len(), 1);
+        assert_eq!(cables[0].tag, "NEW-001");
+
+        drop(saved);
+        std::fs::remove_file(&path).ok();
     }
+    #[test]
+    fn test_route_segments_match_whole_raceway_tags() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+
+        let conduit = |tag: &str| db.insert_conduit(project_id, &serde_json::from_value(serde_json::json!({
+            "tag": tag, "internal_diameter": 40.0
+        })).unwrap()).unwrap();
+        let c1 = conduit("C1");
+        let c10 = conduit("C10");
+        let tray = db.insert_tray(&serde_json::from_value(serde_json::json!({
+            "tag": "TC1", "width": 300.0, "height": 100.0
+        })).unwrap(), project_id, revision_id).unwrap();
+
+        let routed = db.insert_cable(project_id, &NewCable {
+            route: Some("C10, TC1, JB-4".to_string()),
+            ..new_cable("C-001")
+        }).unwrap();
+        let routed_id = routed.id.unwrap();
+
+        // "C1" is not a substring match for C10 or TC1, and unknown parts stay in the text only
+        let segments = db.get_cable_route_segments(routed_id).unwrap();
+        let tags: Vec<&str> = segments.iter().map(|s| s.raceway_tag.as_str()).collect();
+        assert_eq!(tags, vec!["C10", "TC1"]);
+        assert_eq!(db.get_conduit_by_id(c1.id.unwrap()).unwrap().fill_percentage, 0.0);
+        assert!(db.get_conduit_by_id(c10.id.unwrap()).unwrap().fill_percentage > 0.0);
+        // One cable may fill 53% of a conduit (NEC Chapter 9, Table 1)
+        assert_eq!(db.get_conduit_by_id(c10.id.unwrap()).unwrap().max_fill_percentage, 53.0);
+        assert!(db.get_tray_by_id(tray.id.unwrap()).unwrap().fill_percentage > 0.0);
+
+        // Inserting C1 first and reordering rewrites the route text and moves the fill
+        db.set_cable_route_segments(routed_id, &[
+            NewCableRouteSegment { conduit_id: c1.id, tray_id: None },
+            NewCableRouteSegment { conduit_id: None, tray_id: tray.id },
+        ]).unwrap();
+        assert_eq!(db.get_cable_by_id(routed_id).unwrap().route.as_deref(), Some("C1, TC1"));
+        assert!(db.get_conduit_by_id(c1.id.unwrap()).unwrap().fill_percentage > 0.0);
+        assert_eq!(db.get_conduit_by_id(c10.id.unwrap()).unwrap().fill_percentage, 0.0);
 
-    fn create_tables(&self) -> Result<()> {
-        // Projects table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS projects (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                description TEXT,
-                client TEXT,
-                engineer TEXT,
-                major_revision TEXT NOT NULL DEFAULT 'Draft',
-                minor_revision INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )",
-            [],
-        )?;
-
-        // Revisions table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS revisions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                project_id INTEGER NOT NULL,
-                major_revision TEXT NOT NULL,
-                minor_revision INTEGER NOT NULL,
-                description TEXT,
-                is_checkpoint INTEGER NOT NULL DEFAULT 0,
-                is_auto_save INTEGER NOT NULL DEFAULT 0,
-                user_name TEXT,
-                change_count INTEGER NOT NULL DEFAULT 0,
-                parent_revision_id INTEGER,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects (id),
-                FOREIGN KEY (parent_revision_id) REFERENCES revisions (id),
-                UNIQUE(project_id, major_revision, minor_revision)
-            )",
-            [],
-        )?;
-
-        // Revision changes table - tracks individual field changes
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS revision_changes (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                revision_id INTEGER NOT NULL,
-                entity_type TEXT NOT NULL, -- 'cable', 'io_point', 'load', 'conduit', 'tray'
-                entity_id INTEGER NOT NULL,
-                entity_tag TEXT, -- for easier reference
-                change_type TEXT NOT NULL, -- 'create', 'update', 'delete'
-                field_name TEXT, -- null for create/delete
-                old_value TEXT,
-                new_value TEXT,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (revision_id) REFERENCES revisions (id)
-            )",
-            [],
-        )?;
-
-        // Cables table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS cables (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                project_id INTEGER NOT NULL,
-                revision_id INTEGER NOT NULL,
-                tag TEXT NOT NULL,
-                description TEXT,
-                function TEXT,
-                voltage REAL,
-                current REAL,
-                cable_type TEXT,
-                size TEXT,
-                cores INTEGER,
-                segregation_class TEXT,
-                from_location TEXT,
-                from_equipment TEXT,
-                to_location TEXT,
-                to_equipment TEXT,
-                length REAL,
-                spare_percentage REAL,
-                calculated_length REAL,
-                route TEXT,
-                manufacturer TEXT,
-                part_number TEXT,
-                outer_diameter REAL,
-                voltage_drop_percentage REAL,
-                segregation_warning INTEGER DEFAULT 0,
-                tray_id INTEGER,
-                conduit_id INTEGER,
-                notes TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects (id),
-                FOREIGN KEY (revision_id) REFERENCES revisions (id),
-                FOREIGN KEY (tray_id) REFERENCES trays (id),
-                FOREIGN KEY (conduit_id) REFERENCES conduits (id),
-                UNIQUE(project_id, tag)
-            )",
-            [],
-        )?;
-
-        // I/O Points table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS io_points (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                project_id INTEGER NOT NULL,
-                revision_id INTEGER NOT NULL,
-                tag TEXT NOT NULL,
-                description TEXT,
-                signal_type TEXT,
-                io_type TEXT,
-                plc_name TEXT,
-                rack INTEGER,
-                slot INTEGER,
-                channel INTEGER,
-                terminal_block TEXT,
-                cable_id INTEGER,
-                notes TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects (id),
-                FOREIGN KEY (revision_id) REFERENCES revisions (id),
-                FOREIGN KEY (cable_id) REFERENCES cables (id),
-                UNIQUE(project_id, tag)
-            )",
-            [],
-        )?;
-
-        // Conduits table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS conduits (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                project_id INTEGER NOT NULL,
-                revision_id INTEGER NOT NULL,
-                tag TEXT NOT NULL,
-                type TEXT,
-                size TEXT,
-                internal_diameter REAL,
-                fill_percentage REAL DEFAULT 0,
-                max_fill_percentage REAL DEFAULT 40,
-                from_location TEXT,
-                to_location TEXT,
-                notes TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects (id),
-                FOREIGN KEY (revision_id) REFERENCES revisions (id),
-                UNIQUE(project_id, tag)
-            )",
-            [],
-        )?;
-
-        // Loads table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS loads (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                project_id INTEGER NOT NULL,
-                revision_id INTEGER NOT NULL,
-                tag TEXT NOT NULL,
-                description TEXT,
-                load_type TEXT,
-                power_kw REAL,
-                power_hp REAL,
-                voltage REAL,
-                current REAL,
-                power_factor REAL,
-                efficiency REAL,
-                demand_factor REAL,
-                connected_load_kw REAL,
-                demand_load_kw REAL,
-                cable_id INTEGER,
-                feeder_cable TEXT,
-                starter_type TEXT,
-                protection_type TEXT,
-                notes TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects (id),
-                FOREIGN KEY (revision_id) REFERENCES revisions (id),
-                FOREIGN KEY (cable_id) REFERENCES cables (id),
-                UNIQUE(project_id, tag)
-            )",
-            [],
-        )?;
-
-        // Trays table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS trays (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                project_id INTEGER NOT NULL,
-                revision_id INTEGER NOT NULL,
-                tag TEXT NOT NULL,
-                type TEXT,
-                material TEXT,
-                width REAL,
-                height REAL,
-                length REAL,
-                fill_percentage REAL DEFAULT 0,
-                max_fill_percentage REAL DEFAULT 50,
-                from_location TEXT,
-                to_location TEXT,
-                notes TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects (id),
-                FOREIGN KEY (revision_id) REFERENCES revisions (id),
-                UNIQUE(project_id, tag)
-            )",
-            [],
-        )?;
-
-        // Project templates table - stores template metadata
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS project_templates (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                description TEXT,
-                category TEXT DEFAULT 'Custom',
-                version TEXT DEFAULT '1.0',
-                created_by TEXT,
-                is_public BOOLEAN DEFAULT 0,
-                is_builtin BOOLEAN DEFAULT 0,
-                template_data TEXT NOT NULL,
-                preview_image TEXT,
-                tags TEXT,
-                usage_count INTEGER DEFAULT 0,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                UNIQUE(name, version)
-            )",
-            [],
-        )?;
-
-        // Cable library table - stores cable type specifications
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS cable_library (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                manufacturer TEXT,
-                part_number TEXT,
-                cable_type TEXT NOT NULL,
-                size TEXT NOT NULL,
-                cores INTEGER NOT NULL,
-                voltage_rating REAL,
-                current_rating REAL,
-                outer_diameter REAL,
-                weight_per_meter REAL,
-                temperature_rating INTEGER,
-                conductor_material TEXT DEFAULT 'Copper',
-                insulation_type TEXT,
-                jacket_material TEXT,
-                shielding TEXT,
-                armor TEXT,
-                fire_rating TEXT,
-                category TEXT DEFAULT 'Power',
-                description TEXT,
-                specifications TEXT,
-                datasheet_url TEXT,
-                cost_per_meter REAL,
-                is_active BOOLEAN DEFAULT 1,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                UNIQUE(manufacturer, part_number)
-            )",
-            [],
-        )?;
-
-        // Create indices for better performance
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cables_project ON cables(project_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cables_tag ON cables(tag)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cables_revision ON cables(revision_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cables_tray ON cables(tray_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cables_conduit ON cables(conduit_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_io_points_project ON io_points(project_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_io_points_revision ON io_points(revision_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_conduits_project ON conduits(project_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_conduits_revision ON conduits(revision_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_loads_project ON loads(project_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_loads_revision ON loads(revision_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_loads_cable ON loads(cable_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_trays_project ON trays(project_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_trays_revision ON trays(revision_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_revisions_project ON revisions(project_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_revisions_parent ON revisions(parent_revision_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_revision_changes_revision ON revision_changes(revision_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_revision_changes_entity ON revision_changes(entity_type, entity_id)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_templates_category ON project_templates(category)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_templates_builtin ON project_templates(is_builtin)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cable_library_type ON cable_library(cable_type)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cable_library_manufacturer ON cable_library(manufacturer)", [])?;
-        self.connection.execute("CREATE INDEX IF NOT EXISTS idx_cable_library_category ON cable_library(category)", [])?;
-
-        Ok(())
+        // Renaming or deleting a raceway keeps the routes through it in step
+        db.update_tray(tray.id.unwrap(), &serde_json::from_value(serde_json::json!({ "tag": "TR-1" })).unwrap()).unwrap();
+        assert_eq!(db.get_cable_by_id(routed_id).unwrap().route.as_deref(), Some("C1, TR-1"));
+        db.delete_conduit(c1.id.unwrap()).unwrap();
+        let segments = db.get_cable_route_segments(routed_id).unwrap();
+        assert_eq!((segments.len(), segments[0].sequence), (1, 1));
+        assert_eq!(db.get_cable_by_id(routed_id).unwrap().route.as_deref(), Some("TR-1"));
+
+        db.delete_cable(routed_id).unwrap();
+        assert_eq!(db.get_tray_by_id(tray.id.unwrap()).unwrap().fill_percentage, 0.0);
+    }
+
+    #[test]
+    fn test_conduit_fill_lists_cables_without_diameter() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let conduit = db.insert_conduit(project_id, &serde_json::from_value(serde_json::json!({
+            "tag": "C1", "type": "EMT", "size": "1"
+        })).unwrap()).unwrap();
+        let conduit_id = conduit.id.unwrap();
+
+        db.insert_cable(project_id, &NewCable {
+            route: Some("C1".to_string()),
+            outer_diameter: Some(18.0),
+            ..new_cable("C-001")
+        }).unwrap();
+        db.insert_cable(project_id, &NewCable {
+            route: Some("C1".to_string()),
+            outer_diameter: None,
+            ..new_cable("C-002")
+        }).unwrap();
+
+        // The cable without a diameter neither adds area nor moves the single-cable 53% limit
+        let check = db.check_conduit_fill(conduit_id).unwrap();
+        assert_eq!(check.cable_count, 1);
+        assert_eq!(check.max_fill_percentage, 53.0);
+        assert_eq!(check.cables_without_diameter, vec!["C-002".to_string()]);
+        assert_eq!(db.get_conduit_by_id(conduit_id).unwrap().max_fill_percentage, 53.0);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_tray_fill_follows_cable_function_and_size() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+        let tray_id = db.insert_tray(&serde_json::from_value(serde_json::json!({
+            "tag": "TR-1", "type": "LADDER", "width": 300.0, "height": 100.0
+        })).unwrap(), project_id, revision_id).unwrap().id.unwrap();
+        // One 5 in cable: 19.63 in² of cable
+        let cable_id = db.insert_cable(project_id, &NewCable {
+            function: Some("Control".to_string()),
+            size: Some("16 AWG".to_string()),
+            cores: Some(2),
+            outer_diameter: Some(127.0),
+            route: Some("TR-1".to_string()),
+            ..new_cable("C-001")
+        }).unwrap().id.unwrap();
+        let fill = || {
+            let tray = db.get_tray_by_id(tray_id).unwrap();
+            (tray.fill_percentage, tray.max_fill_percentage)
+        };
+
+        // Control only: 50% of 12 in × 3.94 in = 23.25 in²
+        let (fill_percentage, max_fill_percentage) = fill();
+        assert!(fill_percentage < max_fill_percentage);
+
+        // Power smaller than 4/0: Table 392.22(A) Column 1 allows 14 in² for 12 in
+        let update = |changes: serde_json::Value| {
+            db.update_cable(cable_id, &serde_json::from_value(changes).unwrap()).unwrap();
+        };
+        update(serde_json::json!({ "function": "Power" }));
+        let (fill_percentage, max_fill_percentage) = fill();
+        assert!((fill_percentage - 19.635 / 14.0 * 100.0).abs() < 0.01);
+        assert!(fill_percentage > max_fill_percentage);
+
+        // 4/0 goes by diameter against the 11.81 in width
+        update(serde_json::json!({ "size": "4/0 AWG" }));
+        assert!((fill().0 - 5.0 / (300.0 / 25.4) * 100.0).abs() < 0.01);
+
+        // Clearing the size takes the cable as smaller than 4/0 again
+        db.clear_fields(EntityKind::Cable, cable_id, &["size"]).unwrap();
+        assert!(fill().0 > fill().1);
+    }
+}
@@ -1,5 +1,6 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use chrono::{DateTime, Utc};
+use crate::validation::{rules::ValidationRules, ValidationType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -43,6 +44,7 @@ pub struct Cable {
     pub segregation_warning: bool,
     pub tray_id: Option<i64>,
     pub conduit_id: Option<i64>,
+    pub parent_cable_id: Option<i64>,
     pub notes: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -161,6 +163,7 @@ pub struct Conduit {
     pub r#type: Option<String>,
     pub size: Option<String>,
     pub internal_diameter: Option<f64>,
+    pub length: Option<f64>,
     pub fill_percentage: f64,
     pub max_fill_percentage: f64,
     pub from_location: Option<String>,
@@ -176,6 +179,7 @@ pub struct NewConduit {
     pub r#type: Option<String>,
     pub size: Option<String>,
     pub internal_diameter: Option<f64>,
+    pub length: Option<f64>,
     pub from_location: Option<String>,
     pub to_location: Option<String>,
     pub notes: Option<String>,
@@ -187,6 +191,7 @@ pub struct UpdateConduit {
     pub r#type: Option<String>,
     pub size: Option<String>,
     pub internal_diameter: Option<f64>,
+    pub length: Option<f64>,
     pub from_location: Option<String>,
     pub to_location: Option<String>,
     pub notes: Option<String>,
@@ -203,10 +208,12 @@ pub struct Load {
     pub power_kw: Option<f64>,
     pub power_hp: Option<f64>,
     pub voltage: Option<f64>,
+    pub phases: Option<i32>, // 1 or 3
     pub current: Option<f64>,
     pub power_factor: Option<f64>,
     pub efficiency: Option<f64>,
     pub demand_factor: Option<f64>,
+    pub continuous: Option<bool>, // 3 hours or more at maximum current
     pub connected_load_kw: Option<f64>,
     pub demand_load_kw: Option<f64>,
     pub cable_id: Option<i64>,
@@ -226,10 +233,12 @@ pub struct NewLoad {
     pub power_kw: Option<f64>,
     pub power_hp: Option<f64>,
     pub voltage: Option<f64>,
+    pub phases: Option<i32>, // 1 or 3
     pub current: Option<f64>,
     pub power_factor: Option<f64>,
     pub efficiency: Option<f64>,
     pub demand_factor: Option<f64>,
+    pub continuous: Option<bool>, // 3 hours or more at maximum current
     pub cable_id: Option<i64>,
     pub feeder_cable: Option<String>,
     pub starter_type: Option<String>,
@@ -245,10 +254,12 @@ pub struct UpdateLoad {
     pub power_kw: Option<f64>,
     pub power_hp: Option<f64>,
     pub voltage: Option<f64>,
+    pub phases: Option<i32>, // 1 or 3
     pub current: Option<f64>,
     pub power_factor: Option<f64>,
     pub efficiency: Option<f64>,
     pub demand_factor: Option<f64>,
+    pub continuous: Option<bool>, // 3 hours or more at maximum current
     pub cable_id: Option<i64>,
     pub feeder_cable: Option<String>,
     pub starter_type: Option<String>,
@@ -314,6 +325,215 @@ pub struct UpdateTray {
     pub notes: Option<String>,
 }
 
+/// One conduit or tray along a cable's route. Exactly one of `conduit_id` and `tray_id` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CableRouteSegment {
+    pub id: Option<i64>,
+    pub cable_id: i64,
+    pub sequence: i32, // 1-based position along the route
+    pub conduit_id: Option<i64>,
+    pub tray_id: Option<i64>,
+    pub raceway_tag: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCableRouteSegment {
+    pub conduit_id: Option<i64>,
+    pub tray_id: Option<i64>,
+}
+
+/// Per-project allowances the length engine adds to the raceway lengths, in meters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CableLengthAllowances {
+    pub drop_rise: f64, // from the raceway down or up to the equipment, at each end
+    pub termination_makeup: f64, // at each end
+    pub elevation_change: f64, // fittings at each change in tray elevation, on top of the height
+}
+
+impl Default for CableLengthAllowances {
+    fn default() -> Self {
+        Self {
+            drop_rise: 2.0,
+            termination_makeup: 1.0,
+            elevation_change: 0.5,
+        }
+    }
+}
+
+/// Raceway tags of a comma-separated `Cable.route`, e.g. "C1, TR-2, C10"
+pub fn route_raceway_tags(route: &str) -> impl Iterator<Item = &str> {
+    route.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+}
+
+/// Prospective fault current at a bus or source. Cables are matched to it by `from_equipment`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultLevel {
+    pub id: Option<i64>,
+    pub project_id: i64,
+    pub bus: String,
+    pub fault_current_ka: f64,
+    pub clearing_time: f64, // seconds
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewFaultLevel {
+    pub bus: String,
+    pub fault_current_ka: f64,
+    pub clearing_time: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateFaultLevel {
+    pub bus: Option<String>,
+    pub fault_current_ka: Option<f64>,
+    pub clearing_time: Option<f64>,
+    pub notes: Option<String>,
+}
+
+/// Overcurrent device type. Stored by variant name; NEC Table 430.52 sets a different
+/// motor branch-circuit limit for each.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ProtectiveDeviceType {
+    NonTimeDelayFuse,
+    DualElementFuse,
+    InstantaneousTripBreaker,
+    InverseTimeBreaker,
+}
+
+impl ProtectiveDeviceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtectiveDeviceType::NonTimeDelayFuse => "NonTimeDelayFuse",
+            ProtectiveDeviceType::DualElementFuse => "DualElementFuse",
+            ProtectiveDeviceType::InstantaneousTripBreaker => "InstantaneousTripBreaker",
+            ProtectiveDeviceType::InverseTimeBreaker => "InverseTimeBreaker",
+        }
+    }
+}
+
+impl std::str::FromStr for ProtectiveDeviceType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NonTimeDelayFuse" => Ok(ProtectiveDeviceType::NonTimeDelayFuse),
+            "DualElementFuse" => Ok(ProtectiveDeviceType::DualElementFuse),
+            "InstantaneousTripBreaker" => Ok(ProtectiveDeviceType::InstantaneousTripBreaker),
+            "InverseTimeBreaker" => Ok(ProtectiveDeviceType::InverseTimeBreaker),
+            _ => Err(format!("Unknown protective device type: {}", s)),
+        }
+    }
+}
+
+/// Breaker or fuse protecting a load's branch circuit or a feeder cable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectiveDevice {
+    pub id: Option<i64>,
+    pub project_id: i64,
+    pub tag: String,
+    pub device_type: ProtectiveDeviceType,
+    pub frame_rating: Option<f64>, // A
+    pub trip_rating: f64,          // A
+    pub trip_curve: Option<String>,
+    pub load_id: Option<i64>,
+    pub cable_id: Option<i64>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewProtectiveDevice {
+    pub tag: String,
+    pub device_type: ProtectiveDeviceType,
+    pub frame_rating: Option<f64>,
+    pub trip_rating: f64,
+    pub trip_curve: Option<String>,
+    pub load_id: Option<i64>,
+    pub cable_id: Option<i64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateProtectiveDevice {
+    pub tag: Option<String>,
+    pub device_type: Option<ProtectiveDeviceType>,
+    pub frame_rating: Option<f64>,
+    pub trip_rating: Option<f64>,
+    pub trip_curve: Option<String>,
+    /// Links are left unchanged when the field is missing and removed when it is null
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub load_id: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub cable_id: Option<Option<i64>>,
+    pub notes: Option<String>,
+}
+
+/// Tell a missing field (`None`) apart from an explicit null (`Some(None)`)
+fn double_option<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<T>>, D::Error> {
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A project's segregation philosophy; at most one rule set per project is active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationRuleSet {
+    pub id: Option<i64>,
+    pub project_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub rules: ValidationRules,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewValidationRuleSet {
+    pub name: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub rules: ValidationRules,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateValidationRuleSet {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub rules: Option<ValidationRules>,
+}
+
+/// Accepted validation finding with its justification. Kept after it is revoked or expires,
+/// as the record of who accepted what.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationOverride {
+    pub id: Option<i64>,
+    pub project_id: i64,
+    pub cable_id: Option<i64>, // None once the cable is deleted; the row stays for the audit trail
+    pub cable_tag: String,
+    pub validation_type: ValidationType,
+    pub field: Option<String>,
+    pub justification: String,
+    pub user_name: Option<String>,
+    pub fingerprint: String, // cable field values when the override was made
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewValidationOverride {
+    pub cable_id: i64,
+    pub validation_type: ValidationType,
+    pub field: Option<String>,
+    pub justification: String,
+    pub user_name: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Revision {
     pub id: Option<i64>,
@@ -0,0 +1,249 @@
+/**
+ * Schedule Export Module
+ * Builds tabular cable, I/O, load, conduit and tray schedules for deliverables
+ */
+
+use crate::database::models::*;
+use std::io::Write;
+
+pub mod xlsx;
+
+/// A single schedule cell, kept typed so spreadsheet writers can emit numbers as numbers
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl CellValue {
+    fn text(value: &Option<String>) -> Self {
+        match value {
+            Some(v) if !v.is_empty() => CellValue::Text(v.clone()),
+            _ => CellValue::Empty,
+        }
+    }
+
+    pub fn number(value: Option<f64>) -> Self {
+        value.map_or(CellValue::Empty, CellValue::Number)
+    }
+
+    fn integer(value: Option<i32>) -> Self {
+        value.map_or(CellValue::Empty, |v| CellValue::Number(v as f64))
+    }
+
+    pub fn to_display_string(&self) -> String {
+        match self {
+            CellValue::Text(text) => text.clone(),
+            CellValue::Number(number) => number.to_string(),
+            CellValue::Empty => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub name: &'static str,
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<CellValue>>,
+    /// Database ID of the entity behind each row, in row order
+    pub row_ids: Vec<Option<i64>>,
+}
+
+pub fn cable_schedule(cables: &[Cable]) -> Schedule {
+    Schedule {
+        name: "Cables",
+        columns: vec![
+            "Tag", "Description", "Function", "Voltage (V)", "Current (A)", "Cable Type", "Size",
+            "Cores", "Segregation Class", "From Location", "From Equipment", "To Location",
+            "To Equipment", "Length (ft)", "Spare (%)", "Calculated Length (ft)", "Route",
+            "Manufacturer", "Part Number", "Outer Diameter (mm)", "Voltage Drop (%)", "Notes",
+        ],
+        rows: cables.iter().map(|c| vec![
+            CellValue::Text(c.tag.clone()),
+            CellValue::text(&c.description),
+            CellValue::text(&c.function),
+            CellValue::number(c.voltage),
+            CellValue::number(c.current),
+            CellValue::text(&c.cable_type),
+            CellValue::text(&c.size),
+            CellValue::integer(c.cores),
+            CellValue::text(&c.segregation_class),
+            CellValue::text(&c.from_location),
+            CellValue::text(&c.from_equipment),
+            CellValue::text(&c.to_location),
+            CellValue::text(&c.to_equipment),
+            CellValue::number(c.length),
+            CellValue::number(c.spare_percentage),
+            CellValue::number(c.calculated_length),
+            CellValue::text(&c.route),
+            CellValue::text(&c.manufacturer),
+            CellValue::text(&c.part_number),
+            CellValue::number(c.outer_diameter),
+            CellValue::number(c.voltage_drop_percentage),
+            CellValue::text(&c.notes),
+        ]).collect(),
+        row_ids: cables.iter().map(|c| c.id).collect(),
+    }
+}
+
+pub fn io_point_schedule(io_points: &[IOPoint], cables: &[Cable]) -> Schedule {
+    Schedule {
+        name: "IO Points",
+        columns: vec![
+            "Tag", "Description", "Signal Type", "IO Type", "PLC", "Rack", "Slot", "Channel",
+            "Terminal Block", "Cable", "Notes",
+        ],
+        rows: io_points.iter().map(|io| vec![
+            CellValue::Text(io.tag.clone()),
+            CellValue::text(&io.description),
+            CellValue::text(&io.signal_type),
+            CellValue::text(&io.io_type),
+            CellValue::text(&io.plc_name),
+            CellValue::integer(io.rack),
+            CellValue::integer(io.slot),
+            CellValue::integer(io.channel),
+            CellValue::text(&io.terminal_block),
+            CellValue::text(&cable_tag(cables, io.cable_id)),
+            CellValue::text(&io.notes),
+        ]).collect(),
+        row_ids: io_points.iter().map(|io| io.id).collect(),
+    }
+}
+
+pub fn load_schedule(loads: &[Load], cables: &[Cable]) -> Schedule {
+    Schedule {
+        name: "Loads",
+        columns: vec![
+            "Tag", "Description", "Load Type", "Power (kW)", "Power (HP)", "Voltage (V)",
+            "Current (A)", "Power Factor", "Efficiency", "Demand Factor", "Connected Load (kW)",
+            "Demand Load (kW)", "Cable", "Feeder Cable", "Starter Type", "Protection Type", "Notes",
+        ],
+        rows: loads.iter().map(|l| vec![
+            CellValue::Text(l.tag.clone()),
+            CellValue::text(&l.description),
+            CellValue::text(&l.load_type),
+            CellValue::number(l.power_kw),
+            CellValue::number(l.power_hp),
+            CellValue::number(l.voltage),
+            CellValue::number(l.current),
+            CellValue::number(l.power_factor),
+            CellValue::number(l.efficiency),
+            CellValue::number(l.demand_factor),
+            CellValue::number(l.connected_load_kw),
+            CellValue::number(l.demand_load_kw),
+            CellValue::text(&cable_tag(cables, l.cable_id)),
+            CellValue::text(&l.feeder_cable),
+            CellValue::text(&l.starter_type),
+            CellValue::text(&l.protection_type),
+            CellValue::text(&l.notes),
+        ]).collect(),
+        row_ids: loads.iter().map(|l| l.id).collect(),
+    }
+}
+
+pub fn conduit_schedule(conduits: &[Conduit]) -> Schedule {
+    Schedule {
+        name: "Conduits",
+        columns: vec![
+            "Tag", "Type", "Size", "Internal Diameter (mm)", "Length (m)", "Fill (%)", "Max Fill (%)",
+            "From Location", "To Location", "Notes",
+        ],
+        rows: conduits.iter().map(|c| vec![
+            CellValue::Text(c.tag.clone()),
+            CellValue::text(&c.r#type),
+            CellValue::text(&c.size),
+            CellValue::number(c.internal_diameter),
+            CellValue::number(c.length),
+            CellValue::Number(c.fill_percentage),
+            CellValue::Number(c.max_fill_percentage),
+            CellValue::text(&c.from_location),
+            CellValue::text(&c.to_location),
+            CellValue::text(&c.notes),
+        ]).collect(),
+        row_ids: conduits.iter().map(|c| c.id).collect(),
+    }
+}
+
+pub fn tray_schedule(trays: &[Tray]) -> Schedule {
+    Schedule {
+        name: "Trays",
+        columns: vec![
+            "Tag", "Type", "Width (mm)", "Height (mm)", "Length (m)", "Fill (%)", "Max Fill (%)",
+            "Material", "Finish", "From Location", "To Location", "Elevation (mm)", "Support Spacing",
+            "Load Rating", "Notes",
+        ],
+        rows: trays.iter().map(|t| vec![
+            CellValue::Text(t.tag.clone()),
+            CellValue::text(&t.r#type),
+            CellValue::number(t.width),
+            CellValue::number(t.height),
+            CellValue::number(t.length),
+            CellValue::Number(t.fill_percentage),
+            CellValue::Number(t.max_fill_percentage),
+            CellValue::text(&t.material),
+            CellValue::text(&t.finish),
+            CellValue::text(&t.from_location),
+            CellValue::text(&t.to_location),
+            CellValue::number(t.elevation),
+            CellValue::number(t.support_spacing),
+            CellValue::number(t.load_rating),
+            CellValue::text(&t.notes),
+        ]).collect(),
+        row_ids: trays.iter().map(|t| t.id).collect(),
+    }
+}
+
+fn cable_tag(cables: &[Cable], cable_id: Option<i64>) -> Option<String> {
+    cable_id.and_then(|id| cables.iter().find(|c| c.id == Some(id)).map(|c| c.tag.clone()))
+}
+
+/// Write a schedule as CSV with a header row
+pub fn write_csv<W: Write>(schedule: &Schedule, writer: W) -> Result<(), csv::Error> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record(&schedule.columns)?;
+    for row in &schedule.rows {
+        csv_writer.write_record(row.iter().map(|cell| cell.to_display_string()))?;
+    }
+    csv_writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_conduit_schedule_csv() {
+        let now = Utc::now();
+        let conduits = vec![Conduit {
+            id: Some(7),
+            project_id: 1,
+            revision_id: 1,
+            tag: "CD-01".to_string(),
+            r#type: Some("RMC".to_string()),
+            size: Some("2".to_string()),
+            internal_diameter: Some(52.9),
+            length: Some(45.0),
+            fill_percentage: 12.5,
+            max_fill_percentage: 40.0,
+            from_location: Some("MCC-1".to_string()),
+            to_location: None,
+            notes: Some("Spare, \"future\"".to_string()),
+            created_at: now,
+            updated_at: now,
+        }];
+
+        let schedule = conduit_schedule(&conduits);
+        assert_eq!(schedule.row_ids, vec![Some(7)]);
+
+        let mut output = Vec::new();
+        write_csv(&schedule, &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "Tag,Type,Size,Internal Diameter (mm),Length (m),Fill (%),Max Fill (%),From Location,To Location,Notes");
+        assert_eq!(lines[1], "CD-01,RMC,2,52.9,45,12.5,40,MCC-1,,\"Spare, \"\"future\"\"\"");
+    }
+}
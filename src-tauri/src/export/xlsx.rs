@@ -0,0 +1,292 @@
+/**
+ * Excel Workbook Export
+ * Writes schedules as one worksheet per entity with a project title block
+ */
+
+use super::{CellValue, Schedule};
+use crate::database::{models::*, Database};
+use crate::validation::{CableValidator, ValidationResult, ValidationSeverity};
+use rust_xlsxwriter::{
+    column_number_to_name, Color, ConditionalFormatBlank, ConditionalFormatDuplicate, ConditionalFormatFormula, Format,
+    FormatBorder, Workbook, Worksheet, XlsxError,
+};
+use std::collections::HashMap;
+
+/// Header of the hidden column holding each row's database ID, used to match rows on re-import
+pub const ID_COLUMN: &str = "ID";
+pub const SEVERITY_COLUMN: &str = "Severity";
+pub const VALIDATION_COLUMN: &str = "Validation";
+
+/// Rows above the column headers: project, client, engineer, revision, schedule, blank
+const TITLE_ROWS: u32 = 6;
+
+#[derive(Debug, Clone)]
+pub struct TitleBlock {
+    pub project_name: String,
+    pub client: Option<String>,
+    pub engineer: Option<String>,
+    pub revision: String,
+}
+
+impl TitleBlock {
+    pub fn from_project(project: &Project) -> Self {
+        TitleBlock {
+            project_name: project.name.clone(),
+            client: project.client.clone(),
+            engineer: project.engineer.clone(),
+            revision: format!("{}.{}", project.major_revision, project.minor_revision),
+        }
+    }
+}
+
+/// A schedule plus the validation results for its rows. Sheets with `issues` get severity
+/// and validation columns, and rows are highlighted by their severity.
+#[derive(Debug, Clone)]
+pub struct ExportSheet {
+    pub schedule: Schedule,
+    pub issues: Option<Vec<ValidationResult>>,
+}
+
+/// Load every schedule of a project, with cables run through the validator
+pub fn project_sheets(db: &Database, project_id: i64) -> rusqlite::Result<(TitleBlock, Vec<ExportSheet>)> {
+    let projects = db.get_projects()?;
+    let project = projects
+        .iter()
+        .find(|p| p.id == Some(project_id))
+        .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+    let cables = db.get_cables(project_id)?;
+    let validation = CableValidator::for_project(db, project_id)?.validate_project(db, project_id)?;
+
+    let sheets = vec![
+        ExportSheet { schedule: super::cable_schedule(&cables), issues: Some(validation.results) },
+        ExportSheet { schedule: super::io_point_schedule(&db.get_io_points(project_id)?, &cables), issues: None },
+        ExportSheet { schedule: super::load_schedule(&db.get_loads(project_id)?, &cables), issues: None },
+        ExportSheet { schedule: super::conduit_schedule(&db.get_conduits(project_id)?), issues: None },
+        ExportSheet { schedule: super::tray_schedule(&db.get_trays(project_id)?), issues: None },
+    ];
+
+    Ok((TitleBlock::from_project(project), sheets))
+}
+
+pub fn build_workbook(title: &TitleBlock, sheets: &[ExportSheet]) -> Result<Workbook, XlsxError> {
+    let mut workbook = Workbook::new();
+    for sheet in sheets {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(sheet.schedule.name)?;
+        write_sheet(worksheet, title, sheet)?;
+    }
+    Ok(workbook)
+}
+
+fn write_sheet(worksheet: &mut Worksheet, title: &TitleBlock, sheet: &ExportSheet) -> Result<(), XlsxError> {
+    let schedule = &sheet.schedule;
+    let bold = Format::new().set_bold();
+
+    // Title block
+    worksheet.write_string_with_format(0, 0, &title.project_name, &Format::new().set_bold().set_font_size(14))?;
+    let details = [
+        ("Client", title.client.clone().unwrap_or_default()),
+        ("Engineer", title.engineer.clone().unwrap_or_default()),
+        ("Revision", title.revision.clone()),
+        ("Schedule", schedule.name.to_string()),
+    ];
+    for (row, (label, value)) in (1..).zip(details) {
+        worksheet.write_string_with_format(row, 0, label, &bold)?;
+        worksheet.write_string(row, 1, value)?;
+    }
+
+    // Column headers, followed by the severity and validation columns (if any) and the hidden ID column
+    let header_format = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(0xD9D9D9))
+        .set_border_bottom(FormatBorder::Thin);
+    let mut headers = schedule.columns.clone();
+    if sheet.issues.is_some() {
+        headers.push(SEVERITY_COLUMN);
+        headers.push(VALIDATION_COLUMN);
+    }
+    headers.push(ID_COLUMN);
+    for (col, header) in (0..).zip(&headers) {
+        worksheet.write_string_with_format(TITLE_ROWS, col, *header, &header_format)?;
+    }
+
+    let issues = issues_by_row(sheet.issues.as_deref().unwrap_or_default());
+    for (offset, (cells, id)) in schedule.rows.iter().zip(&schedule.row_ids).enumerate() {
+        let row = TITLE_ROWS + 1 + offset as u32;
+        for (col, cell) in (0..).zip(cells) {
+            match cell {
+                CellValue::Text(text) => worksheet.write_string(row, col, text)?,
+                CellValue::Number(number) => worksheet.write_number(row, col, *number)?,
+                CellValue::Empty => continue,
+            };
+        }
+
+        let mut col = cells.len() as u16;
+        if sheet.issues.is_some() {
+            let row_issues = id.and_then(|id| issues.get(&id));
+            let severity = match row_issues.map(|results| worst_severity(results)) {
+                Some(ValidationSeverity::Error) => "Error",
+                Some(ValidationSeverity::Warning) => "Warning",
+                _ => "",
+            };
+            let messages: Vec<&str> = row_issues
+                .map(|results| results.iter().map(|r| r.message.as_str()).collect())
+                .unwrap_or_default();
+            worksheet.write_string(row, col, severity)?;
+            worksheet.write_string(row, col + 1, messages.join("; "))?;
+            col += 2;
+        }
+        if let Some(id) = id {
+            worksheet.write_number(row, col, *id as f64)?;
+        }
+    }
+    if !schedule.rows.is_empty() {
+        add_highlighting(worksheet, sheet, headers.len() as u16 - 2)?;
+    }
+
+    worksheet.autofit();
+    worksheet.set_column_hidden(headers.len() as u16 - 1)?;
+    worksheet.set_freeze_panes(TITLE_ROWS + 1, 1)?;
+    Ok(())
+}
+
+/// Highlight with conditional formats so the colours follow edits made in the workbook: rows
+/// by their severity, blank or repeated tags, and raceway fill above the maximum
+fn add_highlighting(worksheet: &mut Worksheet, sheet: &ExportSheet, last_col: u16) -> Result<(), XlsxError> {
+    let schedule = &sheet.schedule;
+    let first_row = TITLE_ROWS + 1;
+    let last_row = TITLE_ROWS + schedule.rows.len() as u32;
+    let error_format = Format::new().set_background_color(Color::RGB(0xFFC7CE));
+    let warning_format = Format::new().set_background_color(Color::RGB(0xFFEB9C));
+    // Formulas are written for the first data row; Excel shifts the relative row for the rest
+    let cell = |col: usize| format!("${}{}", column_number_to_name(col as u16), first_row + 1);
+
+    if sheet.issues.is_some() {
+        let severity = cell(schedule.columns.len());
+        for (value, format) in [("Error", &error_format), ("Warning", &warning_format)] {
+            let rule = ConditionalFormatFormula::new()
+                .set_rule(format!("={}=\"{}\"", severity, value).as_str())
+                .set_format(format);
+            worksheet.add_conditional_format(first_row, 0, last_row, last_col, &rule)?;
+        }
+    }
+
+    worksheet.add_conditional_format(first_row, 0, last_row, 0, &ConditionalFormatBlank::new().set_format(&error_format))?;
+    worksheet.add_conditional_format(first_row, 0, last_row, 0, &ConditionalFormatDuplicate::new().set_format(&error_format))?;
+
+    let column = |name: &str| schedule.columns.iter().position(|c| *c == name);
+    if let (Some(fill), Some(max_fill)) = (column("Fill (%)"), column("Max Fill (%)")) {
+        let rule = ConditionalFormatFormula::new()
+            .set_rule(format!("={}>{}", cell(fill), cell(max_fill)).as_str())
+            .set_format(&error_format);
+        worksheet.add_conditional_format(first_row, fill as u16, last_row, fill as u16, &rule)?;
+    }
+    Ok(())
+}
+
+fn issues_by_row(results: &[ValidationResult]) -> HashMap<i64, Vec<&ValidationResult>> {
+    let mut by_row: HashMap<i64, Vec<&ValidationResult>> = HashMap::new();
+    for result in results {
+        if result.suppressed || matches!(result.severity, ValidationSeverity::Info) {
+            continue;
+        }
+        if let Some(id) = result.cable_id {
+            by_row.entry(id).or_default().push(result);
+        }
+    }
+    by_row
+}
+
+fn worst_severity(results: &[&ValidationResult]) -> ValidationSeverity {
+    if results.iter().any(|r| matches!(r.severity, ValidationSeverity::Error)) {
+        ValidationSeverity::Error
+    } else {
+        ValidationSeverity::Warning
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::{EntityType, ValidationType};
+
+    #[test]
+    fn test_workbook_has_one_sheet_per_schedule() {
+        let title = TitleBlock {
+            project_name: "Plant Upgrade".to_string(),
+            client: Some("Acme".to_string()),
+            engineer: None,
+            revision: "A.2".to_string(),
+        };
+        let schedule = Schedule {
+            name: "Cables",
+            columns: vec!["Tag", "Length (ft)"],
+            rows: vec![
+                vec![CellValue::Text("C-001".to_string()), CellValue::Number(250.0)],
+                vec![CellValue::Text("C-002".to_string()), CellValue::Empty],
+            ],
+            row_ids: vec![Some(1), Some(2)],
+        };
+        let issue = ValidationResult {
+            entity_type: EntityType::Cable,
+            entity_id: Some(2),
+            entity_tag: "C-002".to_string(),
+            cable_id: Some(2),
+            cable_tag: "C-002".to_string(),
+            severity: ValidationSeverity::Error,
+            validation_type: ValidationType::RequiredField,
+            message: "Length is required".to_string(),
+            field: Some("length".to_string()),
+            suggested_fix: None,
+            override_allowed: false,
+            suppressed: false,
+        };
+        let sheets = vec![
+            ExportSheet { schedule: schedule.clone(), issues: Some(vec![issue]) },
+            ExportSheet { schedule: Schedule { name: "Trays", ..schedule }, issues: None },
+        ];
+
+        let mut workbook = build_workbook(&title, &sheets).unwrap();
+        assert_eq!(workbook.worksheets().len(), 2);
+        assert_eq!(workbook.worksheets()[1].name(), "Trays");
+
+        let bytes = workbook.save_to_buffer().unwrap();
+        assert_eq!(&bytes[..2], b"PK");
+
+        // Highlighting is a conditional format keyed on the severity column, not a fixed fill
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut xml = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("xl/worksheets/sheet1.xml").unwrap(), &mut xml).unwrap();
+        assert!(xml.contains("<conditionalFormatting sqref=\"A8:D9\">"));
+        assert!(xml.contains("<formula>$C8=\"Error\"</formula>"));
+        assert!(xml.contains("type=\"duplicateValues\""));
+    }
+
+    #[test]
+    fn test_issues_group_by_cable_and_skip_info() {
+        let result = |id, severity| ValidationResult {
+            entity_type: EntityType::Cable,
+            entity_id: Some(id),
+            entity_tag: String::new(),
+            cable_id: Some(id),
+            cable_tag: String::new(),
+            severity,
+            validation_type: ValidationType::InvalidValue,
+            message: String::new(),
+            field: None,
+            suggested_fix: None,
+            override_allowed: true,
+            suppressed: false,
+        };
+        let results = vec![
+            result(1, ValidationSeverity::Warning),
+            result(1, ValidationSeverity::Error),
+            result(2, ValidationSeverity::Info),
+        ];
+        let by_row = issues_by_row(&results);
+
+        assert_eq!(by_row.len(), 1);
+        assert!(matches!(worst_severity(&by_row[&1]), ValidationSeverity::Error));
+    }
+}
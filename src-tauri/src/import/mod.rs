@@ -0,0 +1,523 @@
+/**
+ * Cable Schedule Import Module
+ * Maps CSV columns onto cable fields and parses values with units into cable rows
+ */
+
+use crate::database::models::*;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use thiserror::Error;
+
+pub mod xlsx;
+
+/// Cable fields that an import column can be mapped onto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CableField {
+    Tag,
+    Description,
+    Function,
+    Voltage,
+    Current,
+    CableType,
+    Size,
+    Cores,
+    SegregationClass,
+    FromLocation,
+    FromEquipment,
+    ToLocation,
+    ToEquipment,
+    Length,
+    SparePercentage,
+    Route,
+    Manufacturer,
+    PartNumber,
+    OuterDiameter,
+    Notes,
+}
+
+impl CableField {
+    /// Guess the field for a column header. Matches field names as well as the
+    /// headers written by the cable schedule export, so exports import cleanly.
+    pub fn from_header(header: &str) -> Option<Self> {
+        let key: String = header
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+
+        Some(match key.as_str() {
+            "tag" | "cabletag" | "cable" => CableField::Tag,
+            "description" => CableField::Description,
+            "function" => CableField::Function,
+            "voltage" => CableField::Voltage,
+            "current" => CableField::Current,
+            "cabletype" | "type" => CableField::CableType,
+            "size" => CableField::Size,
+            "cores" | "conductors" => CableField::Cores,
+            "segregationclass" | "segregation" => CableField::SegregationClass,
+            "fromlocation" | "from" => CableField::FromLocation,
+            "fromequipment" => CableField::FromEquipment,
+            "tolocation" | "to" => CableField::ToLocation,
+            "toequipment" => CableField::ToEquipment,
+            "length" => CableField::Length,
+            "spare" | "sparepercentage" => CableField::SparePercentage,
+            "route" => CableField::Route,
+            "manufacturer" => CableField::Manufacturer,
+            "partnumber" => CableField::PartNumber,
+            "outerdiameter" | "od" => CableField::OuterDiameter,
+            "notes" => CableField::Notes,
+            _ => return None,
+        })
+    }
+
+    /// The `cables` column the field is stored in
+    pub fn column(self) -> &'static str {
+        match self {
+            CableField::Tag => "tag",
+            CableField::Description => "description",
+            CableField::Function => "function",
+            CableField::Voltage => "voltage",
+            CableField::Current => "current",
+            CableField::CableType => "cable_type",
+            CableField::Size => "size",
+            CableField::Cores => "cores",
+            CableField::SegregationClass => "segregation_class",
+            CableField::FromLocation => "from_location",
+            CableField::FromEquipment => "from_equipment",
+            CableField::ToLocation => "to_location",
+            CableField::ToEquipment => "to_equipment",
+            CableField::Length => "length",
+            CableField::SparePercentage => "spare_percentage",
+            CableField::Route => "route",
+            CableField::Manufacturer => "manufacturer",
+            CableField::PartNumber => "part_number",
+            CableField::OuterDiameter => "outer_diameter",
+            CableField::Notes => "notes",
+        }
+    }
+
+    fn default_unit(self) -> Option<Unit> {
+        match self {
+            CableField::Length => Some(Unit::Feet),
+            CableField::OuterDiameter => Some(Unit::Millimeters),
+            _ => None,
+        }
+    }
+
+    fn accepts(self, unit: Unit) -> bool {
+        self.default_unit().is_some_and(|default| default.is_length() == unit.is_length())
+    }
+}
+
+/// Units accepted for length (stored in feet) and diameter (stored in mm) columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unit {
+    Feet,
+    Meters,
+    Inches,
+    Millimeters,
+}
+
+impl Unit {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "ft" | "feet" | "foot" | "'" => Some(Unit::Feet),
+            "m" | "meter" | "meters" | "metre" | "metres" => Some(Unit::Meters),
+            "in" | "inch" | "inches" | "\"" => Some(Unit::Inches),
+            "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => Some(Unit::Millimeters),
+            _ => None,
+        }
+    }
+
+    fn is_length(self) -> bool {
+        matches!(self, Unit::Feet | Unit::Meters)
+    }
+
+    /// Convert a value in this unit to the unit the database stores
+    fn to_stored(self, value: f64) -> f64 {
+        match self {
+            Unit::Feet | Unit::Millimeters => value,
+            Unit::Meters => value / 0.3048,
+            Unit::Inches => value * 25.4,
+        }
+    }
+}
+
+/// Maps one column of the source file onto a cable field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub header: String,
+    pub field: CableField,
+    /// Unit for values without a unit suffix; defaults to feet for length and mm for diameter
+    pub unit: Option<Unit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CableImportOptions {
+    pub mappings: Vec<ColumnMapping>,
+    pub dry_run: bool,
+    /// Blank cells clear the field on existing cables; otherwise the stored value is kept
+    #[serde(default)]
+    pub clear_blanks: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvImportPreview {
+    pub headers: Vec<String>,
+    pub suggested_mappings: Vec<ColumnMapping>,
+}
+
+/// One parsed data row; `cable` is `None` when the row has errors
+#[derive(Debug, Clone)]
+pub struct CableImportRow {
+    pub row: usize,
+    pub tag: Option<String>,
+    pub cable: Option<NewCable>,
+    pub blank_fields: Vec<CableField>, // mapped columns left empty in this row
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ImportAction {
+    Create,
+    Update,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowReport {
+    pub row: usize,
+    pub tag: Option<String>,
+    pub action: ImportAction,
+    pub cable_id: Option<i64>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub committed: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowReport>,
+}
+
+impl ImportReport {
+    pub fn new(dry_run: bool) -> Self {
+        ImportReport { dry_run, committed: false, created: 0, updated: 0, failed: 0, rows: Vec::new() }
+    }
+
+    pub fn push(&mut self, row: ImportRowReport) {
+        match row.action {
+            ImportAction::Create => self.created += 1,
+            ImportAction::Update => self.updated += 1,
+            ImportAction::Error => self.failed += 1,
+        }
+        self.rows.push(row);
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Excel error: {0}")]
+    Xlsx(#[from] calamine::XlsxError),
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("The workbook has no CableForge schedule sheets")]
+    NoSchedules,
+    #[error("Column '{0}' was not found in the file")]
+    MissingColumn(String),
+    #[error("A column must be mapped to the cable tag")]
+    NoTagColumn,
+    #[error("{0:?} is mapped from more than one column")]
+    DuplicateField(CableField),
+    #[error("Unit {unit:?} cannot be used for {field:?}")]
+    InvalidUnit { field: CableField, unit: Unit },
+}
+
+/// Read the header row and suggest a mapping for every recognised column
+pub fn preview_csv<R: Read>(reader: R) -> Result<CsvImportPreview, ImportError> {
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let headers: Vec<String> = csv_reader.headers()?.iter().map(|h| h.trim().to_string()).collect();
+    let suggested_mappings = suggest_mappings(&headers);
+    Ok(CsvImportPreview { headers, suggested_mappings })
+}
+
+pub fn suggest_mappings(headers: &[String]) -> Vec<ColumnMapping> {
+    let mut mappings: Vec<ColumnMapping> = Vec::new();
+    for header in headers {
+        let Some(field) = CableField::from_header(header) else { continue };
+        if mappings.iter().any(|m| m.field == field) {
+            continue;
+        }
+        // A unit in the header, e.g. "Length (m)", becomes the column unit
+        let unit = header
+            .split_once('(')
+            .and_then(|(_, rest)| Unit::parse(rest.trim_end_matches(')')))
+            .filter(|&unit| field.accepts(unit));
+        mappings.push(ColumnMapping { header: header.clone(), field, unit });
+    }
+    mappings
+}
+
+/// Parse every data row of a CSV file into cables using the given column mapping
+pub fn read_csv<R: Read>(reader: R, mappings: &[ColumnMapping]) -> Result<Vec<CableImportRow>, ImportError> {
+    let mut csv_reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers: Vec<String> = csv_reader.headers()?.iter().map(|h| h.trim().to_string()).collect();
+
+    let mut columns = Vec::with_capacity(mappings.len());
+    for (i, mapping) in mappings.iter().enumerate() {
+        if mappings[..i].iter().any(|m| m.field == mapping.field) {
+            return Err(ImportError::DuplicateField(mapping.field));
+        }
+        if let Some(unit) = mapping.unit {
+            if !mapping.field.accepts(unit) {
+                return Err(ImportError::InvalidUnit { field: mapping.field, unit });
+            }
+        }
+        let index = headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(mapping.header.trim()))
+            .ok_or_else(|| ImportError::MissingColumn(mapping.header.clone()))?;
+        columns.push((index, mapping));
+    }
+    if !mappings.iter().any(|m| m.field == CableField::Tag) {
+        return Err(ImportError::NoTagColumn);
+    }
+
+    let mut rows = Vec::new();
+    for record in csv_reader.records() {
+        let record = record?;
+        let row = record.position().map_or(rows.len() + 2, |p| p.line() as usize);
+        let cells: Vec<(&ColumnMapping, &str)> = columns
+            .iter()
+            .map(|&(index, mapping)| (mapping, record.get(index).unwrap_or_default().trim()))
+            .collect();
+
+        if cells.iter().all(|(_, value)| value.is_empty()) {
+            continue;
+        }
+        rows.push(parse_row(row, &cells));
+    }
+    Ok(rows)
+}
+
+fn parse_row(row: usize, cells: &[(&ColumnMapping, &str)]) -> CableImportRow {
+    let mut cable = NewCable {
+        tag: String::new(),
+        description: None,
+        function: None,
+        voltage: None,
+        current: None,
+        cable_type: None,
+        size: None,
+        cores: None,
+        segregation_class: None,
+        from_location: None,
+        from_equipment: None,
+        to_location: None,
+        to_equipment: None,
+        length: None,
+        spare_percentage: None,
+        route: None,
+        manufacturer: None,
+        part_number: None,
+        outer_diameter: None,
+        tray_id: None,
+        conduit_id: None,
+        notes: None,
+    };
+    let mut blank_fields = Vec::new();
+    let mut errors = Vec::new();
+
+    for &(mapping, value) in cells {
+        if value.is_empty() {
+            if mapping.field != CableField::Tag {
+                blank_fields.push(mapping.field);
+            }
+            continue;
+        }
+        let text = Some(value.to_string());
+        let result = match mapping.field {
+            CableField::Tag => { cable.tag = value.to_string(); Ok(()) }
+            CableField::Description => { cable.description = text; Ok(()) }
+            CableField::Function => { cable.function = text; Ok(()) }
+            CableField::CableType => { cable.cable_type = text; Ok(()) }
+            CableField::Size => { cable.size = text; Ok(()) }
+            CableField::SegregationClass => { cable.segregation_class = text; Ok(()) }
+            CableField::FromLocation => { cable.from_location = text; Ok(()) }
+            CableField::FromEquipment => { cable.from_equipment = text; Ok(()) }
+            CableField::ToLocation => { cable.to_location = text; Ok(()) }
+            CableField::ToEquipment => { cable.to_equipment = text; Ok(()) }
+            CableField::Route => { cable.route = text; Ok(()) }
+            CableField::Manufacturer => { cable.manufacturer = text; Ok(()) }
+            CableField::PartNumber => { cable.part_number = text; Ok(()) }
+            CableField::Notes => { cable.notes = text; Ok(()) }
+            CableField::Voltage => parse_number(value).map(|v| cable.voltage = Some(v)),
+            CableField::Current => parse_number(value).map(|v| cable.current = Some(v)),
+            CableField::SparePercentage => parse_number(value.trim_end_matches('%')).map(|v| cable.spare_percentage = Some(v)),
+            CableField::Cores => value
+                .parse::<i32>()
+                .map(|v| cable.cores = Some(v))
+                .map_err(|_| format!("'{}' is not a whole number", value)),
+            CableField::Length => parse_measurement(value, mapping).map(|v| cable.length = Some(v)),
+            CableField::OuterDiameter => parse_measurement(value, mapping).map(|v| cable.outer_diameter = Some(v)),
+        };
+        if let Err(message) = result {
+            errors.push(format!("{}: {}", mapping.header, message));
+        }
+    }
+
+    let tag = (!cable.tag.is_empty()).then(|| cable.tag.clone());
+    if tag.is_none() {
+        errors.push("Tag is required".to_string());
+    }
+
+    CableImportRow {
+        row,
+        tag,
+        cable: errors.is_empty().then_some(cable),
+        blank_fields,
+        errors,
+    }
+}
+
+fn parse_number(value: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .replace(',', "")
+        .parse::<f64>()
+        .map_err(|_| format!("'{}' is not a number", value))
+}
+
+/// Parse a value such as `250`, `76.2 m`, `250'` or `1.25 in` into the stored unit
+fn parse_measurement(value: &str, mapping: &ColumnMapping) -> Result<f64, String> {
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+')))
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split);
+    let number = parse_number(number)?;
+
+    let default_unit = mapping.unit.or(mapping.field.default_unit()).unwrap_or(Unit::Feet);
+    let unit = if suffix.trim().is_empty() {
+        default_unit
+    } else {
+        Unit::parse(suffix).ok_or_else(|| format!("unknown unit '{}'", suffix.trim()))?
+    };
+    if unit.is_length() != default_unit.is_length() {
+        return Err(format!("'{}' is not a valid unit here", suffix.trim()));
+    }
+    Ok(unit.to_stored(number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::export;
+
+    fn mapping(header: &str, field: CableField, unit: Option<Unit>) -> ColumnMapping {
+        ColumnMapping { header: header.to_string(), field, unit }
+    }
+
+    #[test]
+    fn test_measurement_units() {
+        let length = mapping("Len", CableField::Length, Some(Unit::Meters));
+        assert!((parse_measurement("100", &length).unwrap() - 328.084).abs() < 0.001);
+        assert_eq!(parse_measurement("250 ft", &length).unwrap(), 250.0);
+        assert_eq!(parse_measurement("1,250'", &length).unwrap(), 1250.0);
+        assert!(parse_measurement("3 in", &length).is_err());
+        assert!(parse_measurement("3 yards", &length).is_err());
+
+        let diameter = mapping("OD", CableField::OuterDiameter, None);
+        assert_eq!(parse_measurement("18.5", &diameter).unwrap(), 18.5);
+        assert!((parse_measurement("1.25\"", &diameter).unwrap() - 31.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_suggested_mappings_cover_cable_schedule_export() {
+        let headers: Vec<String> = export::cable_schedule(&[]).columns.iter().map(|c| c.to_string()).collect();
+        let mappings = suggest_mappings(&headers);
+
+        assert_eq!(mappings.len(), 20);
+        let length = mappings.iter().find(|m| m.field == CableField::Length).unwrap();
+        assert_eq!(length.header, "Length (ft)");
+        assert_eq!(length.unit, Some(Unit::Feet));
+        assert!(mappings.iter().all(|m| m.header != "Calculated Length (ft)"));
+    }
+
+    #[test]
+    fn test_read_csv_reports_row_errors() {
+        let data = "Cable No,Len (m),Volts,Cores\nC-001,100,480,3\n,20,480,3\nC-003,abc,480,three\n";
+        let mappings = vec![
+            mapping("Cable No", CableField::Tag, None),
+            mapping("Len (m)", CableField::Length, Some(Unit::Meters)),
+            mapping("Volts", CableField::Voltage, None),
+            mapping("Cores", CableField::Cores, None),
+        ];
+        let rows = read_csv(data.as_bytes(), &mappings).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].errors.is_empty());
+        assert_eq!(rows[0].cable.as_ref().unwrap().cores, Some(3));
+        assert_eq!(rows[1].errors, vec!["Tag is required".to_string()]);
+        assert_eq!(rows[2].row, 4);
+        assert_eq!(rows[2].errors.len(), 2);
+
+        assert!(matches!(
+            read_csv(data.as_bytes(), &mappings[1..]),
+            Err(ImportError::NoTagColumn)
+        ));
+        assert!(matches!(
+            read_csv(data.as_bytes(), &[mapping("Missing", CableField::Tag, None)]),
+            Err(ImportError::MissingColumn(_))
+        ));
+    }
+
+    #[test]
+    fn test_import_upserts_by_tag_and_rolls_back() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let mappings = vec![
+            mapping("Tag", CableField::Tag, None),
+            mapping("Length", CableField::Length, None),
+            mapping("Notes", CableField::Notes, None),
+        ];
+        db.import_cables(project_id, &read_csv("Tag,Length,Notes\nC-001,100,Keep\n".as_bytes(), &mappings).unwrap(), false, false)
+            .unwrap();
+
+        let rows = read_csv("Tag,Length,Notes\nC-001,150,\nC-002,80,New\n".as_bytes(), &mappings).unwrap();
+
+        // Dry run reports the plan without touching the project
+        let report = db.import_cables(project_id, &rows, true, false).unwrap();
+        assert_eq!((report.created, report.updated, report.failed, report.committed), (1, 1, 0, false));
+        assert_eq!(db.get_cables(project_id).unwrap().len(), 1);
+
+        let report = db.import_cables(project_id, &rows, false, false).unwrap();
+        assert!(report.committed);
+        let cables = db.get_cables(project_id).unwrap();
+        let updated = cables.iter().find(|c| c.tag == "C-001").unwrap();
+        assert_eq!(updated.length, Some(150.0));
+        assert_eq!(updated.notes.as_deref(), Some("Keep"));
+        assert_eq!(cables.len(), 2);
+
+        // Clearing blanks is explicit; the tag column is never cleared
+        assert_eq!(rows[0].blank_fields, vec![CableField::Notes]);
+        db.import_cables(project_id, &rows, false, true).unwrap();
+        let cleared = db.get_cables(project_id).unwrap().into_iter().find(|c| c.tag == "C-001").unwrap();
+        assert_eq!((cleared.length, cleared.notes), (Some(150.0), None));
+
+        // Any failing row, here a tag repeated in the file, rolls back the whole import
+        let rows = read_csv("Tag,Length,Notes\nC-003,10,\nC-003,20,\n".as_bytes(), &mappings).unwrap();
+        let report = db.import_cables(project_id, &rows, false, false).unwrap();
+        assert_eq!((report.created, report.failed, report.committed), (1, 1, false));
+        assert_eq!(report.rows[1].action, ImportAction::Error);
+        assert_eq!(db.get_cables(project_id).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,575 @@
+/**
+ * Excel Markup Import
+ * Reads a workbook written by the schedule export back in and diffs it field by field
+ * against the project, so marked-up schedules can be applied as tracked updates
+ */
+
+use super::{parse_number, ImportError};
+use crate::database::{models::*, Database};
+use crate::export::{self, xlsx::ID_COLUMN, CellValue, Schedule};
+use calamine::{Data, Reader, Xlsx};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntityKind {
+    Cable,
+    IOPoint,
+    Load,
+    Conduit,
+    Tray,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    Text,
+    Number,
+    Integer,
+}
+
+impl EntityKind {
+    const ALL: [EntityKind; 5] = [EntityKind::Cable, EntityKind::IOPoint, EntityKind::Load, EntityKind::Conduit, EntityKind::Tray];
+
+    /// The `entity_type` recorded in revision changes
+    pub fn entity_type(self) -> &'static str {
+        match self {
+            EntityKind::Cable => "cable",
+            EntityKind::IOPoint => "io_point",
+            EntityKind::Load => "load",
+            EntityKind::Conduit => "conduit",
+            EntityKind::Tray => "tray",
+        }
+    }
+
+    /// The table the entity is stored in
+    pub fn table(self) -> &'static str {
+        match self {
+            EntityKind::Cable => "cables",
+            EntityKind::IOPoint => "io_points",
+            EntityKind::Load => "loads",
+            EntityKind::Conduit => "conduits",
+            EntityKind::Tray => "trays",
+        }
+    }
+
+    fn schedule(self, db: &Database, project_id: i64, cables: &[Cable]) -> rusqlite::Result<Schedule> {
+        Ok(match self {
+            EntityKind::Cable => export::cable_schedule(cables),
+            EntityKind::IOPoint => export::io_point_schedule(&db.get_io_points(project_id)?, cables),
+            EntityKind::Load => export::load_schedule(&db.get_loads(project_id)?, cables),
+            EntityKind::Conduit => export::conduit_schedule(&db.get_conduits(project_id)?),
+            EntityKind::Tray => export::tray_schedule(&db.get_trays(project_id)?),
+        })
+    }
+
+    /// Columns a markup may change, as (export header, update field, kind). Calculated
+    /// columns and links to other entities are left out.
+    fn editable_columns(self) -> &'static [(&'static str, &'static str, FieldKind)] {
+        use FieldKind::*;
+        match self {
+            EntityKind::Cable => &[
+                ("Tag", "tag", Text),
+                ("Description", "description", Text),
+                ("Function", "function", Text),
+                ("Voltage (V)", "voltage", Number),
+                ("Current (A)", "current", Number),
+                ("Cable Type", "cable_type", Text),
+                ("Size", "size", Text),
+                ("Cores", "cores", Integer),
+                ("Segregation Class", "segregation_class", Text),
+                ("From Location", "from_location", Text),
+                ("From Equipment", "from_equipment", Text),
+                ("To Location", "to_location", Text),
+                ("To Equipment", "to_equipment", Text),
+                ("Length (ft)", "length", Number),
+                ("Spare (%)", "spare_percentage", Number),
+                ("Route", "route", Text),
+                ("Manufacturer", "manufacturer", Text),
+                ("Part Number", "part_number", Text),
+                ("Outer Diameter (mm)", "outer_diameter", Number),
+                ("Notes", "notes", Text),
+            ],
+            EntityKind::IOPoint => &[
+                ("Tag", "tag", Text),
+                ("Description", "description", Text),
+                ("Signal Type", "signal_type", Text),
+                ("IO Type", "io_type", Text),
+                ("PLC", "plc_name", Text),
+                ("Rack", "rack", Integer),
+                ("Slot", "slot", Integer),
+                ("Channel", "channel", Integer),
+                ("Terminal Block", "terminal_block", Text),
+                ("Notes", "notes", Text),
+            ],
+            EntityKind::Load => &[
+                ("Tag", "tag", Text),
+                ("Description", "description", Text),
+                ("Load Type", "load_type", Text),
+                ("Power (kW)", "power_kw", Number),
+                ("Power (HP)", "power_hp", Number),
+                ("Voltage (V)", "voltage", Number),
+                ("Current (A)", "current", Number),
+                ("Power Factor", "power_factor", Number),
+                ("Efficiency", "efficiency", Number),
+                ("Demand Factor", "demand_factor", Number),
+                ("Feeder Cable", "feeder_cable", Text),
+                ("Starter Type", "starter_type", Text),
+                ("Protection Type", "protection_type", Text),
+                ("Notes", "notes", Text),
+            ],
+            EntityKind::Conduit => &[
+                ("Tag", "tag", Text),
+                ("Type", "type", Text),
+                ("Size", "size", Text),
+                ("Internal Diameter (mm)", "internal_diameter", Number),
+                ("Length (m)", "length", Number),
+                ("From Location", "from_location", Text),
+                ("To Location", "to_location", Text),
+                ("Notes", "notes", Text),
+            ],
+            EntityKind::Tray => &[
+                ("Tag", "tag", Text),
+                ("Type", "type", Text),
+                ("Width (mm)", "width", Number),
+                ("Height (mm)", "height", Number),
+                ("Length (m)", "length", Number),
+                ("Material", "material", Text),
+                ("Finish", "finish", Text),
+                ("From Location", "from_location", Text),
+                ("To Location", "to_location", Text),
+                ("Elevation (mm)", "elevation", Number),
+                ("Support Spacing", "support_spacing", Number),
+                ("Load Rating", "load_rating", Number),
+                ("Notes", "notes", Text),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EntityUpdate {
+    Cable(UpdateCable),
+    IOPoint(UpdateIOPoint),
+    Load(UpdateLoad),
+    Conduit(UpdateConduit),
+    Tray(UpdateTray),
+}
+
+impl EntityUpdate {
+    /// Build the typed update from changed fields keyed by their serialized names
+    fn from_fields(kind: EntityKind, fields: Map<String, Value>) -> serde_json::Result<Self> {
+        let value = Value::Object(fields);
+        Ok(match kind {
+            EntityKind::Cable => EntityUpdate::Cable(serde_json::from_value(value)?),
+            EntityKind::IOPoint => EntityUpdate::IOPoint(serde_json::from_value(value)?),
+            EntityKind::Load => EntityUpdate::Load(serde_json::from_value(value)?),
+            EntityKind::Conduit => EntityUpdate::Conduit(serde_json::from_value(value)?),
+            EntityKind::Tray => EntityUpdate::Tray(serde_json::from_value(value)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub column: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>, // None when the field is cleared
+}
+
+/// A workbook row that differs from the project, or could not be matched to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkupRow {
+    pub sheet: String,
+    pub row: usize,
+    pub kind: EntityKind,
+    pub entity_id: Option<i64>,
+    pub tag: String,
+    pub changes: Vec<FieldChange>,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+    #[serde(skip)]
+    pub update: Option<EntityUpdate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkupReport {
+    pub dry_run: bool,
+    pub committed: bool,
+    pub rows_updated: usize,
+    pub fields_changed: usize,
+    pub failed: usize,
+    pub rows: Vec<MarkupRow>,
+}
+
+impl MarkupReport {
+    pub fn new(dry_run: bool) -> Self {
+        MarkupReport { dry_run, committed: false, rows_updated: 0, fields_changed: 0, failed: 0, rows: Vec::new() }
+    }
+
+    pub fn push(&mut self, row: MarkupRow) {
+        if !row.errors.is_empty() {
+            self.failed += 1;
+        } else if !row.changes.is_empty() {
+            self.rows_updated += 1;
+            self.fields_changed += row.changes.len();
+        }
+        self.rows.push(row);
+    }
+}
+
+/// Compare every schedule sheet of an exported workbook against the project. Rows are
+/// matched by the hidden ID column, falling back to the tag; unchanged rows are omitted.
+/// Blank cells clear the stored value only with `clear_blanks`; otherwise they are kept.
+pub fn diff_workbook<R: Read + Seek>(reader: R, db: &Database, project_id: i64, clear_blanks: bool) -> Result<Vec<MarkupRow>, ImportError> {
+    let mut workbook: Xlsx<R> = Xlsx::new(reader)?;
+    let cables = db.get_cables(project_id)?;
+    let mut rows = Vec::new();
+    let mut found_schedule = false;
+
+    for kind in EntityKind::ALL {
+        let current = kind.schedule(db, project_id, &cables)?;
+        if !workbook.sheet_names().iter().any(|name| name == current.name) {
+            continue;
+        }
+        found_schedule = true;
+        let range = workbook.worksheet_range(current.name)?;
+        let first_row = range.start().map_or(0, |(row, _)| row as usize);
+        let sheet_rows: Vec<&[Data]> = range.rows().collect();
+        rows.extend(diff_sheet(kind, &current, &sheet_rows, first_row, clear_blanks));
+    }
+
+    if !found_schedule {
+        return Err(ImportError::NoSchedules);
+    }
+    Ok(rows)
+}
+
+fn diff_sheet(kind: EntityKind, current: &Schedule, sheet_rows: &[&[Data]], first_row: usize, clear_blanks: bool) -> Vec<MarkupRow> {
+    let Some(header_index) = sheet_rows
+        .iter()
+        .position(|row| row.first().map(cell_text).as_deref() == Some("Tag"))
+    else {
+        return Vec::new();
+    };
+    let headers: Vec<String> = sheet_rows[header_index].iter().map(cell_text).collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let id_column = column(ID_COLUMN);
+
+    let mut matched = HashSet::new();
+    let mut diffs = Vec::new();
+
+    for (offset, cells) in sheet_rows[header_index + 1..].iter().enumerate() {
+        if cells.iter().all(|cell| cell_text(cell).is_empty()) {
+            continue;
+        }
+        let tag = cell_text(&cells[0]);
+        let id = id_column.and_then(|i| cells.get(i)).and_then(cell_id);
+        let mut diff = MarkupRow {
+            sheet: current.name.to_string(),
+            row: first_row + header_index + offset + 2,
+            kind,
+            entity_id: None,
+            tag: tag.clone(),
+            changes: Vec::new(),
+            warnings: Vec::new(),
+            errors: Vec::new(),
+            update: None,
+        };
+
+        let index = id
+            .and_then(|id| current.row_ids.iter().position(|row_id| *row_id == Some(id)))
+            .or_else(|| current.rows.iter().position(|row| row[0] == CellValue::Text(tag.clone())));
+        let Some(index) = index else {
+            diff.errors.push(format!("No {} with tag '{}' in the project", kind.entity_type().replace('_', " "), tag));
+            diffs.push(diff);
+            continue;
+        };
+        if !matched.insert(index) {
+            diff.errors.push("Another row in this sheet already updates the same entry".to_string());
+            diffs.push(diff);
+            continue;
+        }
+        diff.entity_id = current.row_ids[index];
+        if let CellValue::Text(current_tag) = &current.rows[index][0] {
+            diff.tag = current_tag.clone();
+        }
+
+        let editable = kind.editable_columns();
+        let mut fields = Map::new();
+        for (col, name) in current.columns.iter().enumerate() {
+            let Some(sheet_col) = column(name) else { continue };
+            let new_cell = cells.get(sheet_col).unwrap_or(&Data::Empty);
+            let old = &current.rows[index][col];
+
+            let Some(&(_, field, field_kind)) = editable.iter().find(|(header, _, _)| header == name) else {
+                if cell_differs(old, new_cell) {
+                    diff.warnings.push(format!("{} cannot be changed from a markup; the edit was ignored", name));
+                }
+                continue;
+            };
+
+            match read_value(new_cell, field_kind) {
+                Err(message) => diff.errors.push(format!("{}: {}", name, message)),
+                Ok(None) if *old != CellValue::Empty => {
+                    if field == "tag" {
+                        diff.errors.push(format!("{} cannot be cleared", name));
+                    } else if clear_blanks {
+                        diff.changes.push(FieldChange {
+                            column: name.to_string(),
+                            field: field.to_string(),
+                            old_value: Some(old.to_display_string()),
+                            new_value: None,
+                        });
+                    } else {
+                        diff.warnings.push(format!("{} was left blank; the existing value was kept", name));
+                    }
+                }
+                Ok(None) => {}
+                Ok(Some(value)) => {
+                    if value_matches(old, &value) {
+                        continue;
+                    }
+                    let new_display = match &value {
+                        Value::String(text) => text.clone(),
+                        other => CellValue::number(other.as_f64()).to_display_string(),
+                    };
+                    diff.changes.push(FieldChange {
+                        column: name.to_string(),
+                        field: field.to_string(),
+                        old_value: Some(old.to_display_string()).filter(|v| !v.is_empty()),
+                        new_value: Some(new_display.clone()),
+                    });
+                    if field == "tag" {
+                        diff.tag = new_display;
+                    }
+                    fields.insert(field.to_string(), value);
+                }
+            }
+        }
+
+        if diff.errors.is_empty() && !fields.is_empty() {
+            match EntityUpdate::from_fields(kind, fields) {
+                Ok(update) => diff.update = Some(update),
+                Err(e) => diff.errors.push(e.to_string()),
+            }
+        }
+        if !diff.changes.is_empty() || !diff.warnings.is_empty() || !diff.errors.is_empty() {
+            diffs.push(diff);
+        }
+    }
+
+    diffs
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::String(text) => text.trim().to_string(),
+        Data::Float(number) => CellValue::Number(*number).to_display_string(),
+        Data::Empty => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn cell_id(cell: &Data) -> Option<i64> {
+    match cell {
+        Data::Int(id) => Some(*id),
+        Data::Float(id) if id.fract() == 0.0 => Some(*id as i64),
+        Data::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Read a cell as the JSON value of an update field; `None` when the cell is blank
+fn read_value(cell: &Data, kind: FieldKind) -> Result<Option<Value>, String> {
+    let text = cell_text(cell);
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let number = match cell {
+        Data::Float(number) => Ok(*number),
+        Data::Int(number) => Ok(*number as f64),
+        _ => parse_number(&text),
+    };
+    match kind {
+        FieldKind::Text => Ok(Some(Value::String(text))),
+        FieldKind::Number => Ok(Some(Value::from(number?))),
+        FieldKind::Integer => match number? {
+            n if n.fract() == 0.0 => Ok(Some(Value::from(n as i64))),
+            _ => Err(format!("'{}' is not a whole number", text)),
+        },
+    }
+}
+
+fn value_matches(old: &CellValue, new: &Value) -> bool {
+    match (old, new) {
+        (CellValue::Number(old), Value::Number(new)) => {
+            let new = new.as_f64().unwrap_or(f64::NAN);
+            (old - new).abs() <= 1e-9 * old.abs().max(1.0)
+        }
+        (CellValue::Text(old), Value::String(new)) => old == new,
+        _ => false,
+    }
+}
+
+fn cell_differs(old: &CellValue, new: &Data) -> bool {
+    match (old, new) {
+        (CellValue::Number(old), Data::Float(new)) => (old - new).abs() > 1e-9 * old.abs().max(1.0),
+        (CellValue::Number(old), Data::Int(new)) => (old - *new as f64).abs() > 1e-9 * old.abs().max(1.0),
+        _ => old.to_display_string() != cell_text(new),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::xlsx::{build_workbook, TitleBlock};
+    use crate::export::xlsx::ExportSheet;
+    use std::io::Cursor;
+
+    fn new_cable(tag: &str, length: f64) -> NewCable {
+        NewCable {
+            tag: tag.to_string(),
+            description: Some("Pump feeder".to_string()),
+            function: Some("Power".to_string()),
+            voltage: Some(480.0),
+            current: None,
+            cable_type: None,
+            size: Some("6 AWG".to_string()),
+            cores: Some(3),
+            segregation_class: None,
+            from_location: None,
+            from_equipment: None,
+            to_location: None,
+            to_equipment: None,
+            length: Some(length),
+            spare_percentage: None,
+            route: None,
+            manufacturer: None,
+            part_number: None,
+            outer_diameter: None,
+            tray_id: None,
+            conduit_id: None,
+            notes: None,
+        }
+    }
+
+    /// Export the project, let `markup` edit the cable sheet and return the workbook bytes
+    fn marked_up_workbook(db: &Database, project_id: i64, markup: impl FnOnce(&mut Schedule)) -> Vec<u8> {
+        let (title, mut sheets): (TitleBlock, Vec<ExportSheet>) = export::xlsx::project_sheets(db, project_id).unwrap();
+        markup(&mut sheets[0].schedule);
+        build_workbook(&title, &sheets).unwrap().save_to_buffer().unwrap()
+    }
+
+    #[test]
+    fn test_markup_is_applied_and_tracked() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let first = db.insert_cable(project_id, &new_cable("C-001", 250.0)).unwrap();
+        db.insert_cable(project_id, &new_cable("C-002", 100.0)).unwrap();
+
+        let bytes = marked_up_workbook(&db, project_id, |cables| {
+            cables.rows[0][0] = CellValue::Text("C-001A".to_string()); // renamed, still matched by ID
+            cables.rows[0][13] = CellValue::Number(275.0);
+            cables.rows[0][1] = CellValue::Empty; // kept unless blanks clear
+            cables.rows[1][20] = CellValue::Number(2.5); // voltage drop is calculated
+        });
+
+        let diffs = diff_workbook(Cursor::new(bytes), &db, project_id, false).unwrap();
+        assert_eq!(diffs.len(), 2);
+        let renamed = &diffs[0];
+        assert_eq!(renamed.entity_id, first.id);
+        assert_eq!(renamed.tag, "C-001A");
+        let fields: Vec<&str> = renamed.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["tag", "length"]);
+        assert_eq!(renamed.warnings.len(), 1);
+        assert!(diffs[1].changes.is_empty());
+        assert_eq!(diffs[1].warnings.len(), 1);
+
+        let preview = db.apply_markup(project_id, diffs.clone(), true).unwrap();
+        assert_eq!((preview.rows_updated, preview.fields_changed, preview.committed), (1, 2, false));
+        assert_eq!(db.get_cable_by_id(first.id.unwrap()).unwrap().tag, "C-001");
+
+        let report = db.apply_markup(project_id, diffs, false).unwrap();
+        assert!(report.committed);
+        let cable = db.get_cable_by_id(first.id.unwrap()).unwrap();
+        assert_eq!((cable.tag.as_str(), cable.length), ("C-001A", Some(275.0)));
+        assert_eq!(cable.description.as_deref(), Some("Pump feeder"));
+
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+        let changes = db.get_revision_changes(revision_id).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|c| c.entity_type == "cable" && c.entity_id == first.id.unwrap()));
+        let length = changes.iter().find(|c| c.field_name.as_deref() == Some("length")).unwrap();
+        assert_eq!((length.old_value.as_deref(), length.new_value.as_deref()), (Some("250"), Some("275")));
+    }
+
+    #[test]
+    fn test_blank_cells_clear_fields_when_asked() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let cable = db.insert_cable(project_id, &new_cable("C-001", 250.0)).unwrap();
+        let bytes = marked_up_workbook(&db, project_id, |cables| {
+            cables.rows[0][1] = CellValue::Empty;
+            cables.rows[0][13] = CellValue::Empty;
+        });
+
+        let diffs = diff_workbook(Cursor::new(bytes.clone()), &db, project_id, true).unwrap();
+        let cleared: Vec<(&str, Option<&str>)> = diffs[0].changes.iter()
+            .map(|c| (c.field.as_str(), c.new_value.as_deref()))
+            .collect();
+        assert_eq!(cleared, vec![("description", None), ("length", None)]);
+
+        let report = db.apply_markup(project_id, diffs, false).unwrap();
+        assert_eq!((report.rows_updated, report.fields_changed, report.committed), (1, 2, true));
+        let updated = db.get_cable_by_id(cable.id.unwrap()).unwrap();
+        assert_eq!((updated.description, updated.length), (None, None));
+        assert_eq!(updated.size.as_deref(), Some("6 AWG"));
+
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+        assert!(db.get_revision_changes(revision_id).unwrap().iter().all(|c| c.new_value.is_none()));
+
+        // The tag can never be cleared
+        let bytes = marked_up_workbook(&db, project_id, |cables| cables.rows[0][0] = CellValue::Empty);
+        let diffs = diff_workbook(Cursor::new(bytes), &db, project_id, true).unwrap();
+        assert_eq!(diffs[0].errors.len(), 1);
+    }
+
+    #[test]
+    fn test_unmatched_rows_block_the_markup() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let cable = db.insert_cable(project_id, &new_cable("C-001", 250.0)).unwrap();
+
+        let bytes = marked_up_workbook(&db, project_id, |cables| {
+            cables.rows[0][13] = CellValue::Number(300.0);
+            cables.rows.push(cables.rows[0].clone());
+            cables.rows[1][0] = CellValue::Text("C-999".to_string());
+            cables.row_ids.push(None);
+        });
+
+        let diffs = diff_workbook(Cursor::new(bytes), &db, project_id, false).unwrap();
+        let report = db.apply_markup(project_id, diffs, false).unwrap();
+        assert_eq!(report.failed, 1);
+        assert!(!report.committed);
+        assert_eq!(db.get_cable_by_id(cable.id.unwrap()).unwrap().length, Some(250.0));
+    }
+
+    #[test]
+    fn test_editable_columns_exist_in_exports() {
+        for kind in EntityKind::ALL {
+            let schedule = match kind {
+                EntityKind::Cable => export::cable_schedule(&[]),
+                EntityKind::IOPoint => export::io_point_schedule(&[], &[]),
+                EntityKind::Load => export::load_schedule(&[], &[]),
+                EntityKind::Conduit => export::conduit_schedule(&[]),
+                EntityKind::Tray => export::tray_schedule(&[]),
+            };
+            for (header, _, _) in kind.editable_columns() {
+                assert!(schedule.columns.contains(header), "{} missing from {}", header, schedule.name);
+            }
+        }
+    }
+}
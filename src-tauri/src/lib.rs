@@ -1,7 +1,10 @@
-mod database;
+pub mod database;
 mod commands;
-mod validation;
-mod calculations;
+pub mod validation;
+pub mod calculations;
+pub mod export;
+pub mod import;
+pub mod routing;
 
 use commands::*;
 use std::sync::Mutex;
@@ -28,19 +31,47 @@ pub fn run() {
       update_cable,
       delete_cable,
       get_next_cable_tag,
+      get_cable_route_segments,
+      set_cable_route_segments,
+      insert_cable_route_segment,
+      reorder_cable_route_segments,
+      preview_cable_routes,
+      accept_cable_routes,
+      get_cable_length_allowances,
+      update_cable_length_allowances,
+      get_cable_length_report,
+      preview_cable_csv,
+      import_cables_csv,
+      import_schedule_markup,
+      export_schedules_xlsx,
       validate_all_cables,
       validate_cable,
       check_duplicate_tag,
       get_validation_summary,
+      override_validation_result,
+      get_validation_overrides,
+      revoke_validation_override,
       take_screenshot,
       save_workflow_json,
       export_workflow_zip,
       get_all_workflows,
       delete_all_workflows,
       calculate_voltage_drop,
+      size_grounding_conductor,
+      size_motor_circuit,
       calculate_minimum_conductor_size,
       calculate_current_from_power,
+      check_cable_ampacity,
+      check_all_cable_ampacity,
       update_cable_voltage_drop,
+      recalculate_voltage_drops,
+      set_cable_parent,
+      check_feeder_voltage_drop,
+      get_fault_levels,
+      create_fault_level,
+      update_fault_level,
+      delete_fault_level,
+      check_short_circuit_withstand,
       create_io_point,
       get_io_points,
       update_io_point,
@@ -52,6 +83,23 @@ pub fn run() {
       update_load,
       delete_load,
       get_load_summary,
+      get_load_analysis,
+      preview_power_cables,
+      accept_power_cables,
+      check_motor_circuits,
+      create_protective_device,
+      get_protective_devices,
+      update_protective_device,
+      delete_protective_device,
+      check_protective_devices,
+      get_validation_rule_sets,
+      get_active_validation_rules,
+      create_validation_rule_set,
+      update_validation_rule_set,
+      set_active_validation_rule_set,
+      delete_validation_rule_set,
+      export_validation_rule_set,
+      import_validation_rule_set,
       create_conduit,
       get_conduits,
       update_conduit,
@@ -65,6 +113,10 @@ pub fn run() {
       recalculate_conduit_fill,
       recalculate_tray_fill,
       recalculate_all_fills,
+      check_conduit_fill,
+      check_tray_fill,
+      get_conduit_trade_sizes,
+      calculate_minimum_conduit_size,
       create_revision,
       get_revision_history,
       get_revision_by_id,
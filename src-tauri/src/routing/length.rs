@@ -0,0 +1,150 @@
+/**
+ * Cable Length Engine
+ * Calculated cable length from the raceways along the route, vertical and termination
+ * allowances, and the cable's spare percentage
+ *
+ * Raceway lengths and the allowances are entered in m and tray elevations in mm, while cable
+ * lengths are stored in ft, so everything is converted to ft before it is added up.
+ */
+
+use crate::database::models::{Cable, CableLengthAllowances};
+use serde::{Deserialize, Serialize};
+
+const FEET_PER_METER: f64 = 1.0 / 0.3048;
+const FEET_PER_MILLIMETER: f64 = FEET_PER_METER / 1000.0;
+
+/// One raceway along a cable's route, in route order
+#[derive(Debug, Clone)]
+pub struct RouteLeg {
+    pub tag: String,
+    pub length: Option<f64>, // m
+    pub tray_elevation: Option<f64>, // mm; None for conduits and trays without an elevation
+}
+
+/// Length breakdown of one cable, in ft like the entered length
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CableLengthReport {
+    pub cable_id: i64,
+    pub cable_tag: String,
+    pub raceway_length: f64,
+    pub drop_rise_allowance: f64,
+    pub termination_allowance: f64,
+    pub elevation_allowance: f64,
+    pub spare_percentage: f64,
+    pub calculated_length: Option<f64>, // None when the route is empty or a raceway has no length
+    pub entered_length: Option<f64>,
+    pub difference: Option<f64>, // calculated minus entered
+    pub missing_lengths: Vec<String>, // tags of raceways without a length
+}
+
+/// Raceway lengths plus a drop/rise and a termination makeup at each end, the height of every
+/// change in tray elevation plus its fitting allowance, all increased by the spare percentage
+pub fn calculate_cable_length(cable: &Cable, legs: &[RouteLeg], allowances: &CableLengthAllowances) -> CableLengthReport {
+    let raceway_length: f64 = legs.iter().filter_map(|leg| leg.length).sum::<f64>() * FEET_PER_METER;
+    let missing_lengths: Vec<String> = legs.iter()
+        .filter(|leg| leg.length.is_none())
+        .map(|leg| leg.tag.clone())
+        .collect();
+
+    // Compare each tray with the previous tray on the route; conduits between them don't reset it
+    let mut elevation_allowance = 0.0;
+    let mut previous_elevation: Option<f64> = None;
+    for elevation in legs.iter().filter_map(|leg| leg.tray_elevation) {
+        if let Some(previous) = previous_elevation {
+            let rise = (elevation - previous).abs();
+            if rise > f64::EPSILON {
+                elevation_allowance += rise * FEET_PER_MILLIMETER + allowances.elevation_change * FEET_PER_METER;
+            }
+        }
+        previous_elevation = Some(elevation);
+    }
+
+    let drop_rise_allowance = 2.0 * allowances.drop_rise * FEET_PER_METER;
+    let termination_allowance = 2.0 * allowances.termination_makeup * FEET_PER_METER;
+    let spare_percentage = cable.spare_percentage.unwrap_or(0.0);
+
+    let calculated_length = if legs.is_empty() || !missing_lengths.is_empty() {
+        None
+    } else {
+        let total = raceway_length + drop_rise_allowance + termination_allowance + elevation_allowance;
+        Some(total * (1.0 + spare_percentage / 100.0))
+    };
+    let difference = match (calculated_length, cable.length) {
+        (Some(calculated), Some(entered)) => Some(calculated - entered),
+        _ => None,
+    };
+
+    CableLengthReport {
+        cable_id: cable.id.unwrap_or_default(),
+        cable_tag: cable.tag.clone(),
+        raceway_length,
+        drop_rise_allowance,
+        termination_allowance,
+        elevation_allowance,
+        spare_percentage,
+        calculated_length,
+        entered_length: cable.length,
+        difference,
+        missing_lengths,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::Database;
+    use crate::database::models::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_calculated_length_follows_route_and_raceway_changes() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+
+        let tray = |tag: &str, length: f64, elevation: f64| db.insert_tray(&serde_json::from_value(json!({
+            "tag": tag, "width": 300.0, "height": 100.0, "length": length, "elevation": elevation
+        })).unwrap(), project_id, revision_id).unwrap();
+        let low = tray("TR-1", 40.0, 3000.0);
+        tray("TR-2", 25.0, 5000.0);
+        let conduit = db.insert_conduit(project_id, &serde_json::from_value(json!({ "tag": "C1", "length": 15.0 })).unwrap()).unwrap();
+
+        let cable = db.insert_cable(project_id, &serde_json::from_value(json!({
+            "tag": "C-001", "route": "TR-1, C1, TR-2", "length": 300.0, "spare_percentage": 10.0
+        })).unwrap()).unwrap();
+        let cable_id = cable.id.unwrap();
+        let feet = |meters: f64| meters / 0.3048;
+
+        // (80 m of raceway + 2 × 2 m drops + 2 × 1 m terminations + 2 m rise + 0.5 m fittings) × 1.1,
+        // in ft like the entered 300 ft
+        let expected = feet(88.5) * 1.1;
+        assert!((cable.calculated_length.unwrap() - expected).abs() < 1e-9);
+        assert!((expected - 319.39).abs() < 0.01);
+
+        let report = db.get_cable_length_report(project_id).unwrap();
+        assert_eq!(report.len(), 1);
+        assert!((report[0].raceway_length - feet(80.0)).abs() < 1e-9);
+        assert!((report[0].elevation_allowance - feet(2.5)).abs() < 1e-9);
+        assert!((report[0].difference.unwrap() - (expected - 300.0)).abs() < 1e-9);
+
+        // Changing a raceway length, the spare or the allowances reruns the engine
+        db.update_tray(low.id.unwrap(), &serde_json::from_value(json!({ "length": 50.0 })).unwrap()).unwrap();
+        assert!((db.get_cable_by_id(cable_id).unwrap().calculated_length.unwrap() - feet(98.5) * 1.1).abs() < 1e-9);
+        db.update_cable(cable_id, &serde_json::from_value(json!({ "spare_percentage": 0.0 })).unwrap()).unwrap();
+        assert!((db.get_cable_by_id(cable_id).unwrap().calculated_length.unwrap() - feet(98.5)).abs() < 1e-9);
+        db.set_cable_length_allowances(project_id, &CableLengthAllowances {
+            drop_rise: 0.0,
+            termination_makeup: 0.0,
+            elevation_change: 0.0,
+        }).unwrap();
+        assert!((db.get_cable_by_id(cable_id).unwrap().calculated_length.unwrap() - feet(92.0)).abs() < 1e-9);
+
+        // A raceway without a length leaves the cable without a calculated length
+        let unknown = db.insert_conduit(project_id, &serde_json::from_value(json!({ "tag": "C2" })).unwrap()).unwrap();
+        db.set_cable_route_segments(cable_id, &[
+            NewCableRouteSegment { conduit_id: conduit.id, tray_id: None },
+            NewCableRouteSegment { conduit_id: unknown.id, tray_id: None },
+        ]).unwrap();
+        assert_eq!(db.get_cable_by_id(cable_id).unwrap().calculated_length, None);
+        assert_eq!(db.get_cable_length_report(project_id).unwrap()[0].missing_lengths, vec!["C2"]);
+    }
+}
@@ -0,0 +1,362 @@
+/**
+ * Cable Routing
+ * Raceway network of locations joined by conduits and trays, and a shortest-path auto-router
+ * that respects segregation and remaining fill
+ */
+
+pub mod length;
+
+use crate::calculations::{conduit_fill, tray_fill};
+use crate::database::models::*;
+use crate::validation::CableValidator;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Route found for one cable. Nothing is stored until the proposal is accepted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CableRouteProposal {
+    pub cable_id: i64,
+    pub cable_tag: String,
+    pub segments: Vec<NewCableRouteSegment>,
+    pub raceway_tags: Vec<String>,
+    pub length: Option<f64>, // sum of the raceway lengths, in m
+    pub skipped_reason: Option<String>,
+}
+
+/// A conduit or tray joining two locations
+struct RacewayEdge {
+    segment: NewCableRouteSegment,
+    tag: String,
+    ends: (usize, usize),
+    length: f64,
+    area: Option<f64>, // conduit internal cross-section; None when dimensions are unknown
+    fill_limit: FillLimit,
+    used_area: f64,
+    occupants: Vec<Cable>,
+}
+
+enum FillLimit {
+    Tray(Box<Tray>), // NEC 392.22, by tray type and the cables in it
+    Conduit, // NEC Chapter 9 Table 1, by cable count
+}
+
+impl RacewayEdge {
+    fn other_end(&self, node: usize) -> usize {
+        if self.ends.0 == node { self.ends.1 } else { self.ends.0 }
+    }
+
+    fn accepts(&self, cable: &Cable, validator: &CableValidator) -> bool {
+        let occupants: Vec<&Cable> = self.occupants.iter().collect();
+        let has_room = match &self.fill_limit {
+            FillLimit::Tray(tray) => {
+                let mut cables = occupants.clone();
+                cables.push(cable);
+                // A tray whose allowance can't be worked out is not held to one
+                let check = tray_fill::check_tray_fill(tray, &cables);
+                check.allowable.is_none() || check.passes
+            }
+            FillLimit::Conduit => {
+                let max_fill = conduit_fill::max_fill_percentage(self.occupants.len() + 1, None).0;
+                !matches!(self.area, Some(area) if self.used_area + cable_area(cable) > area * max_fill / 100.0)
+            }
+        };
+        has_room && validator.can_share_raceway(cable, &occupants)
+    }
+
+    fn add(&mut self, cable: &Cable) {
+        self.used_area += cable_area(cable);
+        self.occupants.push(cable.clone());
+    }
+
+    fn remove(&mut self, cable_id: i64) -> bool {
+        let Some(index) = self.occupants.iter().position(|c| c.id == Some(cable_id)) else {
+            return false;
+        };
+        let cable = self.occupants.remove(index);
+        self.used_area -= cable_area(&cable);
+        true
+    }
+}
+
+/// Cross-sectional area from the cable's outer diameter, as in the raceway fill calculation
+fn cable_area(cable: &Cable) -> f64 {
+    cable.outer_diameter
+        .map(|diameter| std::f64::consts::PI * (diameter / 2.0).powi(2))
+        .unwrap_or(0.0)
+}
+
+fn location_key(location: &str) -> String {
+    location.trim().to_lowercase()
+}
+
+/// Node waiting in the Dijkstra queue; ordered so the shortest distance pops first
+#[derive(PartialEq)]
+struct Visit {
+    distance: f64,
+    node: usize,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance).then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub struct RacewayNetwork {
+    locations: HashMap<String, usize>,
+    edges: Vec<RacewayEdge>,
+    adjacency: Vec<Vec<usize>>, // edge indexes at each location
+}
+
+impl RacewayNetwork {
+    /// Network of the conduits and trays that have both end locations and a length, holding the
+    /// cables already routed through them. Locations match case-insensitively.
+    pub fn new(conduits: &[Conduit], trays: &[Tray], cables: &[Cable], segments: &[CableRouteSegment]) -> Self {
+        let mut network = Self {
+            locations: HashMap::new(),
+            edges: Vec::new(),
+            adjacency: Vec::new(),
+        };
+
+        for conduit in conduits {
+            let area = conduit_fill::check_conduit_fill(
+                conduit.r#type.as_deref(),
+                conduit.size.as_deref(),
+                conduit.internal_diameter,
+                None,
+                &[],
+            ).internal_area;
+            network.add_edge(
+                NewCableRouteSegment { conduit_id: conduit.id, tray_id: None },
+                &conduit.tag,
+                (&conduit.from_location, &conduit.to_location),
+                conduit.length,
+                (area, FillLimit::Conduit),
+            );
+        }
+        for tray in trays {
+            network.add_edge(
+                NewCableRouteSegment { conduit_id: None, tray_id: tray.id },
+                &tray.tag,
+                (&tray.from_location, &tray.to_location),
+                tray.length,
+                (None, FillLimit::Tray(Box::new(tray.clone()))),
+            );
+        }
+
+        let cables_by_id: HashMap<i64, &Cable> = cables.iter()
+            .filter_map(|cable| cable.id.map(|id| (id, cable)))
+            .collect();
+        for segment in segments {
+            let edge = network.edges.iter_mut().find(|edge| {
+                edge.segment.conduit_id.is_some() && edge.segment.conduit_id == segment.conduit_id
+                    || edge.segment.tray_id.is_some() && edge.segment.tray_id == segment.tray_id
+            });
+            if let (Some(edge), Some(cable)) = (edge, cables_by_id.get(&segment.cable_id)) {
+                edge.add(cable);
+            }
+        }
+
+        network
+    }
+
+    fn add_edge(
+        &mut self,
+        segment: NewCableRouteSegment,
+        tag: &str,
+        ends: (&Option<String>, &Option<String>),
+        length: Option<f64>,
+        (area, fill_limit): (Option<f64>, FillLimit),
+    ) {
+        let (Some(from), Some(to), Some(length)) = (ends.0, ends.1, length) else {
+            return;
+        };
+        let ends = (self.location(from), self.location(to));
+        let index = self.edges.len();
+        self.edges.push(RacewayEdge {
+            segment,
+            tag: tag.to_string(),
+            ends,
+            length,
+            area,
+            fill_limit,
+            used_area: 0.0,
+            occupants: Vec::new(),
+        });
+        self.adjacency[ends.0].push(index);
+        self.adjacency[ends.1].push(index);
+    }
+
+    fn location(&mut self, name: &str) -> usize {
+        let next = self.adjacency.len();
+        let node = *self.locations.entry(location_key(name)).or_insert(next);
+        if node == next {
+            self.adjacency.push(Vec::new());
+        }
+        node
+    }
+
+    /// Route a cable along the shortest path from its from location to its to location, using
+    /// only raceways it can share and that have room for it. The cable's current route does not
+    /// count against it. A routed cable occupies its new raceways for the cables routed after it.
+    pub fn route(&mut self, cable: &Cable, validator: &CableValidator) -> CableRouteProposal {
+        let mut proposal = CableRouteProposal {
+            cable_id: cable.id.unwrap_or_default(),
+            cable_tag: cable.tag.clone(),
+            segments: Vec::new(),
+            raceway_tags: Vec::new(),
+            length: None,
+            skipped_reason: None,
+        };
+
+        let (Some(from), Some(to)) = (cable.from_location.as_deref(), cable.to_location.as_deref()) else {
+            proposal.skipped_reason = Some("Cable needs a from and a to location".to_string());
+            return proposal;
+        };
+        let Some(&start) = self.locations.get(&location_key(from)) else {
+            proposal.skipped_reason = Some(format!("No raceway reaches {}", from));
+            return proposal;
+        };
+        let Some(&end) = self.locations.get(&location_key(to)) else {
+            proposal.skipped_reason = Some(format!("No raceway reaches {}", to));
+            return proposal;
+        };
+        if start == end {
+            proposal.skipped_reason = Some("Cable starts and ends at the same location".to_string());
+            return proposal;
+        }
+
+        // Take the cable out of its current raceways while searching; put it back if no route is found
+        let current: Vec<usize> = match cable.id {
+            Some(id) => (0..self.edges.len()).filter(|&index| self.edges[index].remove(id)).collect(),
+            None => Vec::new(),
+        };
+
+        let Some(path) = self.shortest_path(start, end, cable, validator) else {
+            for index in current {
+                self.edges[index].add(cable);
+            }
+            proposal.skipped_reason = Some(format!(
+                "No path from {} to {} with room for the cable and compatible segregation", from, to
+            ));
+            return proposal;
+        };
+
+        let mut length = 0.0;
+        for index in path {
+            let edge = &mut self.edges[index];
+            edge.add(cable);
+            length += edge.length;
+            proposal.segments.push(edge.segment.clone());
+            proposal.raceway_tags.push(edge.tag.clone());
+        }
+        proposal.length = Some(length);
+        proposal
+    }
+
+    /// Dijkstra over the raceways the cable is allowed into; returns edge indexes from start to end
+    fn shortest_path(&self, start: usize, end: usize, cable: &Cable, validator: &CableValidator) -> Option<Vec<usize>> {
+        let mut distances = vec![f64::INFINITY; self.adjacency.len()];
+        let mut arrived_by: Vec<Option<usize>> = vec![None; self.adjacency.len()];
+        let mut queue = BinaryHeap::new();
+
+        distances[start] = 0.0;
+        queue.push(Visit { distance: 0.0, node: start });
+        while let Some(Visit { distance, node }) = queue.pop() {
+            if node == end {
+                break;
+            }
+            if distance > distances[node] {
+                continue;
+            }
+            for &index in &self.adjacency[node] {
+                let edge = &self.edges[index];
+                let next = edge.other_end(node);
+                let next_distance = distance + edge.length;
+                if next_distance < distances[next] && edge.accepts(cable, validator) {
+                    distances[next] = next_distance;
+                    arrived_by[next] = Some(index);
+                    queue.push(Visit { distance: next_distance, node: next });
+                }
+            }
+        }
+
+        if distances[end].is_infinite() {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut node = end;
+        while let Some(index) = arrived_by[node] {
+            path.push(index);
+            node = self.edges[index].other_end(node);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::Database;
+    use serde_json::json;
+
+    #[test]
+    fn test_auto_route_detours_around_segregation_and_full_raceways() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+
+        let conduit = |tag: &str, from: &str, to: &str, length: f64| db.insert_conduit(project_id, &serde_json::from_value(json!({
+            "tag": tag, "internal_diameter": 40.0, "length": length, "from_location": from, "to_location": to
+        })).unwrap()).unwrap();
+        let tray = |tag: &str, from: &str, to: &str, length: f64| db.insert_tray(&serde_json::from_value(json!({
+            "tag": tag, "width": 300.0, "height": 100.0, "length": length, "from_location": from, "to_location": to
+        })).unwrap(), project_id, revision_id).unwrap();
+        conduit("C1", "MCC-1", "JB-1", 10.0);
+        conduit("C2", "jb-1 ", "FIELD", 10.0);
+        tray("TR-1", "MCC-1", "JB-2", 30.0);
+        conduit("C3", "JB-2", "FIELD", 20.0);
+        tray("TR-2", "JB-2", "FIELD", 50.0);
+
+        let cable = |value: serde_json::Value| db.insert_cable(project_id, &serde_json::from_value(value).unwrap()).unwrap();
+        cable(json!({ "tag": "I-001", "segregation_class": "Control Power 24VDC", "voltage": 24.0, "route": "C2", "outer_diameter": 8.0 }));
+        let power = cable(json!({
+            "tag": "P-001", "segregation_class": "Power 480VAC", "voltage": 480.0, "outer_diameter": 10.0,
+            "from_location": "MCC-1", "to_location": "FIELD"
+        }));
+        let large = cable(json!({
+            "tag": "P-002", "segregation_class": "Power 480VAC", "voltage": 480.0, "outer_diameter": 30.0,
+            "from_location": "MCC-1", "to_location": "Field"
+        }));
+        let unreachable = cable(json!({ "tag": "P-003", "from_location": "MCC-1", "to_location": "PUMP-7" }));
+
+        let ids: Vec<i64> = [&power, &large, &unreachable].iter().map(|c| c.id.unwrap()).collect();
+        let mut proposals = db.preview_cable_routes(project_id, &ids).unwrap();
+
+        // C2 carries an incompatible cable, and the 30 mm cable overfills every 40 mm conduit
+        assert_eq!(proposals[0].raceway_tags, vec!["TR-1", "C3"]);
+        assert_eq!(proposals[0].length, Some(50.0));
+        assert_eq!(proposals[1].raceway_tags, vec!["TR-1", "TR-2"]);
+        assert_eq!(proposals[1].length, Some(80.0));
+        assert_eq!(proposals[2].skipped_reason.as_deref(), Some("No raceway reaches PUMP-7"));
+
+        // The stored length comes from the raceways, whatever the proposal says
+        proposals[0].length = Some(1.0);
+        let routed = db.accept_cable_routes(&proposals).unwrap();
+        assert_eq!(routed.len(), 2);
+        assert_eq!(routed[0].route.as_deref(), Some("TR-1, C3"));
+        // 50 m of raceway plus the default drop and termination allowances, stored in ft
+        assert!((routed[0].calculated_length.unwrap() - 56.0 / 0.3048).abs() < 1e-9);
+        assert_eq!(db.get_cable_route_segments(large.id.unwrap()).unwrap().len(), 2);
+        assert!(db.get_cable_by_id(unreachable.id.unwrap()).unwrap().route.is_none());
+    }
+}
@@ -0,0 +1,246 @@
+/**
+ * Incremental Validation
+ * Keeps the cable-list rules (required fields, duplicate tags, field formats, raceway
+ * segregation and conduit fill checks) up to date one edit at a time, using per-raceway and
+ * per-tag indexes
+ */
+
+use super::{apply_overrides, CableValidator, RacewayKey, ValidationResult};
+use crate::database::{models::*, Database};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Tauri event carrying a `Vec<CableValidationUpdate>` after each cable edit
+pub const VALIDATION_UPDATED_EVENT: &str = "validation-updated";
+
+/// Re-evaluated cable-list results of one cable. They replace the cable's previous
+/// cable-list results; an empty list clears them, e.g. after the cable is deleted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CableValidationUpdate {
+    pub cable_id: i64,
+    pub results: Vec<ValidationResult>,
+}
+
+pub struct IncrementalValidator {
+    validator: CableValidator,
+    overrides: Vec<ValidationOverride>,
+    conduits: HashMap<i64, Conduit>,
+    cables: HashMap<i64, Cable>,
+    routes: HashMap<i64, Vec<RacewayKey>>,
+    raceway_tags: HashMap<RacewayKey, String>,
+    by_raceway: HashMap<RacewayKey, HashSet<i64>>,
+    by_tag: HashMap<String, HashSet<i64>>,
+    cable_results: HashMap<i64, Vec<ValidationResult>>,
+    raceway_results: HashMap<RacewayKey, Vec<ValidationResult>>,
+}
+
+/// Tags compare case-insensitively, as in `validate_duplicate_tag`
+fn tag_key(cable: &Cable) -> String {
+    cable.tag.to_ascii_lowercase()
+}
+
+impl IncrementalValidator {
+    /// Index and evaluate every cable once
+    pub fn new(
+        validator: CableValidator,
+        conduits: Vec<Conduit>,
+        cables: Vec<Cable>,
+        route_segments: Vec<CableRouteSegment>,
+        overrides: Vec<ValidationOverride>,
+    ) -> Self {
+        let mut incremental = Self {
+            validator,
+            overrides,
+            conduits: conduits.into_iter()
+                .filter_map(|conduit| conduit.id.map(|id| (id, conduit)))
+                .collect(),
+            cables: HashMap::new(),
+            routes: HashMap::new(),
+            raceway_tags: HashMap::new(),
+            by_raceway: HashMap::new(),
+            by_tag: HashMap::new(),
+            cable_results: HashMap::new(),
+            raceway_results: HashMap::new(),
+        };
+
+        let mut segments_by_cable: HashMap<i64, Vec<CableRouteSegment>> = HashMap::new();
+        for segment in route_segments {
+            segments_by_cable.entry(segment.cable_id).or_default().push(segment);
+        }
+        for cable in cables {
+            let segments = cable.id.and_then(|id| segments_by_cable.remove(&id)).unwrap_or_default();
+            incremental.index(cable, &segments);
+        }
+        let ids: Vec<i64> = incremental.cables.keys().copied().collect();
+        for id in ids {
+            incremental.evaluate_cable(id);
+        }
+        let raceways: Vec<RacewayKey> = incremental.by_raceway.keys().copied().collect();
+        for raceway in raceways {
+            incremental.evaluate_raceway(raceway);
+        }
+
+        incremental
+    }
+
+    /// Validator for a project's cables, using its active rule set and overrides
+    pub fn load(db: &Database, project_id: i64) -> rusqlite::Result<Self> {
+        Ok(Self::new(
+            CableValidator::for_project(db, project_id)?,
+            db.get_conduits(project_id)?,
+            db.get_cables(project_id)?,
+            db.get_project_route_segments(project_id)?,
+            db.get_validation_overrides(project_id)?,
+        ))
+    }
+
+    /// Current cable-list results of one cable
+    pub fn cable_results(&self, id: i64) -> Vec<ValidationResult> {
+        let mut results = self.cable_results.get(&id).cloned().unwrap_or_default();
+        if let Some(cable) = self.cables.get(&id) {
+            for raceway in self.routes.get(&id).into_iter().flatten() {
+                results.extend(self.raceway_results.get(raceway)
+                    .into_iter()
+                    .flatten()
+                    .filter(|result| result.cable_id == Some(id))
+                    .cloned());
+            }
+            apply_overrides(&mut results, &self.overrides, std::slice::from_ref(cable));
+        }
+        results
+    }
+
+    /// A cable was created or updated: re-evaluate it, the cables sharing its old or new tag,
+    /// and the conduits and trays of its stored route it left or joined
+    pub fn upsert_cable(&mut self, cable: Cable, route_segments: &[CableRouteSegment]) -> Vec<CableValidationUpdate> {
+        let Some(id) = cable.id else {
+            return Vec::new();
+        };
+        let previous = self.unindex(id);
+        let current = cable.clone();
+        self.index(cable, route_segments);
+
+        let mut raceways: HashSet<RacewayKey> = self.routes.get(&id).into_iter().flatten().copied().collect();
+        let mut versions = vec![&current];
+        if let Some((cable, route)) = &previous {
+            versions.push(cable);
+            raceways.extend(route);
+        }
+        self.reevaluate(id, &versions, raceways)
+    }
+
+    /// A cable was deleted: its results are cleared and its tag-mates and the cables sharing
+    /// its conduits and trays re-evaluated
+    pub fn remove_cable(&mut self, id: i64) -> Vec<CableValidationUpdate> {
+        let Some((previous, route)) = self.unindex(id) else {
+            return Vec::new();
+        };
+        self.cable_results.remove(&id);
+
+        let mut updates = self.reevaluate(id, &[&previous], route.into_iter().collect());
+        updates.push(CableValidationUpdate { cable_id: id, results: Vec::new() });
+        updates
+    }
+
+    fn reevaluate(&mut self, id: i64, versions: &[&Cable], raceways: HashSet<RacewayKey>) -> Vec<CableValidationUpdate> {
+        let tags: HashSet<String> = versions.iter().map(|cable| tag_key(cable)).collect();
+
+        let mut changed: BTreeSet<i64> = BTreeSet::new();
+        if self.cables.contains_key(&id) {
+            changed.insert(id);
+        }
+        for tag in &tags {
+            changed.extend(self.by_tag.get(tag).into_iter().flatten());
+        }
+        for cable_id in changed.clone() {
+            self.evaluate_cable(cable_id);
+        }
+        for raceway in raceways {
+            self.evaluate_raceway(raceway);
+            changed.extend(self.by_raceway.get(&raceway).into_iter().flatten());
+        }
+
+        changed.into_iter()
+            .map(|cable_id| CableValidationUpdate { cable_id, results: self.cable_results(cable_id) })
+            .collect()
+    }
+
+    fn evaluate_cable(&mut self, id: i64) {
+        let Some(cable) = self.cables.get(&id) else {
+            return;
+        };
+        // Duplicate detection only needs the cables with the same tag
+        let same_tag: Vec<Cable> = self.by_tag.get(&tag_key(cable))
+            .into_iter()
+            .flatten()
+            .filter_map(|other| self.cables.get(other).cloned())
+            .collect();
+        let results = self.validator.validate_cable(cable, &same_tag);
+        self.cable_results.insert(id, results);
+    }
+
+    fn evaluate_raceway(&mut self, raceway: RacewayKey) {
+        let raceway_cables: Vec<&Cable> = self.by_raceway.get(&raceway)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.cables.get(id))
+            .collect();
+
+        let mut results = Vec::new();
+        if raceway_cables.len() > 1 {
+            let tag = self.raceway_tags.get(&raceway).map(String::as_str).unwrap_or_default();
+            results.extend(self.validator.check_route_segregation(tag, &raceway_cables));
+        }
+        if let Some(conduit) = match raceway {
+            RacewayKey::Conduit(id) => self.conduits.get(&id),
+            RacewayKey::Tray(_) => None,
+        } {
+            results.extend(self.validator.check_conduit_cables(conduit, &raceway_cables));
+        }
+
+        if results.is_empty() {
+            self.raceway_results.remove(&raceway);
+        } else {
+            self.raceway_results.insert(raceway, results);
+        }
+    }
+
+    fn index(&mut self, cable: Cable, route_segments: &[CableRouteSegment]) {
+        let Some(id) = cable.id else {
+            return;
+        };
+        self.by_tag.entry(tag_key(&cable)).or_default().insert(id);
+        let mut route = Vec::new();
+        for segment in route_segments {
+            let Some(raceway) = RacewayKey::of(segment).filter(|raceway| !route.contains(raceway)) else {
+                continue;
+            };
+            self.raceway_tags.insert(raceway, segment.raceway_tag.clone());
+            self.by_raceway.entry(raceway).or_default().insert(id);
+            route.push(raceway);
+        }
+        self.routes.insert(id, route);
+        self.cables.insert(id, cable);
+    }
+
+    fn unindex(&mut self, id: i64) -> Option<(Cable, Vec<RacewayKey>)> {
+        let cable = self.cables.remove(&id)?;
+        if let Some(ids) = self.by_tag.get_mut(&tag_key(&cable)) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.by_tag.remove(&tag_key(&cable));
+            }
+        }
+        let route = self.routes.remove(&id).unwrap_or_default();
+        for raceway in &route {
+            if let Some(ids) = self.by_raceway.get_mut(raceway) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.by_raceway.remove(raceway);
+                    self.raceway_tags.remove(raceway);
+                }
+            }
+        }
+        Some((cable, route))
+    }
+}
@@ -1,38 +1,110 @@
 /**
  * Cable Validation Engine
- * Implements NEC-based validation rules for cable schedule compliance
+ * Implements NEC-based validation rules for cable schedule compliance, plus project-wide
+ * checks of I/O points, loads, raceways and the references between them
  */
 
-use crate::database::models::*;
+use crate::calculations::{CableGroundingCheck, CableShortCircuitCheck, ElectricalCalculator, MotorCircuitCheck, ProtectiveDeviceCheck};
+use crate::calculations::conduit_fill;
+use crate::calculations::tray_fill::TrayFillCheck;
+use crate::database::{models::*, Database};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub mod incremental;
+pub mod rules;
+
+use rules::ValidationRules;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ValidationSeverity {
     Error,   // Blocks operations
     Warning, // Allows override
     Info,    // Informational only
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ValidationType {
     DuplicateTag,
     SegregationViolation,
     RequiredField,
     InvalidValue,
     NecCompliance,
+    ShortCircuitWithstand,
+    GroundingConductor,
+    OvercurrentProtection,
+    MotorCircuit,
+    Capacity,
+    IoAddressConflict,
+    MissingReference,
+}
+
+/// The kind of record a validation result is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntityType {
+    Cable,
+    IOPoint,
+    Load,
+    Conduit,
+    Tray,
+    ProtectiveDevice,
+}
+
+impl ValidationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationType::DuplicateTag => "DuplicateTag",
+            ValidationType::SegregationViolation => "SegregationViolation",
+            ValidationType::RequiredField => "RequiredField",
+            ValidationType::InvalidValue => "InvalidValue",
+            ValidationType::NecCompliance => "NecCompliance",
+            ValidationType::ShortCircuitWithstand => "ShortCircuitWithstand",
+            ValidationType::GroundingConductor => "GroundingConductor",
+            ValidationType::OvercurrentProtection => "OvercurrentProtection",
+            ValidationType::MotorCircuit => "MotorCircuit",
+            ValidationType::Capacity => "Capacity",
+            ValidationType::IoAddressConflict => "IoAddressConflict",
+            ValidationType::MissingReference => "MissingReference",
+        }
+    }
+}
+
+impl std::str::FromStr for ValidationType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DuplicateTag" => Ok(ValidationType::DuplicateTag),
+            "SegregationViolation" => Ok(ValidationType::SegregationViolation),
+            "RequiredField" => Ok(ValidationType::RequiredField),
+            "InvalidValue" => Ok(ValidationType::InvalidValue),
+            "NecCompliance" => Ok(ValidationType::NecCompliance),
+            "ShortCircuitWithstand" => Ok(ValidationType::ShortCircuitWithstand),
+            "GroundingConductor" => Ok(ValidationType::GroundingConductor),
+            "OvercurrentProtection" => Ok(ValidationType::OvercurrentProtection),
+            "MotorCircuit" => Ok(ValidationType::MotorCircuit),
+            "Capacity" => Ok(ValidationType::Capacity),
+            "IoAddressConflict" => Ok(ValidationType::IoAddressConflict),
+            "MissingReference" => Ok(ValidationType::MissingReference),
+            other => Err(format!("Unknown validation type '{}'", other)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
-    pub cable_id: Option<i64>,
-    pub cable_tag: String,
+    pub entity_type: EntityType,
+    pub entity_id: Option<i64>,
+    pub entity_tag: String,
+    pub cable_id: Option<i64>, // the cable involved, if any; overrides are recorded against it
+    pub cable_tag: String,     // empty when no cable is involved
     pub severity: ValidationSeverity,
     pub validation_type: ValidationType,
     pub message: String,
     pub field: Option<String>,
     pub suggested_fix: Option<String>,
     pub override_allowed: bool,
+    pub suppressed: bool, // Covered by an unexpired override; not counted by severity
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,15 +113,146 @@ pub struct ValidationSummary {
     pub error_count: usize,
     pub warning_count: usize,
     pub info_count: usize,
+    pub suppressed_count: usize,
     pub results: Vec<ValidationResult>,
     pub validation_time: chrono::DateTime<chrono::Utc>,
 }
 
-pub struct CableValidator;
+impl ValidationSummary {
+    /// Add results from checks that need more than the cable list, keeping the counts in step
+    pub fn add_results(&mut self, results: Vec<ValidationResult>) {
+        for result in &results {
+            match result.severity {
+                ValidationSeverity::Error => self.error_count += 1,
+                ValidationSeverity::Warning => self.warning_count += 1,
+                ValidationSeverity::Info => self.info_count += 1,
+            }
+        }
+        self.results.extend(results);
+    }
+
+    /// Mark results covered by an override as suppressed and take them out of the severity counts
+    pub fn apply_overrides(&mut self, overrides: &[ValidationOverride], cables: &[Cable]) {
+        for result in &mut self.results {
+            if result.suppressed || !is_overridden(result, overrides, cables) {
+                continue;
+            }
+            result.suppressed = true;
+            self.suppressed_count += 1;
+            match result.severity {
+                ValidationSeverity::Error => self.error_count -= 1,
+                ValidationSeverity::Warning => self.warning_count -= 1,
+                ValidationSeverity::Info => self.info_count -= 1,
+            }
+        }
+    }
+}
+
+/// Mark results covered by an override as suppressed, for callers that work without a summary
+pub fn apply_overrides(results: &mut [ValidationResult], overrides: &[ValidationOverride], cables: &[Cable]) {
+    for result in results {
+        if is_overridden(result, overrides, cables) {
+            result.suppressed = true;
+        }
+    }
+}
+
+/// An override holds while it isn't revoked and the cable fields the rule looks at are unchanged
+fn is_overridden(result: &ValidationResult, overrides: &[ValidationOverride], cables: &[Cable]) -> bool {
+    if !result.override_allowed {
+        return false;
+    }
+    let Some(cable) = cables.iter().find(|c| c.id.is_some() && c.id == result.cable_id) else {
+        return false;
+    };
+    let fingerprint = override_fingerprint(cable, &result.validation_type);
+
+    overrides.iter().any(|o| {
+        o.revoked_at.is_none()
+            && o.cable_id == result.cable_id
+            && o.validation_type == result.validation_type
+            && o.field == result.field
+            && o.fingerprint == fingerprint
+    })
+}
+
+/// Values of the cable fields a validation type depends on. An override records this when it is
+/// created, so editing any of them makes the override expire.
+pub fn override_fingerprint(cable: &Cable, validation_type: &ValidationType) -> String {
+    let values = match validation_type {
+        ValidationType::DuplicateTag
+        | ValidationType::RequiredField
+        | ValidationType::Capacity
+        | ValidationType::IoAddressConflict => serde_json::json!([cable.tag]),
+        ValidationType::MissingReference => serde_json::json!([
+            cable.tray_id, cable.conduit_id, cable.parent_cable_id
+        ]),
+        ValidationType::SegregationViolation => serde_json::json!([
+            cable.route, cable.segregation_class, cable.voltage, cable.function, cable.tray_id, cable.conduit_id
+        ]),
+        ValidationType::InvalidValue => serde_json::json!([
+            cable.voltage, cable.length, cable.spare_percentage, cable.segregation_class
+        ]),
+        ValidationType::NecCompliance => serde_json::json!([
+            cable.size, cable.length, cable.current, cable.voltage, cable.part_number
+        ]),
+        ValidationType::ShortCircuitWithstand => serde_json::json!([
+            cable.size, cable.cable_type, cable.part_number, cable.from_equipment
+        ]),
+        ValidationType::GroundingConductor
+        | ValidationType::OvercurrentProtection
+        | ValidationType::MotorCircuit => serde_json::json!([
+            cable.size, cable.cable_type, cable.part_number, cable.current
+        ]),
+    };
+    values.to_string()
+}
+
+/// Every entity of a project, loaded once for project-wide validation
+pub struct ProjectEntities {
+    pub cables: Vec<Cable>,
+    pub route_segments: Vec<CableRouteSegment>,
+    pub io_points: Vec<IOPoint>,
+    pub loads: Vec<Load>,
+    pub conduits: Vec<Conduit>,
+    pub trays: Vec<Tray>,
+    pub protective_devices: Vec<ProtectiveDevice>,
+}
+
+impl ProjectEntities {
+    pub fn load(db: &Database, project_id: i64) -> rusqlite::Result<Self> {
+        Ok(Self {
+            cables: db.get_cables(project_id)?,
+            route_segments: db.get_project_route_segments(project_id)?,
+            io_points: db.get_io_points(project_id)?,
+            loads: db.get_loads(project_id)?,
+            conduits: db.get_conduits(project_id)?,
+            trays: db.get_trays(project_id)?,
+            protective_devices: db.get_protective_devices(project_id)?,
+        })
+    }
+}
+
+pub struct CableValidator {
+    rules: ValidationRules,
+}
 
 impl CableValidator {
     pub fn new() -> Self {
-        Self
+        Self { rules: ValidationRules::default() }
+    }
+
+    /// Evaluate segregation against a project's own rule set instead of the defaults
+    pub fn with_rules(rules: ValidationRules) -> Self {
+        Self { rules }
+    }
+
+    /// Validator using the project's active rule set, or the defaults when none is active
+    pub fn for_project(db: &Database, project_id: i64) -> rusqlite::Result<Self> {
+        Ok(match db.get_active_validation_rule_set(project_id)? {
+            Some(rule_set) => Self::with_rules(rule_set.rules),
+            None => Self::new(),
+        })
     }
 
     /// Validate a single cable against all rules
@@ -68,8 +271,8 @@ impl CableValidator {
         results
     }
 
-    /// Validate all cables in a project
-    pub fn validate_all_cables(&self, cables: &[Cable]) -> ValidationSummary {
+    /// Validate all cables in a project, checking segregation in the raceways of their stored routes
+    pub fn validate_all_cables(&self, cables: &[Cable], route_segments: &[CableRouteSegment]) -> ValidationSummary {
         let mut all_results = Vec::new();
 
         // Validate each cable individually
@@ -79,7 +282,7 @@ impl CableValidator {
         }
 
         // Add project-level validations
-        all_results.extend(self.validate_segregation_rules(cables));
+        all_results.extend(self.validate_segregation_rules(cables, route_segments));
 
         // Count by severity
         let error_count = all_results.iter()
@@ -97,11 +300,49 @@ impl CableValidator {
             error_count,
             warning_count,
             info_count,
+            suppressed_count: 0,
             results: all_results,
             validation_time: chrono::Utc::now(),
         }
     }
 
+    /// Validate every entity in a project: the cable rules, including those that need loads,
+    /// fault levels and the cable library from the database, then I/O points, loads, raceways
+    /// and the references between them
+    pub fn validate_project(&self, db: &Database, project_id: i64) -> rusqlite::Result<ValidationSummary> {
+        let entities = ProjectEntities::load(db, project_id)?;
+        let library = db.get_cable_library_items(None, None)?;
+        let calculator = ElectricalCalculator::new();
+
+        let protection = db.check_protective_devices(project_id)?;
+
+        let mut summary = self.validate_all_cables(&entities.cables, &entities.route_segments);
+        summary.add_results(self.validate_short_circuit(&db.check_short_circuit_withstand(project_id)?));
+        summary.add_results(self.validate_protective_devices(&protection));
+        summary.add_results(self.validate_motor_circuits(&db.check_motor_circuits(project_id)?));
+        let grounding: Vec<_> = entities.cables.iter()
+            .map(|cable| {
+                let device_rating = protection.iter()
+                    .find(|check| check.cable_id.is_some() && check.cable_id == cable.id)
+                    .map(|check| check.trip_rating);
+                calculator.check_cable_grounding_conductor(cable, &entities.loads, &library, device_rating)
+            })
+            .collect();
+        summary.add_results(self.validate_grounding_conductors(&grounding));
+        summary.add_results(self.validate_io_points(&entities.io_points));
+        summary.add_results(self.validate_loads(&entities.loads));
+        summary.add_results(self.validate_raceways(&entities.conduits, &entities.trays));
+        summary.add_results(self.validate_conduit_fills(&entities.conduits, &entities.cables, &entities.route_segments));
+        let tray_fills = entities.trays.iter()
+            .filter_map(|tray| tray.id.map(|id| db.check_tray_fill(id).map(|check| (tray, check))))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        summary.add_results(self.validate_tray_conductors(&tray_fills));
+        summary.add_results(self.validate_references(&entities));
+        summary.apply_overrides(&db.get_validation_overrides(project_id)?, &entities.cables);
+
+        Ok(summary)
+    }
+
     /// Validate required fields
     fn validate_required_fields(&self, cable: &Cable) -> Vec<ValidationResult> {
         let mut results = Vec::new();
@@ -109,7 +350,10 @@ impl CableValidator {
         // Tag is the only required field
         if cable.tag.trim().is_empty() {
             results.push(ValidationResult {
+                entity_type: EntityType::Cable,
+                entity_id: cable.id,
                 cable_id: cable.id,
+                entity_tag: cable.tag.clone(),
                 cable_tag: cable.tag.clone(),
                 severity: ValidationSeverity::Error,
                 validation_type: ValidationType::RequiredField,
@@ -117,6 +361,7 @@ impl CableValidator {
                 field: Some("tag".to_string()),
                 suggested_fix: Some("Enter a unique cable tag (e.g., C-001)".to_string()),
                 override_allowed: false,
+                suppressed: false,
             });
         }
 
@@ -146,7 +391,10 @@ impl CableValidator {
                 .collect();
 
             results.push(ValidationResult {
+                entity_type: EntityType::Cable,
+                entity_id: cable.id,
                 cable_id: cable.id,
+                entity_tag: cable.tag.clone(),
                 cable_tag: cable.tag.clone(),
                 severity: ValidationSeverity::Error,
                 validation_type: ValidationType::DuplicateTag,
@@ -154,6 +402,7 @@ impl CableValidator {
                 field: Some("tag".to_string()),
                 suggested_fix: Some(format!("Change to unique tag or use auto-numbering")),
                 override_allowed: true, // Can override with justification
+                suppressed: false,
             });
         }
 
@@ -168,7 +417,10 @@ impl CableValidator {
         if let Some(voltage) = cable.voltage {
             if voltage < 0.0 || voltage > 35000.0 {
                 results.push(ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
                     cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
                     cable_tag: cable.tag.clone(),
                     severity: ValidationSeverity::Warning,
                     validation_type: ValidationType::InvalidValue,
@@ -176,6 +428,7 @@ impl CableValidator {
                     field: Some("voltage".to_string()),
                     suggested_fix: Some("Verify voltage rating is correct".to_string()),
                     override_allowed: true,
+                    suppressed: false,
                 });
             }
         }
@@ -184,7 +437,10 @@ impl CableValidator {
         if let Some(length) = cable.length {
             if length < 0.0 {
                 results.push(ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
                     cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
                     cable_tag: cable.tag.clone(),
                     severity: ValidationSeverity::Error,
                     validation_type: ValidationType::InvalidValue,
@@ -192,10 +448,14 @@ impl CableValidator {
                     field: Some("length".to_string()),
                     suggested_fix: Some("Enter a positive length value".to_string()),
                     override_allowed: false,
+                    suppressed: false,
                 });
             } else if length > 10000.0 {
                 results.push(ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
                     cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
                     cable_tag: cable.tag.clone(),
                     severity: ValidationSeverity::Warning,
                     validation_type: ValidationType::InvalidValue,
@@ -203,6 +463,27 @@ impl CableValidator {
                     field: Some("length".to_string()),
                     suggested_fix: Some("Verify length is correct".to_string()),
                     override_allowed: true,
+                    suppressed: false,
+                });
+            }
+        }
+
+        // Segregation class must be one the rule set knows, or no segregation rule can apply to it
+        if let Some(class) = cable.segregation_class.as_deref() {
+            if !class.trim().is_empty() && !self.rules.has_segregation_class(class) {
+                results.push(ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
+                    cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
+                    cable_tag: cable.tag.clone(),
+                    severity: ValidationSeverity::Warning,
+                    validation_type: ValidationType::InvalidValue,
+                    message: format!("Segregation class '{}' is not defined in the project rule set", class),
+                    field: Some("segregation_class".to_string()),
+                    suggested_fix: Some("Choose a defined segregation class or add it to the rule set".to_string()),
+                    override_allowed: true,
+                    suppressed: false,
                 });
             }
         }
@@ -211,7 +492,10 @@ impl CableValidator {
         if let Some(spare_pct) = cable.spare_percentage {
             if spare_pct < 0.0 || spare_pct > 100.0 {
                 results.push(ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
                     cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
                     cable_tag: cable.tag.clone(),
                     severity: ValidationSeverity::Warning,
                     validation_type: ValidationType::InvalidValue,
@@ -219,6 +503,7 @@ impl CableValidator {
                     field: Some("spare_percentage".to_string()),
                     suggested_fix: Some("Enter percentage as 0-100 (e.g., 10 for 10%)".to_string()),
                     override_allowed: true,
+                    suppressed: false,
                 });
             }
         }
@@ -227,33 +512,49 @@ impl CableValidator {
     }
 
     /// Validate cable segregation rules
-    fn validate_segregation_rules(&self, cables: &[Cable]) -> Vec<ValidationResult> {
+    fn validate_segregation_rules(&self, cables: &[Cable], route_segments: &[CableRouteSegment]) -> Vec<ValidationResult> {
         let mut results = Vec::new();
+        let by_id: HashMap<i64, &Cable> = cables.iter()
+            .filter_map(|cable| cable.id.map(|id| (id, cable)))
+            .collect();
 
-        // Group cables by route to check segregation within same conduit/tray
-        let mut route_groups: HashMap<String, Vec<&Cable>> = HashMap::new();
+        // Group cables by each conduit/tray along their routes to check segregation within it
+        let mut route_groups: BTreeMap<RacewayKey, (&str, Vec<&Cable>)> = BTreeMap::new();
 
-        for cable in cables {
-            if let Some(route) = &cable.route {
-                if !route.trim().is_empty() {
-                    // For now, treat entire route as key (could be enhanced to split by conduit)
-                    route_groups.entry(route.clone())
-                        .or_insert_with(Vec::new)
-                        .push(cable);
-                }
+        for segment in route_segments {
+            let (Some(key), Some(&cable)) = (RacewayKey::of(segment), by_id.get(&segment.cable_id)) else {
+                continue;
+            };
+            let (_, group) = route_groups.entry(key)
+                .or_insert_with(|| (segment.raceway_tag.as_str(), Vec::new()));
+            if !group.iter().any(|other| other.id == cable.id) {
+                group.push(cable);
             }
         }
 
-        // Check segregation within each route
-        for (route, route_cables) in route_groups {
+        // Check segregation within each conduit/tray
+        for (route, route_cables) in route_groups.into_values() {
             if route_cables.len() > 1 {
-                results.extend(self.check_route_segregation(&route, &route_cables));
+                results.extend(self.check_route_segregation(route, &route_cables));
             }
         }
 
         results
     }
 
+    /// Whether a cable can join the cables already in a conduit or tray without any segregation
+    /// violation involving it. Used by the auto-router.
+    pub fn can_share_raceway(&self, cable: &Cable, occupants: &[&Cable]) -> bool {
+        if occupants.is_empty() {
+            return true;
+        }
+        let mut cables = occupants.to_vec();
+        cables.push(cable);
+        !self.check_route_segregation("", &cables)
+            .iter()
+            .any(|result| result.cable_id == cable.id)
+    }
+
     /// Check segregation rules for cables in the same route
     fn check_route_segregation(&self, route: &str, cables: &[&Cable]) -> Vec<ValidationResult> {
         let mut results = Vec::new();
@@ -295,7 +596,10 @@ impl CableValidator {
             // Create violations for all affected cables
             for cable in power_cables.iter().chain(signal_cables.iter()) {
                 results.push(ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
                     cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
                     cable_tag: cable.tag.clone(),
                     severity: ValidationSeverity::Warning,
                     validation_type: ValidationType::SegregationViolation,
@@ -303,6 +607,7 @@ impl CableValidator {
                     field: Some("route".to_string()),
                     suggested_fix: Some("Separate power and signal cables into different conduits per NEC".to_string()),
                     override_allowed: true,
+                    suppressed: false,
                 });
             }
         }
@@ -310,128 +615,119 @@ impl CableValidator {
         results
     }
 
-    /// Check voltage level separation (NEC 300.3)
+    /// Check voltage level separation against the rule set's band incompatibilities (NEC 300.3 by default)
     fn check_voltage_level_separation(&self, route: &str, cables: &[&Cable]) -> Vec<ValidationResult> {
-        let mut results = Vec::new();
+        let mut band_groups: HashMap<&str, Vec<&Cable>> = HashMap::new();
 
-        // Group cables by voltage level
-        let mut voltage_groups: HashMap<String, Vec<&Cable>> = HashMap::new();
-        
         for cable in cables {
-            if let Some(voltage) = cable.voltage {
-                let voltage_class = if voltage <= 50.0 {
-                    "Low Voltage (<50V)"
-                } else if voltage <= 600.0 {
-                    "Medium Voltage (50-600V)"
-                } else if voltage <= 1000.0 {
-                    "High Voltage (600-1000V)"
-                } else {
-                    "Extra High Voltage (>1000V)"
-                };
-                
-                voltage_groups.entry(voltage_class.to_string())
-                    .or_insert_with(Vec::new)
+            if let Some(band) = cable.voltage.and_then(|voltage| self.rules.voltage_band(voltage)) {
+                band_groups.entry(band)
+                    .or_default()
                     .push(cable);
             }
         }
 
-        // Check for mixed voltage levels that require separation
-        if voltage_groups.len() > 1 {
-            let has_low = voltage_groups.contains_key("Low Voltage (<50V)");
-            let has_high = voltage_groups.contains_key("High Voltage (600-1000V)") || 
-                          voltage_groups.contains_key("Extra High Voltage (>1000V)");
-
-            if has_low && has_high {
-                for cable in cables {
-                    results.push(ValidationResult {
-                        cable_id: cable.id,
-                        cable_tag: cable.tag.clone(),
-                        severity: ValidationSeverity::Error,
-                        validation_type: ValidationType::SegregationViolation,
-                        message: format!("Low voltage and high voltage cables in same route '{}' (NEC 300.3)", route),
-                        field: Some("route".to_string()),
-                        suggested_fix: Some("Separate low voltage (<50V) from high voltage (>600V) cables".to_string()),
-                        override_allowed: true,
-                    });
-                }
-            }
-        }
-
-        results
+        self.check_incompatibilities(route, &band_groups, &self.rules.voltage_band_incompatibilities, "route")
     }
 
-    /// Check segregation class conflicts (NEC-based)
+    /// Check segregation class conflicts against the rule set's incompatibility matrix
     fn check_segregation_class_conflicts(&self, route: &str, cables: &[&Cable]) -> Vec<ValidationResult> {
-        let mut results = Vec::new();
+        let mut class_groups: HashMap<&str, Vec<&Cable>> = HashMap::new();
 
-        // Group by segregation class
-        let mut class_groups: HashMap<String, Vec<&Cable>> = HashMap::new();
-        
         for cable in cables {
             if let Some(seg_class) = &cable.segregation_class {
-                class_groups.entry(seg_class.clone())
-                    .or_insert_with(Vec::new)
+                class_groups.entry(seg_class.as_str())
+                    .or_default()
                     .push(cable);
             }
         }
 
-        // Define incompatible segregation class pairs
-        let incompatible_pairs = [
-            ("IS Signal", "Non-IS Signal"),
-            ("IS Signal", "Power 120VAC"),
-            ("IS Signal", "Power 240VAC"),
-            ("IS Signal", "Power 480VAC"),
-            ("IS Signal", "Power 600VAC"),
-            ("Control Power 24VDC", "Power 480VAC"),
-            ("Control Power 24VDC", "Power 600VAC"),
-        ];
+        self.check_incompatibilities(route, &class_groups, &self.rules.class_incompatibilities, "segregation_class")
+    }
 
-        for (class1, class2) in incompatible_pairs {
-            if class_groups.contains_key(class1) && class_groups.contains_key(class2) {
-                let affected_cables: Vec<&Cable> = class_groups[class1].iter()
-                    .chain(class_groups[class2].iter())
-                    .copied()
-                    .collect();
+    /// Flag every cable of both groups for each incompatible pair present in the route. A cable
+    /// caught by several rules, e.g. low voltage beside both higher bands, gets one result naming
+    /// each pair, with the strictest severity.
+    fn check_incompatibilities(
+        &self,
+        route: &str,
+        groups: &HashMap<&str, Vec<&Cable>>,
+        rules: &[rules::IncompatibilityRule],
+        field: &str,
+    ) -> Vec<ValidationResult> {
+        let mut conflicts: Vec<(&Cable, Vec<&rules::IncompatibilityRule>)> = Vec::new();
 
-                for cable in affected_cables {
-                    results.push(ValidationResult {
-                        cable_id: cable.id,
-                        cable_tag: cable.tag.clone(),
-                        severity: ValidationSeverity::Error,
-                        validation_type: ValidationType::SegregationViolation,
-                        message: format!("Incompatible segregation classes '{}' and '{}' in route '{}'", class1, class2, route),
-                        field: Some("segregation_class".to_string()),
-                        suggested_fix: Some(format!("Separate {} from {} cables", class1, class2)),
-                        override_allowed: true,
-                    });
+        for rule in rules {
+            let (Some(first), Some(second)) = (groups.get(rule.first.as_str()), groups.get(rule.second.as_str())) else {
+                continue;
+            };
+            for &cable in first.iter().chain(second.iter()) {
+                match conflicts.iter_mut().find(|(other, _)| std::ptr::eq(*other, cable)) {
+                    Some((_, cable_rules)) => cable_rules.push(rule),
+                    None => conflicts.push((cable, vec![rule])),
                 }
             }
         }
 
-        results
+        let pair = |rule: &rules::IncompatibilityRule| {
+            let reference = rule.reference.as_ref()
+                .map(|reference| format!(" ({})", reference))
+                .unwrap_or_default();
+            (format!("'{}' and '{}'", rule.first, rule.second), reference)
+        };
+        let rank = |severity: &ValidationSeverity| match severity {
+            ValidationSeverity::Error => 0,
+            ValidationSeverity::Warning => 1,
+            ValidationSeverity::Info => 2,
+        };
+
+        conflicts.into_iter()
+            .map(|(cable, cable_rules)| {
+                let (first_pair, first_reference) = pair(cable_rules[0]);
+                let mut message = format!("Incompatible {} cables in route '{}'{}", first_pair, route, first_reference);
+                for &rule in &cable_rules[1..] {
+                    let (also, reference) = pair(rule);
+                    message.push_str(&format!("; also {}{}", also, reference));
+                }
+                let suggested_fix = cable_rules.iter()
+                    .map(|rule| format!("Separate {} from {} cables", rule.first, rule.second))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+
+                ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
+                    cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
+                    cable_tag: cable.tag.clone(),
+                    severity: cable_rules.iter().map(|rule| &rule.severity).min_by_key(|severity| rank(severity)).unwrap().clone(),
+                    validation_type: ValidationType::SegregationViolation,
+                    message,
+                    field: Some(field.to_string()),
+                    suggested_fix: Some(suggested_fix),
+                    override_allowed: cable_rules.iter().all(|rule| rule.override_allowed),
+                    suppressed: false,
+                }
+            })
+            .collect()
     }
 
-    /// Check intrinsic safety separation (NEC 504.30)
+    /// Check intrinsic safety separation (NEC 504.30) for the rule set's intrinsically safe classes
     fn check_intrinsic_safety_separation(&self, route: &str, cables: &[&Cable]) -> Vec<ValidationResult> {
         let mut results = Vec::new();
 
-        let is_cables: Vec<&Cable> = cables.iter()
-            .filter(|c| c.segregation_class.as_ref()
-                .map_or(false, |sc| sc.contains("IS")))
+        let (is_cables, non_is_cables): (Vec<&Cable>, Vec<&Cable>) = cables.iter()
             .copied()
-            .collect();
-
-        let non_is_cables: Vec<&Cable> = cables.iter()
-            .filter(|c| c.segregation_class.as_ref()
-                .map_or(false, |sc| !sc.contains("IS")) || 
-                c.segregation_class.is_none())
-            .copied()
-            .collect();
+            .partition(|c| c.segregation_class.as_deref()
+                .is_some_and(|class| self.rules.is_intrinsically_safe(class)));
 
         if !is_cables.is_empty() && !non_is_cables.is_empty() {
             for cable in is_cables.iter().chain(non_is_cables.iter()) {
                 results.push(ValidationResult {
+                    entity_type: EntityType::Cable,
+                    entity_id: cable.id,
                     cable_id: cable.id,
+                    entity_tag: cable.tag.clone(),
                     cable_tag: cable.tag.clone(),
                     severity: ValidationSeverity::Error,
                     validation_type: ValidationType::SegregationViolation,
@@ -439,11 +735,933 @@ impl CableValidator {
                     field: Some("segregation_class".to_string()),
                     suggested_fix: Some("IS cables must be separated from all non-IS circuits".to_string()),
                     override_allowed: false, // Critical safety rule
+                    suppressed: false,
+                });
+            }
+        }
+
+        results
+    }
+
+    /// Flag cables whose conductors cannot withstand the fault current of their source bus
+    pub fn validate_short_circuit(&self, checks: &[CableShortCircuitCheck]) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+
+        for check in checks {
+            let (Some(calculation), Some(result)) = (&check.calculation, &check.result) else {
+                continue;
+            };
+            if result.is_adequate {
+                continue;
+            }
+
+            let suggested_fix = match &result.minimum_size {
+                Some(size) => format!("Increase conductor to at least {} or use a faster protective device", size),
+                None => "Use parallel conductors or a faster, current-limiting protective device".to_string(),
+            };
+            results.push(ValidationResult {
+                entity_type: EntityType::Cable,
+                entity_id: check.cable_id,
+                cable_id: check.cable_id,
+                entity_tag: check.cable_tag.clone(),
+                cable_tag: check.cable_tag.clone(),
+                severity: ValidationSeverity::Error,
+                validation_type: ValidationType::ShortCircuitWithstand,
+                message: format!(
+                    "Conductor withstands {:.1} kA for {:.2} s, below the {:.1} kA available at '{}'",
+                    result.withstand_current / 1000.0,
+                    calculation.clearing_time,
+                    result.fault_current / 1000.0,
+                    check.bus.as_deref().unwrap_or_default()
+                ),
+                field: Some("size".to_string()),
+                suggested_fix: Some(suggested_fix),
+                override_allowed: true,
+                suppressed: false,
+            });
+        }
+
+        results
+    }
+
+    /// Flag ground conductors smaller than NEC 250.122 requires for the circuit's overcurrent device
+    pub fn validate_grounding_conductors(&self, checks: &[CableGroundingCheck]) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+
+        for check in checks {
+            let (Some(sizing), Some(false)) = (&check.sizing, check.is_adequate) else {
+                continue;
+            };
+
+            let device = if check.device_rating_assumed {
+                format!("the {:.0} A device the phase conductors permit", sizing.device_rating)
+            } else {
+                format!("a {:.0} A device", sizing.device_rating)
+            };
+            let upsized = if sizing.phase_upsize_ratio > 1.0 {
+                format!(", increased {:.2}x with the phase conductors (250.122(B))", sizing.phase_upsize_ratio)
+            } else {
+                String::new()
+            };
+            results.push(ValidationResult {
+                entity_type: EntityType::Cable,
+                entity_id: check.cable_id,
+                cable_id: check.cable_id,
+                entity_tag: check.cable_tag.clone(),
+                cable_tag: check.cable_tag.clone(),
+                // An assumed device rating is the worst case, so the circuit may still comply
+                severity: if check.device_rating_assumed { ValidationSeverity::Warning } else { ValidationSeverity::Error },
+                validation_type: ValidationType::GroundingConductor,
+                message: format!(
+                    "Ground conductor {} is smaller than the {} NEC 250.122 requires for {}{}",
+                    check.ground_size.as_deref().unwrap_or_default(),
+                    sizing.required_size,
+                    device,
+                    upsized
+                ),
+                field: Some("part_number".to_string()),
+                suggested_fix: Some(format!("Select a cable with at least a {} ground conductor", sizing.required_size)),
+                override_allowed: true,
+                suppressed: false,
+            });
+        }
+
+        results
+    }
+
+    /// Turn protective device checks into validation results, one per violated rule
+    pub fn validate_protective_devices(&self, checks: &[ProtectiveDeviceCheck]) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+
+        for check in checks {
+            let suggested_fix = match (check.maximum_motor_rating, check.maximum_conductor_rating) {
+                (Some(maximum), _) => format!("Use a device rated {:.0} A or less", maximum),
+                (None, Some(maximum)) => format!("Use a device rated {:.0} A or less, or larger conductors", maximum),
+                (None, None) => "Check the device frame and trip ratings".to_string(),
+            };
+            for violation in &check.violations {
+                results.push(ValidationResult {
+                    entity_type: EntityType::ProtectiveDevice,
+                    entity_id: check.device_id,
+                    cable_id: check.cable_id,
+                    entity_tag: check.device_tag.clone(),
+                    cable_tag: check.cable_tag.clone().unwrap_or_default(),
+                    severity: ValidationSeverity::Error,
+                    validation_type: ValidationType::OvercurrentProtection,
+                    message: format!("{}: {}", check.device_tag, violation),
+                    field: Some("trip_rating".to_string()),
+                    suggested_fix: Some(suggested_fix.clone()),
+                    override_allowed: check.cable_id.is_some(),
+                    suppressed: false,
+                });
+            }
+        }
+
+        results
+    }
+
+    /// Flag motor branch circuits whose conductors carry less than 125% of the motor's
+    /// table full-load current (NEC 430.22)
+    pub fn validate_motor_circuits(&self, checks: &[MotorCircuitCheck]) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+
+        for check in checks {
+            let (Some(sizing), Some(false)) = (&check.sizing, check.is_adequate) else {
+                continue;
+            };
+
+            results.push(ValidationResult {
+                entity_type: EntityType::Load,
+                entity_id: check.load_id,
+                cable_id: check.cable_id,
+                entity_tag: check.load_tag.clone(),
+                cable_tag: check.cable_tag.clone().unwrap_or_default(),
+                severity: ValidationSeverity::Error,
+                validation_type: ValidationType::MotorCircuit,
+                message: format!(
+                    "{} conductors rated {:.0} A are below the {:.1} A NEC 430.22 requires for {} ({} HP, {:.1} A full-load current)",
+                    check.installed_size.as_deref().unwrap_or_default(),
+                    check.installed_ampacity.unwrap_or_default(),
+                    sizing.minimum_ampacity,
+                    check.load_tag,
+                    sizing.horsepower,
+                    sizing.full_load_current
+                ),
+                field: Some("size".to_string()),
+                suggested_fix: Some(format!("Use {} or larger conductors", sizing.recommended_size)),
+                override_allowed: check.cable_id.is_some(),
+                suppressed: false,
+            });
+        }
+
+        results
+    }
+
+    /// Flag I/O points that share a PLC, rack, slot and channel anywhere in the project, in
+    /// address order
+    pub fn validate_io_points(&self, io_points: &[IOPoint]) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+
+        let mut addresses: BTreeMap<(&str, i32, i32, i32), Vec<&IOPoint>> = BTreeMap::new();
+        for point in io_points {
+            if let (Some(plc), Some(rack), Some(slot), Some(channel)) = (&point.plc_name, point.rack, point.slot, point.channel) {
+                addresses.entry((plc.as_str(), rack, slot, channel))
+                    .or_default()
+                    .push(point);
+            }
+        }
+
+        for ((plc, rack, slot, channel), points) in addresses {
+            if points.len() < 2 {
+                continue;
+            }
+            for point in &points {
+                let others: Vec<&str> = points.iter()
+                    .filter(|other| other.id != point.id)
+                    .map(|other| other.tag.as_str())
+                    .collect();
+                results.push(ValidationResult {
+                    entity_type: EntityType::IOPoint,
+                    entity_id: point.id,
+                    cable_id: None,
+                    entity_tag: point.tag.clone(),
+                    cable_tag: String::new(),
+                    severity: ValidationSeverity::Error,
+                    validation_type: ValidationType::IoAddressConflict,
+                    message: format!(
+                        "I/O address {} rack {} slot {} channel {} is also assigned to {}",
+                        plc, rack, slot, channel, others.join(", ")
+                    ),
+                    field: Some("channel".to_string()),
+                    suggested_fix: Some("Assign a free channel".to_string()),
+                    override_allowed: false,
+                    suppressed: false,
+                });
+            }
+        }
+
+        results
+    }
+
+    /// Flag loads that no cable feeds
+    pub fn validate_loads(&self, loads: &[Load]) -> Vec<ValidationResult> {
+        loads.iter()
+            .filter(|load| load.cable_id.is_none())
+            .map(|load| ValidationResult {
+                entity_type: EntityType::Load,
+                entity_id: load.id,
+                cable_id: None,
+                entity_tag: load.tag.clone(),
+                cable_tag: String::new(),
+                severity: ValidationSeverity::Warning,
+                validation_type: ValidationType::RequiredField,
+                message: "Load has no feeder cable".to_string(),
+                field: Some("cable_id".to_string()),
+                suggested_fix: Some("Assign a feeder cable or generate one from the load".to_string()),
+                override_allowed: false,
+                suppressed: false,
+            })
+            .collect()
+    }
+
+    /// Flag conduits and trays filled past their maximum, and trays missing the dimensions
+    /// their fill is calculated from
+    pub fn validate_raceways(&self, conduits: &[Conduit], trays: &[Tray]) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+
+        for conduit in conduits {
+            if conduit.fill_percentage > conduit.max_fill_percentage {
+                results.push(ValidationResult {
+                    entity_type: EntityType::Conduit,
+                    entity_id: conduit.id,
+                    cable_id: None,
+                    entity_tag: conduit.tag.clone(),
+                    cable_tag: String::new(),
+                    severity: ValidationSeverity::Error,
+                    validation_type: ValidationType::Capacity,
+                    message: format!(
+                        "Conduit fill {:.1}% exceeds the {:.0}% maximum (NEC Chapter 9, Table 1)",
+                        conduit.fill_percentage, conduit.max_fill_percentage
+                    ),
+                    field: Some("fill_percentage".to_string()),
+                    suggested_fix: Some("Move cables to another conduit or use a larger trade size".to_string()),
+                    override_allowed: false,
+                    suppressed: false,
+                });
+            }
+        }
+
+        for tray in trays {
+            if tray.fill_percentage > tray.max_fill_percentage {
+                results.push(ValidationResult {
+                    entity_type: EntityType::Tray,
+                    entity_id: tray.id,
+                    cable_id: None,
+                    entity_tag: tray.tag.clone(),
+                    cable_tag: String::new(),
+                    severity: ValidationSeverity::Error,
+                    validation_type: ValidationType::Capacity,
+                    message: format!(
+                        "Tray fill {:.1}% exceeds the {:.0}% maximum (NEC 392.22)",
+                        tray.fill_percentage, tray.max_fill_percentage
+                    ),
+                    field: Some("fill_percentage".to_string()),
+                    suggested_fix: Some("Move cables to another tray or use a wider tray".to_string()),
+                    override_allowed: false,
+                    suppressed: false,
                 });
             }
+            for (field, value) in [("width", tray.width), ("height", tray.height)] {
+                if !matches!(value, Some(v) if v > 0.0) {
+                    results.push(ValidationResult {
+                        entity_type: EntityType::Tray,
+                        entity_id: tray.id,
+                        cable_id: None,
+                        entity_tag: tray.tag.clone(),
+                        cable_tag: String::new(),
+                        severity: ValidationSeverity::Warning,
+                        validation_type: ValidationType::RequiredField,
+                        message: format!("Tray has no {}, so its fill cannot be calculated", field),
+                        field: Some(field.to_string()),
+                        suggested_fix: Some(format!("Enter the tray {}", field)),
+                        override_allowed: false,
+                        suppressed: false,
+                    });
+                }
+            }
         }
 
         results
     }
 
-}
\ No newline at end of file
+    /// Check the cables of every conduit along their stored routes, see `check_conduit_cables`
+    pub fn validate_conduit_fills(
+        &self,
+        conduits: &[Conduit],
+        cables: &[Cable],
+        route_segments: &[CableRouteSegment],
+    ) -> Vec<ValidationResult> {
+        let by_id: HashMap<i64, &Cable> = cables.iter()
+            .filter_map(|cable| cable.id.map(|id| (id, cable)))
+            .collect();
+        let mut by_conduit: HashMap<i64, Vec<&Cable>> = HashMap::new();
+        for segment in route_segments {
+            let (Some(conduit_id), Some(&cable)) = (segment.conduit_id, by_id.get(&segment.cable_id)) else {
+                continue;
+            };
+            let conduit_cables = by_conduit.entry(conduit_id).or_default();
+            if !conduit_cables.iter().any(|other| other.id == cable.id) {
+                conduit_cables.push(cable);
+            }
+        }
+
+        conduits.iter()
+            .filter_map(|conduit| Some((conduit, by_conduit.get(&conduit.id?)?)))
+            .flat_map(|(conduit, conduit_cables)| self.check_conduit_cables(conduit, conduit_cables))
+            .collect()
+    }
+
+    /// Flag each cable in a conduit whose fill cannot be checked for lack of a trade size or
+    /// internal diameter, or whose cables can jam when pulled. Overfill is left to
+    /// `validate_raceways`, which reads the stored fill.
+    pub fn check_conduit_cables(&self, conduit: &Conduit, cables: &[&Cable]) -> Vec<ValidationResult> {
+        let check = conduit_fill::check_conduit_cables(conduit, cables);
+        let (validation_type, message, field, suggested_fix, override_allowed) = if check.internal_area.is_none() {
+            (
+                ValidationType::RequiredField,
+                format!("Conduit '{}' has no known trade size or internal diameter, so its fill cannot be checked", conduit.tag),
+                Some("size".to_string()),
+                "Enter the conduit type and trade size, or its internal diameter",
+                false,
+            )
+        } else if let (true, Some(ratio)) = (check.jam_risk, check.jam_ratio) {
+            (
+                ValidationType::Capacity,
+                format!(
+                    "Cables in conduit '{}' can jam when pulled: jam ratio {:.2} is between 2.8 and 3.2 (NEC Chapter 9, Table 1, Informational Note 2)",
+                    conduit.tag, ratio
+                ),
+                None,
+                "Use a different trade size or cable arrangement",
+                true,
+            )
+        } else {
+            return Vec::new();
+        };
+
+        cables.iter()
+            .map(|cable| ValidationResult {
+                entity_type: EntityType::Conduit,
+                entity_id: conduit.id,
+                cable_id: cable.id,
+                entity_tag: conduit.tag.clone(),
+                cable_tag: cable.tag.clone(),
+                severity: ValidationSeverity::Warning,
+                validation_type: validation_type.clone(),
+                message: message.clone(),
+                field: field.clone(),
+                suggested_fix: Some(suggested_fix.to_string()),
+                override_allowed,
+                suppressed: false,
+            })
+            .collect()
+    }
+
+    /// Flag trays holding single conductors smaller than 1/0 AWG, which the stored fill
+    /// percentage does not reflect
+    pub fn validate_tray_conductors(&self, tray_fills: &[(&Tray, TrayFillCheck)]) -> Vec<ValidationResult> {
+        tray_fills.iter()
+            .filter(|(_, check)| !check.undersized_single_conductors.is_empty())
+            .map(|(tray, check)| ValidationResult {
+                entity_type: EntityType::Tray,
+                entity_id: tray.id,
+                cable_id: None,
+                entity_tag: tray.tag.clone(),
+                cable_tag: String::new(),
+                severity: ValidationSeverity::Error,
+                validation_type: ValidationType::NecCompliance,
+                message: format!(
+                    "Single conductors in tray must be 1/0 AWG or larger (NEC 392.10(B)(1)): {}",
+                    check.undersized_single_conductors.join(", ")
+                ),
+                field: None,
+                suggested_fix: Some("Use multiconductor cable or 1/0 AWG and larger single conductors, or route them in conduit".to_string()),
+                override_allowed: false,
+                suppressed: false,
+            })
+            .collect()
+    }
+
+    /// Flag references to cables, loads, trays and conduits that were deleted while foreign keys
+    /// were not enforced, or that belong to another project
+    pub fn validate_references(&self, entities: &ProjectEntities) -> Vec<ValidationResult> {
+        let cable_ids: Vec<i64> = entities.cables.iter().filter_map(|c| c.id).collect();
+        let load_ids: Vec<i64> = entities.loads.iter().filter_map(|l| l.id).collect();
+        let tray_ids: Vec<i64> = entities.trays.iter().filter_map(|t| t.id).collect();
+        let conduit_ids: Vec<i64> = entities.conduits.iter().filter_map(|c| c.id).collect();
+
+        let mut results = Vec::new();
+
+        for cable in &entities.cables {
+            for (field, kind, reference, existing) in [
+                ("tray_id", "tray", cable.tray_id, &tray_ids),
+                ("conduit_id", "conduit", cable.conduit_id, &conduit_ids),
+                ("parent_cable_id", "cable", cable.parent_cable_id, &cable_ids),
+            ] {
+                if let Some(id) = reference.filter(|id| !existing.contains(id)) {
+                    results.push(missing_reference(EntityType::Cable, cable.id, &cable.tag, cable.id, field, kind, id));
+                }
+            }
+        }
+        for point in &entities.io_points {
+            if let Some(id) = point.cable_id.filter(|id| !cable_ids.contains(id)) {
+                results.push(missing_reference(EntityType::IOPoint, point.id, &point.tag, None, "cable_id", "cable", id));
+            }
+        }
+        for load in &entities.loads {
+            if let Some(id) = load.cable_id.filter(|id| !cable_ids.contains(id)) {
+                results.push(missing_reference(EntityType::Load, load.id, &load.tag, None, "cable_id", "cable", id));
+            }
+        }
+        for device in &entities.protective_devices {
+            for (field, kind, reference, existing) in [
+                ("load_id", "load", device.load_id, &load_ids),
+                ("cable_id", "cable", device.cable_id, &cable_ids),
+            ] {
+                if let Some(id) = reference.filter(|id| !existing.contains(id)) {
+                    results.push(missing_reference(EntityType::ProtectiveDevice, device.id, &device.tag, None, field, kind, id));
+                }
+            }
+        }
+
+        results
+    }
+}
+
+/// A conduit or tray that segregation is checked in. Raceways are told apart by id, so the
+/// casing of the route text and route parts that aren't raceways play no part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RacewayKey {
+    Conduit(i64),
+    Tray(i64),
+}
+
+impl RacewayKey {
+    pub fn of(segment: &CableRouteSegment) -> Option<Self> {
+        match (segment.conduit_id, segment.tray_id) {
+            (Some(id), _) => Some(Self::Conduit(id)),
+            (None, Some(id)) => Some(Self::Tray(id)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Result for a reference to a record missing from the project
+fn missing_reference(
+    entity_type: EntityType,
+    entity_id: Option<i64>,
+    tag: &str,
+    cable_id: Option<i64>,
+    field: &str,
+    kind: &str,
+    id: i64,
+) -> ValidationResult {
+    ValidationResult {
+        entity_type,
+        entity_id,
+        cable_id,
+        entity_tag: tag.to_string(),
+        cable_tag: if cable_id.is_some() { tag.to_string() } else { String::new() },
+        severity: ValidationSeverity::Error,
+        validation_type: ValidationType::MissingReference,
+        message: format!("Refers to {} {}, which does not exist in this project", kind, id),
+        field: Some(field.to_string()),
+        suggested_fix: Some(format!("Assign an existing {} or clear the reference", kind)),
+        override_allowed: false,
+        suppressed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::rules::{IncompatibilityRule, RuleSetExport, SegregationClassRule, VoltageBand};
+
+    fn cable(id: i64, tag: &str, segregation_class: Option<&str>, voltage: f64) -> Cable {
+        Cable {
+            id: Some(id),
+            project_id: 1,
+            revision_id: 1,
+            tag: tag.to_string(),
+            description: None,
+            function: None,
+            voltage: Some(voltage),
+            current: None,
+            cable_type: None,
+            size: None,
+            cores: None,
+            segregation_class: segregation_class.map(str::to_string),
+            from_location: None,
+            from_equipment: None,
+            to_location: None,
+            to_equipment: None,
+            length: None,
+            spare_percentage: None,
+            calculated_length: None,
+            route: Some("TR-1".to_string()),
+            manufacturer: None,
+            part_number: None,
+            outer_diameter: None,
+            voltage_drop_percentage: None,
+            segregation_warning: false,
+            tray_id: None,
+            conduit_id: None,
+            parent_cable_id: None,
+            notes: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Route segments placing each cable in the same tray
+    fn in_tray(cables: &[Cable], tray_id: i64, tag: &str) -> Vec<CableRouteSegment> {
+        cables.iter()
+            .map(|cable| CableRouteSegment {
+                id: None,
+                cable_id: cable.id.unwrap(),
+                sequence: 1,
+                conduit_id: None,
+                tray_id: Some(tray_id),
+                raceway_tag: tag.to_string(),
+            })
+            .collect()
+    }
+
+    fn segregation_messages(summary: &ValidationSummary) -> Vec<&str> {
+        summary.results.iter()
+            .filter(|r| matches!(r.validation_type, ValidationType::SegregationViolation))
+            .map(|r| r.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_default_rules_match_nec_segregation() {
+        let cables = [
+            cable(1, "C-001", Some("Control Power 24VDC"), 24.0),
+            cable(2, "C-002", Some("Power 480VAC"), 480.0),
+            cable(3, "C-003", Some("Power 120VAC"), 4160.0),
+        ];
+
+        let summary = CableValidator::new().validate_all_cables(&cables, &in_tray(&cables, 1, "TR-1"));
+        let messages = segregation_messages(&summary);
+        assert!(messages.contains(&"Incompatible 'Control Power 24VDC' and 'Power 480VAC' cables in route 'TR-1'"));
+        assert!(messages.contains(&"Incompatible 'Low Voltage (<50V)' and 'Extra High Voltage (>1000V)' cables in route 'TR-1' (NEC 300.3)"));
+        // Only the cables in the conflicting bands are flagged for voltage separation
+        assert_eq!(summary.results.iter().filter(|r| r.message.contains("NEC 300.3")).count(), 2);
+
+        // A low voltage cable beside both higher bands gets one result naming both
+        let cables = [
+            cable(1, "C-001", None, 24.0),
+            cable(2, "C-002", None, 800.0),
+            cable(3, "C-003", None, 4160.0),
+        ];
+        let summary = CableValidator::new().validate_all_cables(&cables, &in_tray(&cables, 1, "TR-1"));
+        let voltage: Vec<&ValidationResult> = summary.results.iter().filter(|r| r.message.contains("NEC 300.3")).collect();
+        assert_eq!(voltage.len(), 3);
+        let low = voltage.iter().find(|r| r.cable_tag == "C-001").unwrap();
+        assert_eq!(low.message, "Incompatible 'Low Voltage (<50V)' and 'High Voltage (600-1000V)' cables in route 'TR-1' (NEC 300.3); also 'Low Voltage (<50V)' and 'Extra High Voltage (>1000V)' (NEC 300.3)");
+        assert!(ValidationRules::default().check().is_ok());
+    }
+
+    #[test]
+    fn test_intrinsic_safety_follows_rule_set_classes() {
+        let is_errors = |validator: &CableValidator, cables: &[Cable]| -> Vec<String> {
+            validator.validate_all_cables(cables, &in_tray(cables, 1, "TR-1")).results.into_iter()
+                .filter(|r| r.message.contains("NEC 504.30"))
+                .map(|r| r.cable_tag)
+                .collect()
+        };
+
+        // Class names merely containing "IS" are not intrinsically safe
+        let lookalikes = [
+            cable(1, "C-001", Some("DISTRIBUTION"), 24.0),
+            cable(2, "C-002", Some("Non-IS Signal"), 24.0),
+        ];
+        assert!(is_errors(&CableValidator::new(), &lookalikes).is_empty());
+
+        let mixed = [
+            cable(1, "C-001", Some("IS Signal"), 24.0),
+            cable(2, "C-002", Some("Non-IS Signal"), 24.0),
+        ];
+        assert_eq!(is_errors(&CableValidator::new(), &mixed), ["C-001", "C-002"]);
+
+        // A project class can opt in, and "IS Signal" out
+        let mut rules = ValidationRules::default();
+        for class in &mut rules.segregation_classes {
+            class.intrinsically_safe = class.name == "Non-IS Signal";
+        }
+        let custom = CableValidator::with_rules(rules);
+        assert_eq!(is_errors(&custom, &lookalikes), ["C-002", "C-001"]);
+        let opted_out = [
+            cable(1, "C-001", Some("IS Signal"), 24.0),
+            cable(2, "C-002", Some("DISTRIBUTION"), 24.0),
+        ];
+        assert!(is_errors(&custom, &opted_out).is_empty());
+    }
+
+    #[test]
+    fn test_project_rule_set_replaces_defaults() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let rules = ValidationRules {
+            segregation_classes: ["Level 1", "Level 2"].iter()
+                .map(|name| SegregationClassRule { name: name.to_string(), description: None, intrinsically_safe: false })
+                .collect(),
+            class_incompatibilities: vec![IncompatibilityRule {
+                first: "Level 1".to_string(),
+                second: "Level 2".to_string(),
+                severity: ValidationSeverity::Warning,
+                override_allowed: true,
+                reference: Some("Client spec".to_string()),
+            }],
+            voltage_bands: vec![VoltageBand { name: "All".to_string(), max_voltage: None }],
+            voltage_band_incompatibilities: Vec::new(),
+        };
+        rules.check().unwrap();
+
+        // Export and import through JSON as the commands do
+        let json = serde_json::to_string(&RuleSetExport { name: "Client".to_string(), description: None, rules }).unwrap();
+        let import: RuleSetExport = serde_json::from_str(&json).unwrap();
+        let rule_set = db.insert_validation_rule_set(project_id, &NewValidationRuleSet {
+            name: import.name,
+            description: import.description,
+            is_active: false,
+            rules: import.rules,
+        }).unwrap();
+        assert!(db.get_active_validation_rule_set(project_id).unwrap().is_none());
+
+        let cables = [
+            cable(1, "C-001", Some("Level 1"), 24.0),
+            cable(2, "C-002", Some("Level 2"), 4160.0),
+            cable(3, "C-003", Some("IS Signal"), 24.0),
+        ];
+        let route = in_tray(&cables, 1, "TR-1");
+        let defaults = CableValidator::for_project(&db, project_id).unwrap().validate_all_cables(&cables, &route);
+        assert_eq!(segregation_messages(&defaults).iter().filter(|m| m.contains("Level")).count(), 0);
+
+        db.set_active_validation_rule_set(project_id, rule_set.id).unwrap();
+        let summary = CableValidator::for_project(&db, project_id).unwrap().validate_all_cables(&cables, &route);
+        let conflicts: Vec<_> = summary.results.iter()
+            .filter(|r| r.message.starts_with("Incompatible"))
+            .collect();
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts.iter().all(|r| r.severity == ValidationSeverity::Warning));
+        assert!(conflicts[0].message.ends_with("(Client spec)"));
+        // 'IS Signal' is not part of this rule set
+        assert!(summary.results.iter().any(|r| r.cable_tag == "C-003" && r.message.contains("not defined")));
+
+        // A rule naming an undefined class is rejected
+        let mut invalid = ValidationRules::default();
+        invalid.class_incompatibilities[0].second = "Typo".to_string();
+        assert!(invalid.check().is_err());
+    }
+
+    #[test]
+    fn test_overrides_suppress_until_cable_changes() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let new_cable: NewCable = serde_json::from_value(serde_json::json!({"tag": "C-001", "spare_percentage": 150.0})).unwrap();
+        let cable_id = db.insert_cable(project_id, &new_cable).unwrap().id.unwrap();
+        let validator = CableValidator::new();
+
+        let summary = validator.validate_project(&db, project_id).unwrap();
+        assert_eq!((summary.warning_count, summary.suppressed_count), (1, 0));
+
+        let validation_override = db.insert_validation_override(project_id, &NewValidationOverride {
+            cable_id,
+            validation_type: ValidationType::InvalidValue,
+            field: Some("spare_percentage".to_string()),
+            justification: "Spare cores counted as a multiple".to_string(),
+            user_name: Some("tester".to_string()),
+        }).unwrap();
+        let summary = validator.validate_project(&db, project_id).unwrap();
+        assert_eq!((summary.warning_count, summary.suppressed_count), (0, 1));
+        assert!(summary.results[0].suppressed);
+
+        // Unrelated edits keep the override; editing the checked field expires it
+        let notes: UpdateCable = serde_json::from_value(serde_json::json!({"notes": "Checked"})).unwrap();
+        db.update_cable(cable_id, &notes).unwrap();
+        assert_eq!(validator.validate_project(&db, project_id).unwrap().suppressed_count, 1);
+        let spare: UpdateCable = serde_json::from_value(serde_json::json!({"spare_percentage": 120.0})).unwrap();
+        db.update_cable(cable_id, &spare).unwrap();
+        let summary = validator.validate_project(&db, project_id).unwrap();
+        assert_eq!((summary.warning_count, summary.suppressed_count), (1, 0));
+
+        // Revoked overrides stay on record but no longer apply
+        let revoked = db.revoke_validation_override(validation_override.id.unwrap()).unwrap();
+        assert!(revoked.revoked_at.is_some());
+        assert_eq!(db.get_validation_overrides(project_id).unwrap().len(), 1);
+
+        // Deleting the cable keeps its overrides on record under the tag it had
+        db.delete_cable(cable_id).unwrap();
+        let overrides = db.get_validation_overrides(project_id).unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert_eq!((overrides[0].cable_id, overrides[0].cable_tag.as_str()), (None, "C-001"));
+    }
+
+    #[test]
+    fn test_project_validation_covers_every_entity() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+
+        let cable: NewCable = serde_json::from_value(serde_json::json!({"tag": "C-001"})).unwrap();
+        let cable_id = db.insert_cable(project_id, &cable).unwrap().id.unwrap();
+        let other_project_id = db.create_default_project().unwrap().id.unwrap();
+        let other_tray = db.insert_tray(&serde_json::from_value(serde_json::json!({"tag": "T-9"})).unwrap(), other_project_id, revision_id).unwrap();
+        db.update_cable(cable_id, &serde_json::from_value(serde_json::json!({"tray_id": other_tray.id})).unwrap()).unwrap();
+        let mut conduit = db.insert_conduit(project_id, &serde_json::from_value(serde_json::json!({"tag": "CD-1"})).unwrap()).unwrap();
+        db.insert_tray(&serde_json::from_value(serde_json::json!({"tag": "T-1", "width": 600.0})).unwrap(), project_id, revision_id).unwrap();
+        for (tag, channel) in [("AI-1", 3), ("AI-2", 3), ("AI-3", 4)] {
+            db.insert_io_point(project_id, &serde_json::from_value(serde_json::json!({
+                "tag": tag, "plc_name": "PLC-1", "rack": 1, "slot": 2, "channel": channel
+            })).unwrap()).unwrap();
+        }
+        db.insert_load(project_id, &serde_json::from_value(serde_json::json!({"tag": "P-101"})).unwrap()).unwrap();
+
+        let summary = CableValidator::new().validate_project(&db, project_id).unwrap();
+        let flagged = |entity_type: EntityType, validation_type: ValidationType| -> Vec<&str> {
+            let mut tags: Vec<&str> = summary.results.iter()
+                .filter(|r| r.entity_type == entity_type && r.validation_type == validation_type)
+                .map(|r| r.entity_tag.as_str())
+                .collect();
+            tags.sort();
+            tags
+        };
+
+        assert_eq!(flagged(EntityType::IOPoint, ValidationType::IoAddressConflict), ["AI-1", "AI-2"]);
+        assert_eq!(flagged(EntityType::Load, ValidationType::RequiredField), ["P-101"]);
+        assert_eq!(flagged(EntityType::Tray, ValidationType::RequiredField), ["T-1"]);
+        assert_eq!(flagged(EntityType::Cable, ValidationType::MissingReference), ["C-001"]);
+        let missing = summary.results.iter()
+            .find(|r| r.validation_type == ValidationType::MissingReference)
+            .unwrap();
+        assert_eq!(missing.field.as_deref(), Some("tray_id"));
+        assert_eq!(missing.cable_id, missing.entity_id);
+        // Overrides are recorded against a cable, so results with none can't be overridden
+        let unfed = summary.results.iter().find(|r| r.entity_type == EntityType::Load).unwrap();
+        assert_eq!((unfed.cable_tag.as_str(), unfed.override_allowed), ("", false));
+
+        conduit.fill_percentage = conduit.max_fill_percentage + 5.0;
+        let overfilled = CableValidator::new().validate_raceways(&[conduit], &[]);
+        assert_eq!(overfilled.len(), 1);
+        assert_eq!((overfilled[0].entity_type, &overfilled[0].validation_type), (EntityType::Conduit, &ValidationType::Capacity));
+    }
+
+    #[test]
+    fn test_undersized_single_conductors_in_tray_are_flagged() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+        let tray = db.insert_tray(&serde_json::from_value(serde_json::json!({
+            "tag": "TR-1", "type": "LADDER", "width": 300.0, "height": 100.0
+        })).unwrap(), project_id, revision_id).unwrap();
+        for (tag, size, diameter) in [("L-1", "1/0 AWG", 15.0), ("L-2", "6 AWG", 8.0)] {
+            db.insert_cable(project_id, &serde_json::from_value(serde_json::json!({
+                "tag": tag, "function": "Power", "size": size, "cores": 1,
+                "outer_diameter": diameter, "route": "TR-1"
+            })).unwrap()).unwrap();
+        }
+
+        // The stored fill is within its maximum, so only the conductor check reports the tray
+        let stored = db.get_tray_by_id(tray.id.unwrap()).unwrap();
+        assert!(stored.fill_percentage <= stored.max_fill_percentage);
+        let summary = CableValidator::new().validate_project(&db, project_id).unwrap();
+        let flagged: Vec<&ValidationResult> = summary.results.iter()
+            .filter(|r| r.validation_type == ValidationType::NecCompliance && r.entity_type == EntityType::Tray)
+            .collect();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!((flagged[0].entity_tag.as_str(), flagged[0].entity_id), ("TR-1", tray.id));
+        assert!(flagged[0].message.contains("L-2") && !flagged[0].message.contains("L-1"));
+    }
+
+    #[test]
+    fn test_conduit_jam_risk_and_unknown_area_are_flagged() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let conduit = |tag: &str, size: Option<&str>| db.insert_conduit(project_id, &serde_json::from_value(serde_json::json!({
+            "tag": tag, "type": size.map(|_| "EMT"), "size": size
+        })).unwrap()).unwrap();
+        conduit("CD-1", Some("1-1/4"));
+        conduit("CD-2", None);
+        // Three 12 mm cables in 1-1/4" EMT: 1.05 × 35.05 / 12 = 3.07 can jam
+        for (tag, route) in [("C-001", "CD-1"), ("C-002", "CD-1"), ("C-003", "CD-1"), ("C-004", "CD-2")] {
+            db.insert_cable(project_id, &serde_json::from_value(serde_json::json!({
+                "tag": tag, "outer_diameter": 12.0, "route": route
+            })).unwrap()).unwrap();
+        }
+
+        let conduit_results = |results: &[ValidationResult]| -> Vec<(String, String, ValidationType)> {
+            let mut flagged: Vec<_> = results.iter()
+                .filter(|r| r.entity_type == EntityType::Conduit)
+                .map(|r| (r.entity_tag.clone(), r.cable_tag.clone(), r.validation_type.clone()))
+                .collect();
+            flagged.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+            flagged
+        };
+        let expected = vec![
+            ("CD-1".to_string(), "C-001".to_string(), ValidationType::Capacity),
+            ("CD-1".to_string(), "C-002".to_string(), ValidationType::Capacity),
+            ("CD-1".to_string(), "C-003".to_string(), ValidationType::Capacity),
+            ("CD-2".to_string(), "C-004".to_string(), ValidationType::RequiredField),
+        ];
+
+        let summary = CableValidator::new().validate_project(&db, project_id).unwrap();
+        assert_eq!(conduit_results(&summary.results), expected);
+        assert!(summary.results.iter().any(|r| r.message.contains("jam ratio 3.07")));
+
+        // The incremental validator reports the same, and drops the jam risk once a cable leaves
+        let mut incremental = incremental::IncrementalValidator::load(&db, project_id).unwrap();
+        let cables = db.get_cables(project_id).unwrap();
+        let results: Vec<ValidationResult> = cables.iter()
+            .flat_map(|c| incremental.cable_results(c.id.unwrap()))
+            .collect();
+        assert_eq!(conduit_results(&results), expected);
+
+        let updates = incremental.upsert_cable(cables[2].clone(), &[]);
+        let results: Vec<ValidationResult> = updates.into_iter().flat_map(|u| u.results).collect();
+        assert!(conduit_results(&results).is_empty());
+    }
+
+    #[test]
+    fn test_incremental_validation_matches_full_run() {
+        fn messages_by_cable(results: &[ValidationResult]) -> Vec<(Option<i64>, String)> {
+            let mut messages: Vec<_> = results.iter()
+                .map(|r| (r.cable_id, r.message.clone()))
+                .collect();
+            messages.sort();
+            messages
+        }
+
+        let validator = CableValidator::new();
+        let mut cables = vec![
+            cable(1, "C-001", Some("IS Signal"), 24.0),
+            cable(2, "C-002", Some("IS Signal"), 24.0),
+            cable(3, "C-003", Some("Power 480VAC"), 480.0),
+        ];
+        cables[2].route = Some("TR-2".to_string());
+        let mut route = in_tray(&cables[..2], 1, "TR-1");
+        route.extend(in_tray(&cables[2..], 2, "TR-2"));
+        let mut incremental = incremental::IncrementalValidator::new(CableValidator::new(), Vec::new(), cables.clone(), route.clone(), Vec::new());
+
+        // Moving the power cable into TR-1 re-evaluates both IS cables already routed there
+        cables[2].route = Some("TR-1".to_string());
+        route[2] = in_tray(&cables[2..], 1, "TR-1").remove(0);
+        let updates = incremental.upsert_cable(cables[2].clone(), &route[2..]);
+        let updated: Vec<i64> = updates.iter().map(|u| u.cable_id).collect();
+        assert_eq!(updated, vec![1, 2, 3]);
+        assert!(updates[0].results.iter().any(|r| r.validation_type == ValidationType::SegregationViolation));
+
+        // A duplicate tag is picked up on the other cable carrying it
+        cables[1].tag = "c-001".to_string();
+        let updates = incremental.upsert_cable(cables[1].clone(), &route[1..2]);
+        assert!(updates.iter().any(|u| u.cable_id == 1
+            && u.results.iter().any(|r| r.validation_type == ValidationType::DuplicateTag)));
+
+        let full = validator.validate_all_cables(&cables, &route);
+        let incremental_results: Vec<ValidationResult> = cables.iter()
+            .flat_map(|c| incremental.cable_results(c.id.unwrap()))
+            .collect();
+        assert_eq!(messages_by_cable(&incremental_results), messages_by_cable(&full.results));
+
+        // Deleting the power cable clears its results and the violations it caused
+        let updates = incremental.remove_cable(3);
+        assert_eq!(updates.last().map(|u| (u.cable_id, u.results.len())), Some((3, 0)));
+        cables.pop();
+        route.pop();
+        let full = validator.validate_all_cables(&cables, &route);
+        let incremental_results: Vec<ValidationResult> = cables.iter()
+            .flat_map(|c| incremental.cable_results(c.id.unwrap()))
+            .collect();
+        assert_eq!(messages_by_cable(&incremental_results), messages_by_cable(&full.results));
+    }
+
+    #[test]
+    fn test_segregation_follows_stored_route_segments() {
+        let db = Database::in_memory().unwrap();
+        let project_id = db.create_default_project().unwrap().id.unwrap();
+        let revision_id = db.get_current_revision_id(project_id).unwrap();
+        db.insert_tray(&serde_json::from_value(serde_json::json!({
+            "tag": "TR-1", "width": 600.0, "height": 100.0
+        })).unwrap(), project_id, revision_id).unwrap();
+        // "tr-1" is the same tray as "TR-1"; junction box "JB-4" is not a raceway
+        for (tag, function, route) in [
+            ("C-001", "Power", "tr-1, JB-4"),
+            ("C-002", "Signal", "TR-1"),
+            ("C-003", "Power", "JB-4"),
+            ("C-004", "Signal", "jb-4"),
+        ] {
+            db.insert_cable(project_id, &serde_json::from_value(serde_json::json!({
+                "tag": tag, "function": function, "route": route
+            })).unwrap()).unwrap();
+        }
+
+        let summary = CableValidator::new().validate_project(&db, project_id).unwrap();
+        let mut flagged: Vec<&str> = summary.results.iter()
+            .filter(|r| r.validation_type == ValidationType::SegregationViolation)
+            .map(|r| r.entity_tag.as_str())
+            .collect();
+        flagged.sort();
+        assert_eq!(flagged, ["C-001", "C-002"]);
+        assert!(segregation_messages(&summary).iter().all(|m| m.contains("'TR-1'")));
+
+        let incremental = incremental::IncrementalValidator::load(&db, project_id).unwrap();
+        let mut flagged: Vec<String> = db.get_cables(project_id).unwrap().iter()
+            .flat_map(|c| incremental.cable_results(c.id.unwrap()))
+            .filter(|r| r.validation_type == ValidationType::SegregationViolation)
+            .map(|r| r.entity_tag)
+            .collect();
+        flagged.sort();
+        assert_eq!(flagged, ["C-001", "C-002"]);
+    }
+}
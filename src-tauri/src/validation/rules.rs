@@ -0,0 +1,162 @@
+/**
+ * Validation Rule Sets
+ * Project-editable segregation classes, incompatibility matrices and voltage bands
+ */
+
+use super::ValidationSeverity;
+use serde::{Deserialize, Serialize};
+
+/// A segregation class cables can be assigned to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegregationClassRule {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub intrinsically_safe: bool, // kept apart from every cable outside an IS class (NEC 504.30)
+}
+
+/// Two segregation classes, or two voltage bands, that must not share a route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncompatibilityRule {
+    pub first: String,
+    pub second: String,
+    pub severity: ValidationSeverity,
+    pub override_allowed: bool,
+    pub reference: Option<String>, // e.g. "NEC 300.3", "DEP 33.64.10.10"
+}
+
+impl IncompatibilityRule {
+    fn new(first: &str, second: &str, reference: Option<&str>) -> Self {
+        Self {
+            first: first.to_string(),
+            second: second.to_string(),
+            severity: ValidationSeverity::Error,
+            override_allowed: true,
+            reference: reference.map(str::to_string),
+        }
+    }
+}
+
+/// A named voltage range; cables belong to the first band whose maximum covers their voltage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoltageBand {
+    pub name: String,
+    pub max_voltage: Option<f64>, // V, inclusive; None = no upper limit
+}
+
+/// The segregation philosophy `CableValidator` evaluates, exported and imported as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationRules {
+    pub segregation_classes: Vec<SegregationClassRule>,
+    pub class_incompatibilities: Vec<IncompatibilityRule>,
+    pub voltage_bands: Vec<VoltageBand>,
+    pub voltage_band_incompatibilities: Vec<IncompatibilityRule>,
+}
+
+impl Default for ValidationRules {
+    /// The NEC-based rules CableForge has always applied
+    fn default() -> Self {
+        let classes = [
+            ("IS Signal", "Intrinsically safe instrument circuits"),
+            ("Non-IS Signal", "Non-intrinsically safe instrument circuits"),
+            ("Control Power 24VDC", "24 VDC control circuits"),
+            ("Power 120VAC", "120 VAC power"),
+            ("Power 240VAC", "240 VAC power"),
+            ("Power 480VAC", "480 VAC power"),
+            ("Power 600VAC", "600 VAC power"),
+        ];
+        let class_pairs = [
+            ("IS Signal", "Non-IS Signal"),
+            ("IS Signal", "Power 120VAC"),
+            ("IS Signal", "Power 240VAC"),
+            ("IS Signal", "Power 480VAC"),
+            ("IS Signal", "Power 600VAC"),
+            ("Control Power 24VDC", "Power 480VAC"),
+            ("Control Power 24VDC", "Power 600VAC"),
+        ];
+        let bands = [
+            ("Low Voltage (<50V)", Some(50.0)),
+            ("Medium Voltage (50-600V)", Some(600.0)),
+            ("High Voltage (600-1000V)", Some(1000.0)),
+            ("Extra High Voltage (>1000V)", None),
+        ];
+
+        Self {
+            segregation_classes: classes.iter()
+                .map(|(name, description)| SegregationClassRule {
+                    name: name.to_string(),
+                    description: Some(description.to_string()),
+                    intrinsically_safe: *name == "IS Signal",
+                })
+                .collect(),
+            class_incompatibilities: class_pairs.iter()
+                .map(|(first, second)| IncompatibilityRule::new(first, second, None))
+                .collect(),
+            voltage_bands: bands.iter()
+                .map(|(name, max_voltage)| VoltageBand { name: name.to_string(), max_voltage: *max_voltage })
+                .collect(),
+            voltage_band_incompatibilities: vec![
+                IncompatibilityRule::new("Low Voltage (<50V)", "High Voltage (600-1000V)", Some("NEC 300.3")),
+                IncompatibilityRule::new("Low Voltage (<50V)", "Extra High Voltage (>1000V)", Some("NEC 300.3")),
+            ],
+        }
+    }
+}
+
+impl ValidationRules {
+    /// Name of the voltage band a cable voltage falls in
+    pub fn voltage_band(&self, voltage: f64) -> Option<&str> {
+        self.voltage_bands.iter()
+            .find(|band| !matches!(band.max_voltage, Some(max) if voltage > max))
+            .map(|band| band.name.as_str())
+    }
+
+    pub fn has_segregation_class(&self, name: &str) -> bool {
+        self.segregation_classes.iter().any(|class| class.name == name)
+    }
+
+    pub fn is_intrinsically_safe(&self, name: &str) -> bool {
+        self.segregation_classes.iter().any(|class| class.name == name && class.intrinsically_safe)
+    }
+
+    /// Check that every rule refers to a defined class or band and that band maximums ascend, so a
+    /// typo in an imported file cannot silently disable a rule
+    pub fn check(&self) -> Result<(), String> {
+        for rule in &self.class_incompatibilities {
+            for class in [&rule.first, &rule.second] {
+                if !self.has_segregation_class(class) {
+                    return Err(format!("Incompatibility refers to undefined segregation class '{}'", class));
+                }
+            }
+        }
+        for rule in &self.voltage_band_incompatibilities {
+            for band in [&rule.first, &rule.second] {
+                if !self.voltage_bands.iter().any(|b| &b.name == band) {
+                    return Err(format!("Incompatibility refers to undefined voltage band '{}'", band));
+                }
+            }
+        }
+        let mut previous: Option<f64> = None;
+        for (index, band) in self.voltage_bands.iter().enumerate() {
+            match (previous, band.max_voltage) {
+                (Some(prev), Some(max)) if max <= prev => {
+                    return Err(format!("Voltage band '{}' must have a higher maximum than the band before it", band.name));
+                }
+                (_, None) if index + 1 < self.voltage_bands.len() => {
+                    return Err(format!("Only the last voltage band can be unbounded, not '{}'", band.name));
+                }
+                _ => {}
+            }
+            previous = band.max_voltage;
+        }
+        Ok(())
+    }
+}
+
+/// File format for sharing a rule set between projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSetExport {
+    pub name: String,
+    pub description: Option<String>,
+    pub rules: ValidationRules,
+}
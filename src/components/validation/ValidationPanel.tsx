@@ -118,7 +118,7 @@ const ValidationItem: React.FC<{ result: ValidationResult }> = ({ result }) => {
       <span className="mt-0.5">{severityIcon}</span>
       <div className="flex-1">
         <div className={`font-medium ${severityColor}`}>
-          {result.cableTag}: {result.message}
+          {result.entityTag}: {result.message}
         </div>
         {result.field && (
           <div className="text-gray-600 text-xs mt-1">
@@ -5,6 +5,9 @@
 
 import { invoke } from '@tauri-apps/api/core';
 
+export type SystemType = 'Dc' | 'SinglePhaseTwoWire' | 'SinglePhaseThreeWire' | 'ThreePhase';
+export type RacewayType = 'Pvc' | 'Aluminum' | 'Steel';
+
 export interface VoltageDropCalculation {
   voltage: number;
   current: number;
@@ -12,16 +15,127 @@ export interface VoltageDropCalculation {
   conductor_size: string;
   material: 'Copper' | 'Aluminum';
   power_factor?: number;
+  system_type: SystemType;
+  raceway_type: RacewayType;
+  parallel_sets: number;
+  conductor_temperature?: number;
+}
+
+export interface VoltageDropOptions {
+  systemType?: SystemType;
+  racewayType?: RacewayType;
+  parallelSets?: number;
+  conductorTemperature?: number;
 }
 
 export interface VoltageDropResult {
   voltage_drop_volts: number;
   voltage_drop_percentage: number;
   line_to_line_voltage_drop: number;
+  line_to_neutral_voltage_drop?: number;
+  effective_impedance: number;
   severity: 'Good' | 'Warning' | 'Error';
   compliance_status: string;
 }
 
+export interface CableVoltageDropCheck {
+  cable_id?: number;
+  cable_tag: string;
+  load_tag?: string;
+  calculation?: VoltageDropCalculation;
+  result?: VoltageDropResult;
+  skipped_reason?: string;
+}
+
+export interface VoltageDropReport {
+  updated: number;
+  skipped: number;
+  checks: CableVoltageDropCheck[];
+}
+
+export interface FeederPathCheck {
+  load_id?: number;
+  load_tag: string;
+  cable_tags: string[];
+  segments: CableVoltageDropCheck[];
+  cumulative_percentage?: number;
+  limit_percentage: number;
+  exceeds_limit: boolean;
+  skipped_reason?: string;
+}
+
+export interface GroundingConductorSizing {
+  device_rating: number;
+  material: 'Copper' | 'Aluminum';
+  table_size: string;
+  phase_upsize_ratio: number;
+  required_size: string;
+  required_area_cmil: number;
+}
+
+export interface MotorCircuitSizing {
+  horsepower: number;
+  voltage: number;
+  phases: number;
+  full_load_current: number;
+  minimum_ampacity: number;
+  material: 'Copper' | 'Aluminum';
+  recommended_size: string;
+}
+
+export interface LoadGroupSummary {
+  name: string;
+  load_count: number;
+  connected_kw: number;
+  demand_kw: number;
+  continuous_kw: number;
+  non_continuous_kw: number;
+  diversified_kw: number;
+  kva: number;
+  design_kva: number;
+}
+
+export interface LoadAnalysisReport {
+  options: {
+    power_factor: number;
+    diversity_factor: number;
+    service_voltage?: number;
+    service_phases?: number;
+  };
+  totals: LoadGroupSummary;
+  by_voltage: LoadGroupSummary[];
+  by_load_type: LoadGroupSummary[];
+  by_bus: LoadGroupSummary[];
+  loads_without_power: string[];
+  service_voltage?: number;
+  service_phases: number;
+  service_current?: number;
+  recommended_transformer_kva?: number;
+  recommended_main_breaker?: number;
+}
+
+export interface PowerCableOptions {
+  tagPrefix?: string;
+  material?: 'Copper' | 'Aluminum';
+  maxVoltageDropPercent?: number;
+  ambientTemperature?: number;
+  defaultLength?: number;
+}
+
+// Proposed cables are returned as the backend's NewCable shape so they can be accepted unchanged
+export interface PowerCableProposal {
+  load_id?: number;
+  load_tag: string;
+  cable?: Record<string, unknown> & { tag: string; size?: string };
+  library_item_id?: number;
+  design_current?: number;
+  ampacity_size?: string;
+  voltage_drop_size?: string;
+  derated_ampacity?: number;
+  notes: string[];
+  skipped_reason?: string;
+}
+
 export class CalculationService {
   /**
    * Calculate voltage drop for a cable
@@ -32,7 +146,8 @@ export class CalculationService {
     distance: number,
     conductorSize: string,
     material: 'Copper' | 'Aluminum' = 'Copper',
-    powerFactor: number = 0.85
+    powerFactor: number = 0.85,
+    options: VoltageDropOptions = {}
   ): Promise<VoltageDropResult> {
     try {
       const result = await invoke<VoltageDropResult>('calculate_voltage_drop', {
@@ -41,7 +156,8 @@ export class CalculationService {
         distance,
         conductorSize,
         material: material.toLowerCase(),
-        powerFactor
+        powerFactor,
+        ...options
       });
       return result;
     } catch (error) {
@@ -59,7 +175,8 @@ export class CalculationService {
     distance: number,
     material: 'Copper' | 'Aluminum' = 'Copper',
     maxVoltageDropPercent: number = 3.0,
-    powerFactor: number = 0.85
+    powerFactor: number = 0.85,
+    systemType: SystemType = 'SinglePhaseTwoWire'
   ): Promise<string> {
     try {
       const result = await invoke<string>('calculate_minimum_conductor_size', {
@@ -68,7 +185,8 @@ export class CalculationService {
         distance,
         material: material.toLowerCase(),
         maxVoltageDropPercent,
-        powerFactor
+        powerFactor,
+        systemType
       });
       return result;
     } catch (error) {
@@ -100,6 +218,100 @@ export class CalculationService {
     }
   }
 
+  /**
+   * Size the equipment grounding conductor for an overcurrent device (NEC 250.122)
+   */
+  async sizeGroundingConductor(
+    deviceRating: number,
+    material: 'Copper' | 'Aluminum' = 'Copper',
+    phaseSize?: string,
+    minimumPhaseSize?: string
+  ): Promise<GroundingConductorSizing> {
+    try {
+      return await invoke<GroundingConductorSizing>('size_grounding_conductor', {
+        deviceRating,
+        material: material.toLowerCase(),
+        phaseSize,
+        minimumPhaseSize
+      });
+    } catch (error) {
+      console.error('Failed to size grounding conductor:', error);
+      throw new Error('Grounding conductor sizing failed: ' + error);
+    }
+  }
+
+  /**
+   * Size motor branch-circuit conductors from NEC table full-load current (430.22)
+   */
+  async sizeMotorCircuit(
+    horsepower: number,
+    voltage: number,
+    phases: 1 | 3 = 3,
+    material: 'Copper' | 'Aluminum' = 'Copper'
+  ): Promise<MotorCircuitSizing> {
+    try {
+      return await invoke<MotorCircuitSizing>('size_motor_circuit', {
+        horsepower,
+        voltage,
+        phases,
+        material: material.toLowerCase()
+      });
+    } catch (error) {
+      console.error('Failed to size motor circuit:', error);
+      throw new Error('Motor circuit sizing failed: ' + error);
+    }
+  }
+
+  /**
+   * Demand load breakdown with recommended transformer and main breaker
+   */
+  async getLoadAnalysis(
+    powerFactor?: number,
+    diversityFactor?: number,
+    serviceVoltage?: number,
+    servicePhases?: number
+  ): Promise<LoadAnalysisReport> {
+    try {
+      return await invoke<LoadAnalysisReport>('get_load_analysis', {
+        powerFactor,
+        diversityFactor,
+        serviceVoltage,
+        servicePhases
+      });
+    } catch (error) {
+      console.error('Failed to analyze loads:', error);
+      throw new Error('Load analysis failed: ' + error);
+    }
+  }
+
+  /**
+   * Propose power cables for the selected loads without storing anything
+   */
+  async previewPowerCables(loadIds: number[], options: PowerCableOptions = {}): Promise<PowerCableProposal[]> {
+    try {
+      return await invoke<PowerCableProposal[]>('preview_power_cables', {
+        loadIds,
+        ...options,
+        material: options.material?.toLowerCase()
+      });
+    } catch (error) {
+      console.error('Failed to preview power cables:', error);
+      throw new Error('Power cable preview failed: ' + error);
+    }
+  }
+
+  /**
+   * Create the cables from an accepted preview and link them to their loads
+   */
+  async acceptPowerCables(proposals: PowerCableProposal[]): Promise<void> {
+    try {
+      await invoke('accept_power_cables', { proposals });
+    } catch (error) {
+      console.error('Failed to create power cables:', error);
+      throw new Error('Power cable generation failed: ' + error);
+    }
+  }
+
   /**
    * Update voltage drop for a specific cable
    */
@@ -115,6 +327,30 @@ export class CalculationService {
     }
   }
 
+  /**
+   * Recalculate and store voltage drop for every cable in the project
+   */
+  async recalculateProjectVoltageDrops(): Promise<VoltageDropReport> {
+    try {
+      return await invoke<VoltageDropReport>('recalculate_voltage_drops');
+    } catch (error) {
+      console.error('Failed to recalculate voltage drops:', error);
+      throw new Error('Voltage drop recalculation failed: ' + error);
+    }
+  }
+
+  /**
+   * Check cumulative voltage drop from the source bus to every load
+   */
+  async checkFeederVoltageDrop(limitPercentage?: number): Promise<FeederPathCheck[]> {
+    try {
+      return await invoke<FeederPathCheck[]>('check_feeder_voltage_drop', { limitPercentage });
+    } catch (error) {
+      console.error('Failed to check feeder voltage drop:', error);
+      throw new Error('Feeder voltage drop check failed: ' + error);
+    }
+  }
+
   /**
    * Batch calculate voltage drops for multiple cables
    */
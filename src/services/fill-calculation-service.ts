@@ -17,6 +17,50 @@ export interface FillCalculationResult {
   message: string;
 }
 
+// NEC Chapter 9 results keep the backend's field names; areas in mm²
+export interface ConduitTradeSize {
+  kind: string;
+  trade_size: string;
+  metric_designator: number;
+  internal_diameter: number;
+  internal_area: number;
+}
+
+export interface ConduitFillCheck {
+  internal_area?: number;
+  area_source: string;
+  cable_count: number;
+  cable_area: number;
+  fill_percentage: number;
+  max_fill_percentage: number;
+  rule: string;
+  jam_ratio?: number;
+  jam_risk: boolean;
+  cables_without_diameter: string[];
+  passes: boolean;
+}
+
+export interface ConduitSizing {
+  trade_size: ConduitTradeSize;
+  check: ConduitFillCheck;
+}
+
+// NEC 392.22 result; `unit` is "in" for sums of diameters, "in²" for areas and "mm²" when the
+// tray type has no NEC method and the cross-sectional area is used instead
+export interface TrayFillCheck {
+  rule: string;
+  description: string;
+  used: number;
+  allowable?: number;
+  unit: string;
+  fill_percentage: number;
+  max_fill_percentage: number;
+  cables_without_diameter: string[];
+  undersized_single_conductors: string[];
+  notes: string[];
+  passes: boolean;
+}
+
 export class FillCalculationService {
   /**
    * Recalculate fill for a specific conduit
@@ -60,6 +104,54 @@ export class FillCalculationService {
     }
   }
 
+  /**
+   * Check a conduit against the NEC Chapter 9 fill limit for its cable count, with the jam ratio for three cables
+   */
+  async checkConduitFill(conduitId: number): Promise<ConduitFillCheck> {
+    try {
+      return await invoke<ConduitFillCheck>('check_conduit_fill', { conduitId });
+    } catch (error) {
+      console.error('Failed to check conduit fill:', error);
+      throw new Error('Conduit fill check failed: ' + error);
+    }
+  }
+
+  /**
+   * Check a tray with the NEC 392.22 method for its type and cables, reporting the rule applied
+   */
+  async checkTrayFill(trayId: number): Promise<TrayFillCheck> {
+    try {
+      return await invoke<TrayFillCheck>('check_tray_fill', { trayId });
+    } catch (error) {
+      console.error('Failed to check tray fill:', error);
+      throw new Error('Tray fill check failed: ' + error);
+    }
+  }
+
+  /**
+   * NEC Chapter 9 Table 4 trade sizes for a conduit type
+   */
+  async getConduitTradeSizes(conduitType: string): Promise<ConduitTradeSize[]> {
+    try {
+      return await invoke<ConduitTradeSize[]>('get_conduit_trade_sizes', { conduitType });
+    } catch (error) {
+      console.error('Failed to get conduit trade sizes:', error);
+      throw new Error('Conduit trade size lookup failed: ' + error);
+    }
+  }
+
+  /**
+   * Smallest trade size of a conduit type that holds the selected cables
+   */
+  async calculateMinimumConduitSize(conduitType: string, cableIds: number[]): Promise<ConduitSizing> {
+    try {
+      return await invoke<ConduitSizing>('calculate_minimum_conduit_size', { conduitType, cableIds });
+    } catch (error) {
+      console.error('Failed to size conduit:', error);
+      throw new Error('Conduit sizing failed: ' + error);
+    }
+  }
+
   /**
    * Batch recalculate fills for multiple conduits and trays
    */
@@ -4,7 +4,8 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import { Cable, Project, IOPoint, PLCCard, IOType, SignalType, Load, Conduit, ConduitType, Tray, TrayType, TrayMaterial } from '../types';
+import { Cable, CableLengthAllowances, CableLengthReport, CableRouteSegment, Project, IOPoint, PLCCard, IOType, SignalType, Load, ProtectiveDevice, ProtectiveDeviceType, Conduit, ConduitType, Tray, TrayType, TrayMaterial } from '../types';
+import { ValidationRules, ValidationRuleSet } from '../types/validation';
 
 export interface TauriCable {
   id?: number;
@@ -58,10 +59,12 @@ export interface TauriLoad {
   power_kw?: number;
   power_hp?: number;
   voltage?: number;
+  phases?: 1 | 3;
   current?: number;
   power_factor?: number;
   efficiency?: number;
   demand_factor?: number;
+  continuous?: boolean;
   connected_load_kw?: number;
   demand_load_kw?: number;
   cable_id?: number;
@@ -73,6 +76,71 @@ export interface TauriLoad {
   updated_at: string;
 }
 
+export interface TauriProtectiveDevice {
+  id?: number;
+  project_id: number;
+  tag: string;
+  device_type: ProtectiveDeviceType;
+  frame_rating?: number;
+  trip_rating: number;
+  trip_curve?: string;
+  load_id?: number;
+  cable_id?: number;
+  notes?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface TauriValidationRuleSet {
+  id?: number;
+  project_id: number;
+  name: string;
+  description?: string;
+  is_active: boolean;
+  rules: ValidationRules;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface TauriCableRouteSegment {
+  id?: number;
+  cable_id: number;
+  sequence: number;
+  conduit_id?: number | null;
+  tray_id?: number | null;
+  raceway_tag: string;
+}
+
+export interface TauriCableLengthAllowances {
+  drop_rise: number;
+  termination_makeup: number;
+  elevation_change: number;
+}
+
+export interface TauriCableLengthReport {
+  cable_id: number;
+  cable_tag: string;
+  raceway_length: number;
+  drop_rise_allowance: number;
+  termination_allowance: number;
+  elevation_allowance: number;
+  spare_percentage: number;
+  calculated_length?: number | null;
+  entered_length?: number | null;
+  difference?: number | null;
+  missing_lengths: string[];
+}
+
+// Auto-router proposals keep the backend's shape so they can be accepted unchanged
+export interface CableRouteProposal {
+  cable_id: number;
+  cable_tag: string;
+  segments: { conduit_id?: number | null; tray_id?: number | null }[];
+  raceway_tags: string[];
+  length?: number;
+  skipped_reason?: string;
+}
+
 export interface TauriConduit {
   id?: number;
   project_id: number;
@@ -81,6 +149,7 @@ export interface TauriConduit {
   type?: string;
   size?: string;
   internal_diameter?: number;
+  length?: number;
   fill_percentage: number;
   max_fill_percentage: number;
   from_location?: string;
@@ -448,6 +517,138 @@ export class TauriDatabaseService {
     }
   }
 
+  // Route segment operations
+  async getCableRouteSegments(cableId: number): Promise<CableRouteSegment[]> {
+    try {
+      const result: TauriCableRouteSegment[] = await invoke('get_cable_route_segments', { cableId });
+      return result.map(segment => this.mapTauriRouteSegment(segment));
+    } catch (error) {
+      throw new Error(`Failed to get cable route: ${error}`);
+    }
+  }
+
+  async setCableRouteSegments(cableId: number, segments: Pick<CableRouteSegment, 'conduitId' | 'trayId'>[]): Promise<CableRouteSegment[]> {
+    try {
+      const result: TauriCableRouteSegment[] = await invoke('set_cable_route_segments', {
+        cableId,
+        segments: segments.map(segment => ({
+          conduit_id: segment.conduitId ?? null,
+          tray_id: segment.trayId ?? null,
+        })),
+      });
+      return result.map(segment => this.mapTauriRouteSegment(segment));
+    } catch (error) {
+      throw new Error(`Failed to set cable route: ${error}`);
+    }
+  }
+
+  async insertCableRouteSegment(cableId: number, position: number, segment: Pick<CableRouteSegment, 'conduitId' | 'trayId'>): Promise<CableRouteSegment[]> {
+    try {
+      const result: TauriCableRouteSegment[] = await invoke('insert_cable_route_segment', {
+        cableId,
+        position,
+        segment: {
+          conduit_id: segment.conduitId ?? null,
+          tray_id: segment.trayId ?? null,
+        },
+      });
+      return result.map(segment => this.mapTauriRouteSegment(segment));
+    } catch (error) {
+      throw new Error(`Failed to insert route segment: ${error}`);
+    }
+  }
+
+  async reorderCableRouteSegments(cableId: number, segmentIds: number[]): Promise<CableRouteSegment[]> {
+    try {
+      const result: TauriCableRouteSegment[] = await invoke('reorder_cable_route_segments', { cableId, segmentIds });
+      return result.map(segment => this.mapTauriRouteSegment(segment));
+    } catch (error) {
+      throw new Error(`Failed to reorder route segments: ${error}`);
+    }
+  }
+
+  async previewCableRoutes(cableIds: number[]): Promise<CableRouteProposal[]> {
+    try {
+      return await invoke<CableRouteProposal[]>('preview_cable_routes', { cableIds });
+    } catch (error) {
+      throw new Error(`Failed to route cables: ${error}`);
+    }
+  }
+
+  async acceptCableRoutes(proposals: CableRouteProposal[]): Promise<Cable[]> {
+    try {
+      const result: TauriCable[] = await invoke('accept_cable_routes', { proposals });
+      return result.map(cable => this.mapTauriCableToCable(cable));
+    } catch (error) {
+      throw new Error(`Failed to store cable routes: ${error}`);
+    }
+  }
+
+  private mapTauriRouteSegment(segment: TauriCableRouteSegment): CableRouteSegment {
+    return {
+      id: segment.id,
+      cableId: segment.cable_id,
+      sequence: segment.sequence,
+      conduitId: segment.conduit_id ?? undefined,
+      trayId: segment.tray_id ?? undefined,
+      racewayTag: segment.raceway_tag,
+    };
+  }
+
+  // Cable length operations
+  async getCableLengthAllowances(): Promise<CableLengthAllowances> {
+    try {
+      const result: TauriCableLengthAllowances = await invoke('get_cable_length_allowances');
+      return this.mapTauriLengthAllowances(result);
+    } catch (error) {
+      throw new Error(`Failed to get cable length allowances: ${error}`);
+    }
+  }
+
+  async updateCableLengthAllowances(allowances: CableLengthAllowances): Promise<CableLengthAllowances> {
+    try {
+      const result: TauriCableLengthAllowances = await invoke('update_cable_length_allowances', {
+        allowances: {
+          drop_rise: allowances.dropRise,
+          termination_makeup: allowances.terminationMakeup,
+          elevation_change: allowances.elevationChange,
+        },
+      });
+      return this.mapTauriLengthAllowances(result);
+    } catch (error) {
+      throw new Error(`Failed to update cable length allowances: ${error}`);
+    }
+  }
+
+  async getCableLengthReport(): Promise<CableLengthReport[]> {
+    try {
+      const result: TauriCableLengthReport[] = await invoke('get_cable_length_report');
+      return result.map(report => ({
+        cableId: report.cable_id,
+        cableTag: report.cable_tag,
+        racewayLength: report.raceway_length,
+        dropRiseAllowance: report.drop_rise_allowance,
+        terminationAllowance: report.termination_allowance,
+        elevationAllowance: report.elevation_allowance,
+        sparePercentage: report.spare_percentage,
+        calculatedLength: report.calculated_length ?? undefined,
+        enteredLength: report.entered_length ?? undefined,
+        difference: report.difference ?? undefined,
+        missingLengths: report.missing_lengths,
+      }));
+    } catch (error) {
+      throw new Error(`Failed to get cable length report: ${error}`);
+    }
+  }
+
+  private mapTauriLengthAllowances(allowances: TauriCableLengthAllowances): CableLengthAllowances {
+    return {
+      dropRise: allowances.drop_rise,
+      terminationMakeup: allowances.termination_makeup,
+      elevationChange: allowances.elevation_change,
+    };
+  }
+
   // Additional project operations
   async newProject(name?: string): Promise<Project> {
     try {
@@ -918,10 +1119,12 @@ export class TauriDatabaseService {
         power_kw: data.powerKw || null,
         power_hp: data.powerHp || null,
         voltage: data.voltage || null,
+        phases: data.phases || null,
         current: data.current || null,
         power_factor: data.powerFactor || null,
         efficiency: data.efficiency || null,
         demand_factor: data.demandFactor || null,
+        continuous: data.continuous ?? null,
         cable_id: data.cableId || null,
         feeder_cable: data.feederCable || null,
         starter_type: data.starterType || null,
@@ -944,10 +1147,12 @@ export class TauriDatabaseService {
         power_kw: updates.powerKw || null,
         power_hp: updates.powerHp || null,
         voltage: updates.voltage || null,
+        phases: updates.phases || null,
         current: updates.current || null,
         power_factor: updates.powerFactor || null,
         efficiency: updates.efficiency || null,
         demand_factor: updates.demandFactor || null,
+        continuous: updates.continuous ?? null,
         cable_id: updates.cableId || null,
         feeder_cable: updates.feederCable || null,
         starter_type: updates.starterType || null,
@@ -980,10 +1185,12 @@ export class TauriDatabaseService {
       powerKw: tauriLoad.power_kw,
       powerHp: tauriLoad.power_hp,
       voltage: tauriLoad.voltage,
+      phases: tauriLoad.phases,
       current: tauriLoad.current,
       powerFactor: tauriLoad.power_factor,
       efficiency: tauriLoad.efficiency,
       demandFactor: tauriLoad.demand_factor,
+      continuous: tauriLoad.continuous,
       connectedLoadKw: tauriLoad.connected_load_kw,
       demandLoadKw: tauriLoad.demand_load_kw,
       cableId: tauriLoad.cable_id,
@@ -996,6 +1203,171 @@ export class TauriDatabaseService {
     };
   }
 
+  // Protective device operations
+  async getProtectiveDevices(): Promise<ProtectiveDevice[]> {
+    try {
+      const result: TauriProtectiveDevice[] = await invoke('get_protective_devices');
+      return result.map(tauriDevice => this.mapTauriProtectiveDevice(tauriDevice));
+    } catch (error) {
+      throw new Error(`Failed to get protective devices: ${error}`);
+    }
+  }
+
+  async createProtectiveDevice(data: Partial<ProtectiveDevice>): Promise<ProtectiveDevice> {
+    try {
+      const deviceData = {
+        tag: data.tag || `CB-${Date.now()}`,
+        device_type: data.deviceType || ProtectiveDeviceType.InverseTimeBreaker,
+        frame_rating: data.frameRating || null,
+        trip_rating: data.tripRating || 0,
+        trip_curve: data.tripCurve || null,
+        load_id: data.loadId || null,
+        cable_id: data.cableId || null,
+        notes: data.notes || null,
+      };
+      const result: TauriProtectiveDevice = await invoke('create_protective_device', { deviceData });
+      return this.mapTauriProtectiveDevice(result);
+    } catch (error) {
+      throw new Error(`Failed to create protective device: ${error}`);
+    }
+  }
+
+  async updateProtectiveDevice(id: number, updates: Partial<ProtectiveDevice>): Promise<ProtectiveDevice> {
+    try {
+      const updateData = {
+        tag: updates.tag || null,
+        device_type: updates.deviceType || null,
+        frame_rating: updates.frameRating || null,
+        trip_rating: updates.tripRating || null,
+        trip_curve: updates.tripCurve || null,
+        // Leaving a link out keeps it; null unlinks the device
+        load_id: 'loadId' in updates ? updates.loadId ?? null : undefined,
+        cable_id: 'cableId' in updates ? updates.cableId ?? null : undefined,
+        notes: updates.notes || null,
+      };
+      const result: TauriProtectiveDevice = await invoke('update_protective_device', { id, updates: updateData });
+      return this.mapTauriProtectiveDevice(result);
+    } catch (error) {
+      throw new Error(`Failed to update protective device: ${error}`);
+    }
+  }
+
+  async deleteProtectiveDevice(id: number): Promise<void> {
+    try {
+      await invoke('delete_protective_device', { id });
+    } catch (error) {
+      throw new Error(`Failed to delete protective device: ${error}`);
+    }
+  }
+
+  private mapTauriProtectiveDevice(tauriDevice: TauriProtectiveDevice): ProtectiveDevice {
+    return {
+      id: tauriDevice.id,
+      tag: tauriDevice.tag,
+      deviceType: tauriDevice.device_type,
+      frameRating: tauriDevice.frame_rating,
+      tripRating: tauriDevice.trip_rating,
+      tripCurve: tauriDevice.trip_curve,
+      loadId: tauriDevice.load_id,
+      cableId: tauriDevice.cable_id,
+      notes: tauriDevice.notes,
+      createdAt: new Date(tauriDevice.created_at),
+      updatedAt: new Date(tauriDevice.updated_at),
+    };
+  }
+
+  // Validation rule set operations
+  async getValidationRuleSets(): Promise<ValidationRuleSet[]> {
+    try {
+      const result: TauriValidationRuleSet[] = await invoke('get_validation_rule_sets');
+      return result.map(tauriRuleSet => this.mapTauriValidationRuleSet(tauriRuleSet));
+    } catch (error) {
+      throw new Error(`Failed to get validation rule sets: ${error}`);
+    }
+  }
+
+  async getActiveValidationRules(): Promise<ValidationRules> {
+    try {
+      return await invoke<ValidationRules>('get_active_validation_rules');
+    } catch (error) {
+      throw new Error(`Failed to get active validation rules: ${error}`);
+    }
+  }
+
+  async createValidationRuleSet(name: string, rules: ValidationRules, description?: string, isActive = false): Promise<ValidationRuleSet> {
+    try {
+      const ruleSetData = {
+        name,
+        description: description || null,
+        is_active: isActive,
+        rules,
+      };
+      const result: TauriValidationRuleSet = await invoke('create_validation_rule_set', { ruleSetData });
+      return this.mapTauriValidationRuleSet(result);
+    } catch (error) {
+      throw new Error(`Failed to create validation rule set: ${error}`);
+    }
+  }
+
+  async updateValidationRuleSet(id: number, updates: Partial<ValidationRuleSet>): Promise<ValidationRuleSet> {
+    try {
+      const updateData = {
+        name: updates.name || null,
+        description: updates.description || null,
+        rules: updates.rules || null,
+      };
+      const result: TauriValidationRuleSet = await invoke('update_validation_rule_set', { id, updates: updateData });
+      return this.mapTauriValidationRuleSet(result);
+    } catch (error) {
+      throw new Error(`Failed to update validation rule set: ${error}`);
+    }
+  }
+
+  async setActiveValidationRuleSet(id: number | null): Promise<void> {
+    try {
+      await invoke('set_active_validation_rule_set', { id });
+    } catch (error) {
+      throw new Error(`Failed to activate validation rule set: ${error}`);
+    }
+  }
+
+  async deleteValidationRuleSet(id: number): Promise<void> {
+    try {
+      await invoke('delete_validation_rule_set', { id });
+    } catch (error) {
+      throw new Error(`Failed to delete validation rule set: ${error}`);
+    }
+  }
+
+  async exportValidationRuleSet(id: number, filePath: string): Promise<void> {
+    try {
+      await invoke('export_validation_rule_set', { id, filePath });
+    } catch (error) {
+      throw new Error(`Failed to export validation rule set: ${error}`);
+    }
+  }
+
+  async importValidationRuleSet(filePath: string, activate = false): Promise<ValidationRuleSet> {
+    try {
+      const result: TauriValidationRuleSet = await invoke('import_validation_rule_set', { filePath, activate });
+      return this.mapTauriValidationRuleSet(result);
+    } catch (error) {
+      throw new Error(`Failed to import validation rule set: ${error}`);
+    }
+  }
+
+  private mapTauriValidationRuleSet(tauriRuleSet: TauriValidationRuleSet): ValidationRuleSet {
+    return {
+      id: tauriRuleSet.id,
+      name: tauriRuleSet.name,
+      description: tauriRuleSet.description,
+      isActive: tauriRuleSet.is_active,
+      rules: tauriRuleSet.rules,
+      createdAt: new Date(tauriRuleSet.created_at),
+      updatedAt: new Date(tauriRuleSet.updated_at),
+    };
+  }
+
   // Conduit operations
   async getConduits(): Promise<Conduit[]> {
     try {
@@ -1015,6 +1387,7 @@ export class TauriDatabaseService {
         type: data.type || null,
         size: data.size || null,
         internal_diameter: data.internalDiameter || null,
+        length: data.length || null,
         from_location: data.fromLocation || null,
         to_location: data.toLocation || null,
         notes: data.notes || null,
@@ -1033,6 +1406,7 @@ export class TauriDatabaseService {
         type: updates.type || null,
         size: updates.size || null,
         internal_diameter: updates.internalDiameter || null,
+        length: updates.length || null,
         from_location: updates.fromLocation || null,
         to_location: updates.toLocation || null,
         notes: updates.notes || null,
@@ -1061,6 +1435,7 @@ export class TauriDatabaseService {
       type: tauriConduit.type as ConduitType,
       size: tauriConduit.size,
       internalDiameter: tauriConduit.internal_diameter,
+      length: tauriConduit.length,
       fillPercentage: tauriConduit.fill_percentage,
       maxFillPercentage: tauriConduit.max_fill_percentage,
       fromLocation: tauriConduit.from_location,
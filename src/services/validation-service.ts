@@ -4,9 +4,27 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import { ValidationResult, ValidationSummary, ValidationStatus, ValidationSeverity, ValidationType } from '../types/validation';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { ValidationResult, ValidationSummary, ValidationStatus, ValidationSeverity, ValidationType, ValidationOverride, EntityType, CableValidationUpdate } from '../types/validation';
 import { Cable, IOPoint, Load, Tray, Conduit } from '../types';
 
+interface TauriValidationOverride {
+  id?: number;
+  cable_id?: number | null;
+  cable_tag: string;
+  validation_type: ValidationType;
+  field?: string | null;
+  justification: string;
+  user_name?: string | null;
+  created_at: string;
+  revoked_at?: string | null;
+}
+
+interface TauriCableValidationUpdate {
+  cable_id: number;
+  results: ValidationResult[];
+}
+
 export class ValidationService {
   /**
    * Validate all cables in the current project
@@ -36,6 +54,19 @@ export class ValidationService {
     }
   }
 
+  /**
+   * Subscribe to the results re-evaluated after each cable create, update or delete.
+   * Only the edited cable, cables sharing its tag and its route neighbours are included.
+   */
+  async onValidationUpdated(callback: (updates: CableValidationUpdate[]) => void): Promise<UnlistenFn> {
+    return listen<TauriCableValidationUpdate[]>('validation-updated', event => {
+      callback(event.payload.map(update => ({
+        cableId: update.cable_id,
+        results: update.results
+      })));
+    });
+  }
+
   /**
    * Check if a cable tag is duplicated
    */
@@ -65,6 +96,71 @@ export class ValidationService {
     }
   }
 
+  /**
+   * Accept a validation result with a justification; it stays suppressed until the cable changes
+   */
+  async overrideValidationResult(
+    result: ValidationResult,
+    justification: string,
+    userName?: string
+  ): Promise<ValidationOverride> {
+    try {
+      const override = await invoke<TauriValidationOverride>('override_validation_result', {
+        overrideData: {
+          cable_id: result.cableId,
+          validation_type: result.validationType,
+          field: result.field ?? null,
+          justification,
+          user_name: userName ?? null
+        }
+      });
+      return this.mapOverride(override);
+    } catch (error) {
+      console.error('Failed to override validation result:', error);
+      throw new Error('Validation override failed: ' + error);
+    }
+  }
+
+  /**
+   * All overrides in the project, including revoked and expired ones
+   */
+  async getValidationOverrides(): Promise<ValidationOverride[]> {
+    try {
+      const overrides = await invoke<TauriValidationOverride[]>('get_validation_overrides');
+      return overrides.map(override => this.mapOverride(override));
+    } catch (error) {
+      console.error('Failed to get validation overrides:', error);
+      throw new Error('Loading validation overrides failed: ' + error);
+    }
+  }
+
+  /**
+   * Withdraw an override; it is kept for the audit trail
+   */
+  async revokeValidationOverride(id: number): Promise<ValidationOverride> {
+    try {
+      const override = await invoke<TauriValidationOverride>('revoke_validation_override', { id });
+      return this.mapOverride(override);
+    } catch (error) {
+      console.error('Failed to revoke validation override:', error);
+      throw new Error('Revoking validation override failed: ' + error);
+    }
+  }
+
+  private mapOverride(override: TauriValidationOverride): ValidationOverride {
+    return {
+      id: override.id,
+      cableId: override.cable_id ?? undefined,
+      cableTag: override.cable_tag,
+      validationType: override.validation_type,
+      field: override.field ?? undefined,
+      justification: override.justification,
+      userName: override.user_name ?? undefined,
+      createdAt: new Date(override.created_at),
+      revokedAt: override.revoked_at ? new Date(override.revoked_at) : undefined
+    };
+  }
+
   /**
    * Get validation status for a cable (used for real-time UI updates)
    */
@@ -74,7 +170,7 @@ export class ValidationService {
     }
 
     try {
-      const results = await this.validateCable(cable.id);
+      const results = (await this.validateCable(cable.id)).filter(r => !r.suppressed);
       const errorCount = results.filter(r => r.severity === ValidationSeverity.Error).length;
       const warningCount = results.filter(r => r.severity === ValidationSeverity.Warning).length;
       
@@ -166,12 +262,16 @@ export class ValidationService {
     for (const ioPoint of ioPoints) {
       if (!ioPoint.tag || ioPoint.tag.trim() === '') {
         results.push({
-          cableTag: ioPoint.tag || 'Unnamed',
+          entityType: EntityType.IOPoint,
+          entityId: ioPoint.id,
+          entityTag: ioPoint.tag || 'Unnamed',
+          cableTag: '',
           severity: ValidationSeverity.Error,
           validationType: ValidationType.Required,
           message: 'I/O Point tag is required',
           field: 'tag',
-          overrideAllowed: false
+          overrideAllowed: false,
+          suppressed: false
         });
       }
     }
@@ -188,12 +288,16 @@ export class ValidationService {
     for (const load of loads) {
       if (!load.tag || load.tag.trim() === '') {
         results.push({
-          cableTag: load.tag || 'Unnamed',
+          entityType: EntityType.Load,
+          entityId: load.id,
+          entityTag: load.tag || 'Unnamed',
+          cableTag: '',
           severity: ValidationSeverity.Error,
           validationType: ValidationType.Required,
           message: 'Load tag is required',
           field: 'tag',
-          overrideAllowed: false
+          overrideAllowed: false,
+          suppressed: false
         });
       }
     }
@@ -210,23 +314,31 @@ export class ValidationService {
     for (const tray of trays) {
       if (!tray.tag || tray.tag.trim() === '') {
         results.push({
-          cableTag: tray.tag || 'Unnamed',
+          entityType: EntityType.Tray,
+          entityId: tray.id,
+          entityTag: tray.tag || 'Unnamed',
+          cableTag: '',
           severity: ValidationSeverity.Error,
           validationType: ValidationType.Required,
           message: 'Tray tag is required',
           field: 'tag',
-          overrideAllowed: false
+          overrideAllowed: false,
+          suppressed: false
         });
       }
       
       if (tray.fillPercentage > tray.maxFillPercentage) {
         results.push({
-          cableTag: tray.tag,
+          entityType: EntityType.Tray,
+          entityId: tray.id,
+          entityTag: tray.tag,
+          cableTag: '',
           severity: ValidationSeverity.Warning,
           validationType: ValidationType.Capacity,
           message: `Tray fill percentage (${tray.fillPercentage}%) exceeds maximum (${tray.maxFillPercentage}%)`,
           field: 'fillPercentage',
-          overrideAllowed: true
+          overrideAllowed: true,
+          suppressed: false
         });
       }
     }
@@ -243,23 +355,31 @@ export class ValidationService {
     for (const conduit of conduits) {
       if (!conduit.tag || conduit.tag.trim() === '') {
         results.push({
-          cableTag: conduit.tag || 'Unnamed',
+          entityType: EntityType.Conduit,
+          entityId: conduit.id,
+          entityTag: conduit.tag || 'Unnamed',
+          cableTag: '',
           severity: ValidationSeverity.Error,
           validationType: ValidationType.Required,
           message: 'Conduit tag is required',
           field: 'tag',
-          overrideAllowed: false
+          overrideAllowed: false,
+          suppressed: false
         });
       }
       
       if (conduit.fillPercentage > conduit.maxFillPercentage) {
         results.push({
-          cableTag: conduit.tag,
+          entityType: EntityType.Conduit,
+          entityId: conduit.id,
+          entityTag: conduit.tag,
+          cableTag: '',
           severity: ValidationSeverity.Warning,
           validationType: ValidationType.Capacity,
           message: `Conduit fill percentage (${conduit.fillPercentage}%) exceeds maximum (${conduit.maxFillPercentage}%)`,
           field: 'fillPercentage',
-          overrideAllowed: true
+          overrideAllowed: true,
+          suppressed: false
         });
       }
     }
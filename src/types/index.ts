@@ -41,6 +41,7 @@ export interface Cable {
   route?: string;
   trayId?: number; // Reference to assigned tray
   conduitId?: number; // Reference to assigned conduit
+  parentCableId?: number; // Upstream feeder this cable is supplied from
   
   // Physical properties
   manufacturer?: string;
@@ -104,6 +105,7 @@ export interface Conduit {
   type?: ConduitType;
   size?: string;
   internalDiameter?: number; // mm
+  length?: number; // meters
   fillPercentage: number;
   maxFillPercentage: number;
   
@@ -148,6 +150,38 @@ export interface Tray {
   updatedAt: Date;
 }
 
+// One conduit or tray along a cable's route; exactly one of conduitId and trayId is set
+export interface CableRouteSegment {
+  id?: number;
+  cableId: number;
+  sequence: number; // 1-based position along the route
+  conduitId?: number;
+  trayId?: number;
+  racewayTag: string;
+}
+
+// Allowances added to the raceway lengths when calculating cable length, in meters
+export interface CableLengthAllowances {
+  dropRise: number; // at each end
+  terminationMakeup: number; // at each end
+  elevationChange: number; // per change in tray elevation, on top of the height
+}
+
+// Lengths in ft, like the cable's entered length
+export interface CableLengthReport {
+  cableId: number;
+  cableTag: string;
+  racewayLength: number;
+  dropRiseAllowance: number;
+  terminationAllowance: number;
+  elevationAllowance: number;
+  sparePercentage: number;
+  calculatedLength?: number; // undefined when a raceway on the route has no length
+  enteredLength?: number;
+  difference?: number; // calculated minus entered
+  missingLengths: string[];
+}
+
 export interface Load {
   id?: number;
   tag: string;
@@ -157,10 +191,12 @@ export interface Load {
   powerKw?: number;
   powerHp?: number;
   voltage?: number;
+  phases?: 1 | 3;
   current?: number;
   powerFactor?: number;
   efficiency?: number;
   demandFactor?: number;
+  continuous?: boolean;
   startingCurrentMultiplier?: number;
   connectedLoadKw?: number;
   demandLoadKw?: number;
@@ -201,6 +237,28 @@ export enum ProtectionType {
   Combined = 'Combined'
 }
 
+// NEC Table 430.52 device types
+export enum ProtectiveDeviceType {
+  NonTimeDelayFuse = 'NonTimeDelayFuse',
+  DualElementFuse = 'DualElementFuse',
+  InstantaneousTripBreaker = 'InstantaneousTripBreaker',
+  InverseTimeBreaker = 'InverseTimeBreaker'
+}
+
+export interface ProtectiveDevice {
+  id?: number;
+  tag: string;
+  deviceType: ProtectiveDeviceType;
+  frameRating?: number;
+  tripRating: number;
+  tripCurve?: string;
+  loadId?: number;
+  cableId?: number;
+  notes?: string;
+  createdAt: Date;
+  updatedAt: Date;
+}
+
 
 // Enums
 export enum CableFunction {
@@ -543,6 +601,7 @@ export interface ConduitFormData {
   type?: ConduitType;
   size?: string;
   internalDiameter?: number;
+  length?: number;
   fromLocation?: string;
   toLocation?: string;
   notes?: string;
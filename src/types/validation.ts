@@ -16,19 +16,37 @@ export enum ValidationType {
   Required = 'Required',
   InvalidValue = 'InvalidValue',
   NecCompliance = 'NecCompliance',
-  Capacity = 'Capacity'
+  ShortCircuitWithstand = 'ShortCircuitWithstand',
+  GroundingConductor = 'GroundingConductor',
+  OvercurrentProtection = 'OvercurrentProtection',
+  MotorCircuit = 'MotorCircuit',
+  Capacity = 'Capacity',
+  IoAddressConflict = 'IoAddressConflict',
+  MissingReference = 'MissingReference'
+}
+
+export enum EntityType {
+  Cable = 'Cable',
+  IOPoint = 'IOPoint',
+  Load = 'Load',
+  Conduit = 'Conduit',
+  Tray = 'Tray',
+  ProtectiveDevice = 'ProtectiveDevice'
 }
 
 export interface ValidationResult {
+  entityType: EntityType;
   entityId?: number;
-  cableId?: number; // Keep for backwards compatibility
-  cableTag: string; // This could be renamed to entityTag in future
+  entityTag: string;
+  cableId?: number; // Cable involved, if any; overrides are recorded against it
+  cableTag: string; // Empty when no cable is involved
   severity: ValidationSeverity;
   validationType: ValidationType;
   message: string;
   field?: string;
   suggestedFix?: string;
   overrideAllowed: boolean;
+  suppressed: boolean; // Covered by an unexpired override; not counted by severity
 }
 
 export interface ValidationSummary {
@@ -36,12 +54,67 @@ export interface ValidationSummary {
   errorCount: number;
   warningCount: number;
   infoCount: number;
+  suppressedCount: number;
   results: ValidationResult[];
   validationTime: string; // ISO date string from chrono::DateTime
 }
 
+export interface ValidationOverride {
+  id?: number;
+  cableId?: number; // Unset once the cable is deleted
+  cableTag: string;
+  validationType: ValidationType;
+  field?: string;
+  justification: string;
+  userName?: string;
+  createdAt: Date;
+  revokedAt?: Date; // Kept for the audit trail once revoked
+}
+
+// Pushed after each cable edit; replaces the cable's previous cable-list results
+export interface CableValidationUpdate {
+  cableId: number;
+  results: ValidationResult[]; // Empty once the cable is deleted
+}
+
 export interface ValidationStatus {
   hasIssues: boolean;
   errorCount: number;
   warningCount: number;
-}
\ No newline at end of file
+}
+// Rule set definitions keep the field names of the exported JSON files
+export interface SegregationClassRule {
+  name: string;
+  description?: string | null;
+  intrinsically_safe?: boolean; // kept apart from every non-IS cable (NEC 504.30)
+}
+
+export interface IncompatibilityRule {
+  first: string;
+  second: string;
+  severity: ValidationSeverity;
+  override_allowed: boolean;
+  reference?: string | null;
+}
+
+export interface VoltageBand {
+  name: string;
+  max_voltage?: number | null; // V, inclusive; null = no upper limit
+}
+
+export interface ValidationRules {
+  segregation_classes: SegregationClassRule[];
+  class_incompatibilities: IncompatibilityRule[];
+  voltage_bands: VoltageBand[];
+  voltage_band_incompatibilities: IncompatibilityRule[];
+}
+
+export interface ValidationRuleSet {
+  id?: number;
+  name: string;
+  description?: string;
+  isActive: boolean;
+  rules: ValidationRules;
+  createdAt: Date;
+  updatedAt: Date;
+}